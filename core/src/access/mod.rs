@@ -0,0 +1,94 @@
+// src/access/mod.rs
+//! Listes d'autorisation et de blocage des pairs, appliquées par le swarm
+//! (`allow_block_list`) et à l'ingestion des annonces.
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+
+use crate::identity::keystore::write_private_file;
+
+/// Section `access:` de la configuration, et état persisté dans `access.json`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessLists {
+    /// N'accepter que les pairs de `allow` (changement pris en compte au redémarrage)
+    pub allowlist_only: bool,
+    pub allow: BTreeSet<String>,
+    pub deny: BTreeSet<String>,
+}
+
+/// Modification d'une liste, depuis l'API ou la CLI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", content = "peer_id", rename_all = "snake_case")]
+pub enum AccessUpdate {
+    Allow(String),
+    Unallow(String),
+    Deny(String),
+    Undeny(String),
+}
+
+impl AccessUpdate {
+    pub fn peer_id(&self) -> &str {
+        match self {
+            AccessUpdate::Allow(p) | AccessUpdate::Unallow(p) | AccessUpdate::Deny(p) | AccessUpdate::Undeny(p) => p,
+        }
+    }
+}
+
+impl AccessLists {
+    /// Le pair peut-il se connecter et figurer au registre ?
+    pub fn permits(&self, peer_id: &str) -> bool {
+        !self.deny.contains(peer_id) && (!self.allowlist_only || self.allow.contains(peer_id))
+    }
+
+    /// Applique une modification; renvoie `true` si les listes ont changé
+    pub fn apply(&mut self, update: &AccessUpdate) -> Result<bool> {
+        parse_peer(update.peer_id())?;
+        Ok(match update {
+            AccessUpdate::Allow(p) => self.allow.insert(p.clone()),
+            AccessUpdate::Unallow(p) => self.allow.remove(p),
+            AccessUpdate::Deny(p) => self.deny.insert(p.clone()),
+            AccessUpdate::Undeny(p) => self.deny.remove(p),
+        })
+    }
+
+    /// Vérifie que chaque entrée est un PeerId valide
+    pub fn validate(&self) -> Result<()> {
+        for peer in self.allow.iter().chain(self.deny.iter()) {
+            parse_peer(peer)?;
+        }
+        Ok(())
+    }
+
+    pub fn allowed_peers(&self) -> Vec<PeerId> {
+        self.allow.iter().filter_map(|p| p.parse().ok()).collect()
+    }
+
+    pub fn denied_peers(&self) -> Vec<PeerId> {
+        self.deny.iter().filter_map(|p| p.parse().ok()).collect()
+    }
+
+    /// Recharge l'état persisté (`None` si le fichier n'existe pas)
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        let lists: AccessLists = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {:?}", path))?;
+        lists.validate()?;
+        Ok(Some(lists))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        write_private_file(path, serde_json::to_string_pretty(self)?.as_bytes())
+    }
+}
+
+fn parse_peer(peer: &str) -> Result<PeerId> {
+    peer.parse().map_err(|e| anyhow!("Invalid PeerId {:?}: {:?}", peer, e))
+}
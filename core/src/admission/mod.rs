@@ -0,0 +1,102 @@
+// src/admission/mod.rs
+//! Coût d'admission d'une identité: une preuve de travail liée au PeerId et au mesh.
+//!
+//! Générer une clé ed25519 ne coûte rien; exiger `difficulty` bits nuls en tête de
+//! `sha256(domaine, mesh, PeerId, nonce)` rend coûteuse la création de milliers d'identités.
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::identity::keystore::write_private_file;
+
+const POW_DOMAIN: &str = "cortex-admission/v1";
+/// Au-delà, le calcul de la preuve n'est plus raisonnable sur un CPU
+pub const MAX_DIFFICULTY: u8 = 40;
+
+/// Preuve de travail présentée dans les annonces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdmissionProof {
+    pub nonce: u64,
+}
+
+impl AdmissionProof {
+    /// Cherche un nonce atteignant `difficulty` bits nuls (coût moyen: 2^difficulty hachages)
+    pub fn solve(mesh: &str, peer_id: &PeerId, difficulty: u8) -> Self {
+        let prefix = hash_prefix(mesh, peer_id);
+        let nonce = (0..u64::MAX)
+            .find(|nonce| leading_zero_bits(&digest(&prefix, *nonce)) >= u32::from(difficulty))
+            .unwrap_or(u64::MAX);
+        AdmissionProof { nonce }
+    }
+
+    /// Nombre de bits nuls effectivement atteints par ce nonce
+    pub fn work(&self, mesh: &str, peer_id: &PeerId) -> u32 {
+        leading_zero_bits(&digest(&hash_prefix(mesh, peer_id), self.nonce))
+    }
+
+    pub fn verify(&self, mesh: &str, peer_id: &PeerId, difficulty: u8) -> bool {
+        self.work(mesh, peer_id) >= u32::from(difficulty)
+    }
+}
+
+/// Preuve mise en cache dans le profil, pour ne pas la recalculer à chaque démarrage
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedProof {
+    mesh: String,
+    peer_id: String,
+    nonce: u64,
+}
+
+/// Relit la preuve en cache si elle convient toujours, sinon la calcule et l'enregistre
+pub fn load_or_solve(path: &Path, mesh: &str, peer_id: &PeerId, difficulty: u8) -> Result<AdmissionProof> {
+    if let Ok(content) = fs::read_to_string(path) {
+        if let Ok(cached) = serde_json::from_str::<CachedProof>(&content) {
+            let proof = AdmissionProof { nonce: cached.nonce };
+            if cached.mesh == mesh && cached.peer_id == peer_id.to_string() && proof.verify(mesh, peer_id, difficulty) {
+                return Ok(proof);
+            }
+        }
+    }
+
+    println!("⛏️ Calcul de la preuve d'admission (difficulté {})...", difficulty);
+    let proof = AdmissionProof::solve(mesh, peer_id, difficulty);
+    let cached = CachedProof {
+        mesh: mesh.to_string(),
+        peer_id: peer_id.to_string(),
+        nonce: proof.nonce,
+    };
+    write_private_file(path, serde_json::to_string_pretty(&cached)?.as_bytes())
+        .with_context(|| format!("Failed to write {:?}", path))?;
+    Ok(proof)
+}
+
+fn hash_prefix(mesh: &str, peer_id: &PeerId) -> Sha256 {
+    let mut hasher = Sha256::new();
+    hasher.update(POW_DOMAIN.as_bytes());
+    hasher.update([0]);
+    hasher.update(mesh.as_bytes());
+    hasher.update([0]);
+    hasher.update(peer_id.to_bytes());
+    hasher
+}
+
+fn digest(prefix: &Sha256, nonce: u64) -> [u8; 32] {
+    let mut hasher = prefix.clone();
+    hasher.update(nonce.to_le_bytes());
+    hasher.finalize().into()
+}
+
+fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    bits
+}
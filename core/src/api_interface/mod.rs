@@ -7,18 +7,30 @@ use anyhow::Error as AnyhowError;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use std::convert::Infallible;
+use std::net::SocketAddr;
+use libp2p::PeerId;
+use warp::http::StatusCode;
 
+use crate::access::AccessUpdate;
 use crate::communicator::{CommunicatorMessage, SharedCommunicator};
+use crate::executor::Tensor;
+use crate::metrics::NodeMetrics;
+use crate::pipeline::PipelineCoordinator;
+use crate::policy::{DataClass, EgressRequest, PolicyEngine};
+use crate::discovery::NodeHandle;
 use crate::registry::Registry;
+use crate::scheduler::{capacities, schedule, weigh_by_reputation, ModelManifest};
 
 #[derive(Debug)]
-struct ApiError(AnyhowError);
+struct ApiError(#[allow(dead_code)] AnyhowError);
 
 impl Reject for ApiError {}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiRequest {
     pub query: String,
+    /// PeerId des destinataires: seuls eux pourront lire la requête
+    pub recipients: Vec<String>,
     // Ajouter d'autres champs au besoin
 }
 
@@ -47,11 +59,24 @@ async fn handle_registry(registry: Arc<Mutex<Registry>>) -> Result<impl warp::Re
     ))
 }
 
+// Filtre qui injecte les métriques partagées
+fn with_metrics(metrics: Arc<NodeMetrics>)
+    -> impl Filter<Extract = (Arc<NodeMetrics>,), Error = Infallible> + Clone
+{
+    warp::any().map(move || metrics.clone())
+}
+
+/// Endpoint pour obtenir les compteurs de connexions (dont les refus)
+async fn handle_metrics(metrics: Arc<NodeMetrics>) -> Result<impl warp::Reply, Infallible> {
+    Ok(warp::reply::json(&metrics.snapshot()))
+}
+
 /// Lance l'API et attend des requêtes sur l'endpoint /send
 pub async fn run_api_server(
     port: u16, 
     communicator: SharedCommunicator,
-    registry: Arc<Mutex<Registry>>
+    registry: Arc<Mutex<Registry>>,
+    metrics: Arc<NodeMetrics>,
 ) {
     // La route "send" accepte des requêtes POST avec un JSON correspondant à ApiRequest
     let send_route = warp::path("send")
@@ -66,8 +91,14 @@ pub async fn run_api_server(
         .and(with_registry(registry))
         .and_then(handle_registry);
 
+    // Route pour consulter les métriques
+    let metrics_route = warp::path("metrics")
+        .and(warp::get())
+        .and(with_metrics(metrics))
+        .and_then(handle_metrics);
+
     // Combinaison des routes
-    let routes = send_route.or(registry_route);
+    let routes = send_route.or(registry_route).or(metrics_route);
 
     println!("Lancement du serveur API sur le port {}", port);
     warp::serve(routes).run(([0, 0, 0, 0], port)).await;
@@ -91,9 +122,11 @@ async fn handle_send(req: ApiRequest, communicator: SharedCommunicator)
         timestamp: Utc::now().timestamp_millis() as u64,
     };
 
+    let recipients = parse_recipients(&req.recipients).map_err(|e| warp::reject::custom(ApiError(e)))?;
+
     // On verrouille le communicator pour envoyer le message
     let mut comm = communicator.lock().await;
-    match comm.send_message(&message) {
+    match comm.send_message(&message, &recipients) {
         Ok(_) => Ok(warp::reply::json(&ApiResponse {
             response: "Message envoyé avec succès".to_string(),
         })),
@@ -102,4 +135,268 @@ async fn handle_send(req: ApiRequest, communicator: SharedCommunicator)
             Err(warp::reject::custom(ApiError(e)))
         }
     }
-}
\ No newline at end of file
+}
+
+/// Destinataires d'une requête, sous forme de PeerId
+fn parse_recipients(recipients: &[String]) -> anyhow::Result<Vec<PeerId>> {
+    recipients.iter()
+        .map(|p| p.parse().map_err(|e| anyhow::anyhow!("Invalid PeerId {:?}: {:?}", p, e)))
+        .collect()
+}
+
+// Filtre qui injecte le handle du nœud
+fn with_node(handle: NodeHandle)
+    -> impl Filter<Extract = (NodeHandle,), Error = Infallible> + Clone
+{
+    warp::any().map(move || handle.clone())
+}
+
+/// Endpoint pour consulter le registry d'un nœud en cours d'exécution
+async fn handle_node_registry(handle: NodeHandle) -> Result<impl warp::Reply, Infallible> {
+    let snapshot = handle.registry().lock().map(|reg| reg.snapshot_json()).unwrap_or_default();
+    Ok(warp::reply::with_header(snapshot, "content-type", "application/json"))
+}
+
+/// Endpoint pour consulter les listes d'accès
+async fn handle_access_get(handle: NodeHandle) -> Result<impl warp::Reply, warp::Rejection> {
+    match handle.access_lists().await {
+        Ok(lists) => Ok(warp::reply::json(&lists)),
+        Err(e) => Err(warp::reject::custom(ApiError(e))),
+    }
+}
+
+/// Endpoint pour modifier les listes d'accès (effet immédiat)
+async fn handle_access_update(update: AccessUpdate, handle: NodeHandle) -> Result<impl warp::Reply, Infallible> {
+    Ok(match handle.update_access(update).await {
+        Ok(lists) => warp::reply::with_status(warp::reply::json(&lists), StatusCode::OK),
+        Err(e) => warp::reply::with_status(
+            warp::reply::json(&ApiResponse { response: e.to_string() }),
+            StatusCode::BAD_REQUEST,
+        ),
+    })
+}
+
+// Filtre qui injecte le moteur de politique
+fn with_policy(policy: Arc<PolicyEngine>)
+    -> impl Filter<Extract = (Arc<PolicyEngine>,), Error = Infallible> + Clone
+{
+    warp::any().map(move || policy.clone())
+}
+
+/// Endpoint pour consulter la politique chargée depuis la configuration
+async fn handle_policy(policy: Arc<PolicyEngine>) -> Result<impl warp::Reply, Infallible> {
+    Ok(warp::reply::json(policy.config()))
+}
+
+/// Endpoint pour simuler une décision, sans l'enregistrer
+async fn handle_policy_evaluate(request: EgressRequest, policy: Arc<PolicyEngine>) -> Result<impl warp::Reply, Infallible> {
+    Ok(warp::reply::json(&policy.evaluate(&request)))
+}
+
+/// Endpoint pour consulter les décisions récentes
+async fn handle_policy_decisions(policy: Arc<PolicyEngine>) -> Result<impl warp::Reply, Infallible> {
+    Ok(warp::reply::json(&policy.decisions()))
+}
+
+/// Endpoint listant les shards chargés et leur mémoire
+async fn handle_shards(handle: NodeHandle) -> Result<impl warp::Reply, Infallible> {
+    Ok(warp::reply::json(&handle.shards().loaded()))
+}
+
+/// Endpoint d'exécution locale d'un shard sur un tenseur d'activations
+async fn handle_forward(shard_id: String, input: Tensor, handle: NodeHandle) -> Result<impl warp::Reply, Infallible> {
+    Ok(match handle.execute(&shard_id, input).await {
+        Ok(output) => warp::reply::with_status(warp::reply::json(&output), StatusCode::OK),
+        Err(e) => warp::reply::with_status(
+            warp::reply::json(&ApiResponse { response: e.to_string() }),
+            StatusCode::BAD_REQUEST,
+        ),
+    })
+}
+
+/// Génération répartie sur les nœuds qui hébergent les couches du modèle
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GenerateRequest {
+    pub model: String,
+    pub num_layers: usize,
+    pub prompt: Vec<u32>,
+    pub max_new_tokens: usize,
+    #[serde(default)]
+    pub micro_batch: Option<usize>,
+}
+
+/// Endpoint de génération en pipeline, piloté depuis ce nœud selon le plan signé en vigueur
+/// (à défaut, d'après le registre)
+async fn handle_generate(req: GenerateRequest, handle: NodeHandle) -> Result<impl warp::Reply, Infallible> {
+    let generation = match PipelineCoordinator::for_model(handle, &req.model, req.num_layers) {
+        Ok(coordinator) => {
+            let coordinator = match req.micro_batch {
+                Some(tokens) => coordinator.with_micro_batch(tokens),
+                None => coordinator,
+            };
+            coordinator.generate(&req.prompt, req.max_new_tokens).await
+        },
+        Err(e) => Err(e),
+    };
+    Ok(match generation {
+        Ok(generation) => warp::reply::with_status(warp::reply::json(&generation), StatusCode::OK),
+        Err(e) => warp::reply::with_status(
+            warp::reply::json(&ApiResponse { response: format!("{:#}", e) }),
+            StatusCode::BAD_REQUEST,
+        ),
+    })
+}
+
+/// Endpoint de réputation: scores des pairs observés par ce nœud
+async fn handle_reputation(handle: NodeHandle) -> Result<impl warp::Reply, Infallible> {
+    Ok(warp::reply::json(&handle.reputation().scores()))
+}
+
+/// Endpoint des attestations de réputation reçues des autres nœuds
+async fn handle_attestations(handle: NodeHandle) -> Result<impl warp::Reply, Infallible> {
+    Ok(warp::reply::json(&handle.reputation().attestations()))
+}
+
+/// Endpoint de planification: calcule l'affectation d'un modèle d'après le registre et la
+/// réputation des nœuds, puis la signe et la diffuse au mesh
+async fn handle_schedule(manifest: ModelManifest, handle: NodeHandle) -> Result<impl warp::Reply, Infallible> {
+    let nodes = match handle.registry().lock() {
+        Ok(reg) => Ok(capacities(&reg, manifest.trust_domain.as_deref())),
+        Err(_) => Err(anyhow::anyhow!("Registry lock poisoned")),
+    };
+    let plan = nodes.and_then(|nodes| schedule(&manifest, &weigh_by_reputation(nodes, &handle.reputation())));
+    let signed = match plan {
+        Ok(plan) => handle.publish_plan(plan).await,
+        Err(e) => Err(e),
+    };
+    Ok(match signed {
+        Ok(signed) => warp::reply::with_status(warp::reply::json(&signed), StatusCode::OK),
+        Err(e) => warp::reply::with_status(
+            warp::reply::json(&ApiResponse { response: format!("{:#}", e) }),
+            StatusCode::BAD_REQUEST,
+        ),
+    })
+}
+
+/// Endpoint d'envoi d'une requête scellée pour ses destinataires
+async fn handle_node_send(req: ApiRequest, handle: NodeHandle) -> Result<impl warp::Reply, Infallible> {
+    let message = CommunicatorMessage {
+        sender: "API_Interface".to_string(),
+        payload: req.query,
+        timestamp: Utc::now().timestamp_millis() as u64,
+    };
+    let sent = match parse_recipients(&req.recipients) {
+        Ok(recipients) => handle.send_sealed(DataClass::Prompt, recipients, message).await,
+        Err(e) => Err(e),
+    };
+    Ok(match sent {
+        Ok(()) => warp::reply::with_status(
+            warp::reply::json(&ApiResponse { response: "Message envoyé avec succès".to_string() }),
+            StatusCode::OK,
+        ),
+        Err(e) => warp::reply::with_status(
+            warp::reply::json(&ApiResponse { response: e.to_string() }),
+            StatusCode::BAD_REQUEST,
+        ),
+    })
+}
+
+/// Lance l'API d'un nœud: /send, /registry, /metrics, /access (GET pour lire, POST pour modifier)
+/// /policy (configuration, /policy/evaluate, /policy/decisions), /shards (/shards/<id>/forward)
+/// /pipeline/generate, /schedule et /reputation (/reputation/attestations)
+pub async fn run_node_api(addr: SocketAddr, handle: NodeHandle) {
+    let send_route = warp::path("send")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_node(handle.clone()))
+        .and_then(handle_node_send);
+
+    let registry_route = warp::path("registry")
+        .and(warp::get())
+        .and(with_node(handle.clone()))
+        .and_then(handle_node_registry);
+
+    let metrics_route = warp::path("metrics")
+        .and(warp::get())
+        .and(with_metrics(handle.metrics()))
+        .and_then(handle_metrics);
+
+    let access_get = warp::path("access")
+        .and(warp::get())
+        .and(with_node(handle.clone()))
+        .and_then(handle_access_get);
+
+    let access_post = warp::path("access")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_node(handle.clone()))
+        .and_then(handle_access_update);
+
+    let shards_route = warp::path!("shards")
+        .and(warp::get())
+        .and(with_node(handle.clone()))
+        .and_then(handle_shards);
+
+    let forward_route = warp::path!("shards" / String / "forward")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_node(handle.clone()))
+        .and_then(handle_forward);
+
+    let generate_route = warp::path!("pipeline" / "generate")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_node(handle.clone()))
+        .and_then(handle_generate);
+
+    let schedule_route = warp::path!("schedule")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_node(handle.clone()))
+        .and_then(handle_schedule);
+
+    let reputation_route = warp::path!("reputation")
+        .and(warp::get())
+        .and(with_node(handle.clone()))
+        .and_then(handle_reputation);
+
+    let attestations_route = warp::path!("reputation" / "attestations")
+        .and(warp::get())
+        .and(with_node(handle.clone()))
+        .and_then(handle_attestations);
+
+    let policy = handle.policy();
+    let policy_route = warp::path!("policy")
+        .and(warp::get())
+        .and(with_policy(policy.clone()))
+        .and_then(handle_policy);
+
+    let policy_evaluate = warp::path!("policy" / "evaluate")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_policy(policy.clone()))
+        .and_then(handle_policy_evaluate);
+
+    let policy_decisions = warp::path!("policy" / "decisions")
+        .and(warp::get())
+        .and(with_policy(policy))
+        .and_then(handle_policy_decisions);
+
+    let routes = send_route
+        .or(registry_route)
+        .or(metrics_route)
+        .or(access_get)
+        .or(access_post)
+        .or(policy_route)
+        .or(policy_evaluate)
+        .or(policy_decisions)
+        .or(shards_route)
+        .or(forward_route)
+        .or(generate_route)
+        .or(schedule_route)
+        .or(reputation_route)
+        .or(attestations_route);
+
+    println!("🌐 API du nœud sur http://{}", addr);
+    warp::serve(routes).run(addr).await;
+}
@@ -0,0 +1,289 @@
+// src/audit/mod.rs
+//! Journal d'audit infalsifiable: chaque entrée est chaînée à la précédente par un hachage
+//! SHA-256 et signée par l'identité du nœud. Permet de prouver a posteriori quels pairs
+//! ont reçu quelles données, et ce qui a été exécuté pour le compte d'autres nœuds.
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use libp2p::identity::{ed25519, PeerId, PublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::envelope::ed25519_public_of;
+use crate::identity::keystore::create_private_dir;
+use crate::identity::succession::KeySuccession;
+use crate::policy::{DataClass, PolicyDecision};
+use crate::signing::unix_now;
+
+const AUDIT_DOMAIN: &str = "cortex-audit/v1";
+/// Nom du journal dans le répertoire d'audit
+pub const AUDIT_FILE: &str = "audit.jsonl";
+/// Hachage précédant la première entrée
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Événement consigné dans le journal
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// Données envoyées à un pair (taille et empreinte du contenu en clair)
+    Egress {
+        peer: String,
+        data: DataClass,
+        size: usize,
+        sha256: String,
+    },
+    /// Exécution réalisée pour le compte d'un pair
+    Execution {
+        peer: String,
+        shard_id: String,
+        size: usize,
+        sha256: String,
+    },
+    /// Décision de la politique de localité des données
+    Policy(PolicyDecision),
+}
+
+/// Entrée du journal, une par ligne JSON
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub timestamp: u64,
+    /// PeerId du nœud signataire (change après une rotation de clé)
+    pub signer: String,
+    pub prev_hash: String,
+    pub event: AuditEvent,
+    pub hash: String,
+    pub signature: String,
+}
+
+impl AuditEntry {
+    /// Hachage de l'entrée, sur tous les champs sauf `hash` et `signature`
+    fn compute_hash(&self) -> String {
+        let unsigned = AuditEntry {
+            hash: String::new(),
+            signature: String::new(),
+            ..self.clone()
+        };
+        let mut hasher = Sha256::new();
+        hasher.update(AUDIT_DOMAIN.as_bytes());
+        hasher.update([0]);
+        hasher.update(serde_json::to_vec(&unsigned).unwrap_or_default());
+        to_hex(&hasher.finalize())
+    }
+
+    fn verify_signature(&self) -> Result<()> {
+        let signer: PeerId = self.signer.parse().map_err(|e| anyhow!("Invalid signer PeerId: {:?}", e))?;
+        let key = ed25519_public_of(&signer)?;
+        if !key.verify(self.hash.as_bytes(), &STANDARD.decode(&self.signature)?) {
+            bail!("Invalid signature");
+        }
+        Ok(())
+    }
+}
+
+/// Fin de chaîne courante: prochain numéro et dernier hachage
+struct ChainHead {
+    next_seq: u64,
+    last_hash: String,
+}
+
+/// Journal d'audit en ajout seul, partagé entre la boucle du nœud et la politique
+pub struct AuditLog {
+    path: PathBuf,
+    identity: ed25519::Keypair,
+    signer: String,
+    head: Mutex<ChainHead>,
+}
+
+impl std::fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditLog").field("path", &self.path).finish()
+    }
+}
+
+impl AuditLog {
+    /// Ouvre (ou crée) le journal de `dir` et reprend la chaîne à sa dernière entrée
+    pub fn open(dir: &Path, identity: ed25519::Keypair) -> Result<Self> {
+        create_private_dir(dir)?;
+        let path = dir.join(AUDIT_FILE);
+        let head = match read_entries(&path)?.last() {
+            Some(last) => ChainHead {
+                next_seq: last.seq + 1,
+                last_hash: last.hash.clone(),
+            },
+            None => ChainHead {
+                next_seq: 0,
+                last_hash: GENESIS_HASH.to_string(),
+            },
+        };
+        let signer = PeerId::from(PublicKey::from(identity.public())).to_string();
+        Ok(AuditLog {
+            path,
+            identity,
+            signer,
+            head: Mutex::new(head),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Ajoute un événement signé à la fin de la chaîne
+    pub fn record(&self, event: AuditEvent) -> Result<AuditEntry> {
+        let mut head = self.head.lock().map_err(|_| anyhow!("Audit log lock poisoned"))?;
+        let mut entry = AuditEntry {
+            seq: head.next_seq,
+            timestamp: unix_now(),
+            signer: self.signer.clone(),
+            prev_hash: head.last_hash.clone(),
+            event,
+            hash: String::new(),
+            signature: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+        entry.signature = STANDARD.encode(self.identity.sign(entry.hash.as_bytes()));
+
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        let mut options = fs::OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&self.path).with_context(|| format!("Failed to open {:?}", self.path))?;
+        file.write_all(&line)?;
+
+        head.next_seq += 1;
+        head.last_hash = entry.hash.clone();
+        Ok(entry)
+    }
+
+    /// Consigne un envoi de données, avec l'empreinte du contenu
+    pub fn record_egress(&self, peer: &PeerId, data: DataClass, content: &[u8]) -> Result<AuditEntry> {
+        self.record(AuditEvent::Egress {
+            peer: peer.to_string(),
+            data,
+            size: content.len(),
+            sha256: sha256_hex(content),
+        })
+    }
+
+    /// Consigne une exécution pour le compte d'un pair, avec l'empreinte de l'entrée
+    pub fn record_execution(&self, peer: &PeerId, shard_id: &str, input: &[u8]) -> Result<AuditEntry> {
+        self.record(AuditEvent::Execution {
+            peer: peer.to_string(),
+            shard_id: shard_id.to_string(),
+            size: input.len(),
+            sha256: sha256_hex(input),
+        })
+    }
+}
+
+/// Résultat d'une vérification complète
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainReport {
+    pub entries: u64,
+    /// Dernier hachage: à noter hors du nœud pour détecter une troncature ultérieure
+    pub head_hash: String,
+    /// Signataires successifs (plusieurs après une rotation de clé)
+    pub signers: Vec<String>,
+}
+
+/// Vérifie la numérotation, le chaînage, les hachages et les signatures de tout le journal.
+///
+/// Le journal doit appartenir à `identity`: chaque changement de signataire doit être couvert par
+/// un certificat de succession de l'ancien signataire vers le nouveau, et le dernier signataire doit
+/// être `identity` ou y mener par ces certificats. Un journal re-signé par une autre clé est refusé.
+pub fn verify_chain(path: &Path, identity: &str, successions: &[KeySuccession]) -> Result<ChainReport> {
+    let successor_of = |old: &str| {
+        successions.iter()
+            .filter(|s| s.old_peer_id == old)
+            .find(|s| s.verify().is_ok())
+            .map(|s| s.new_peer_id.as_str())
+    };
+    let mut report = ChainReport {
+        entries: 0,
+        head_hash: GENESIS_HASH.to_string(),
+        signers: Vec::new(),
+    };
+    for entry in read_entries(path)? {
+        let at = format!("entry {}", report.entries);
+        if entry.seq != report.entries {
+            bail!("{}: sequence number {} (entry removed or reordered)", at, entry.seq);
+        }
+        if entry.prev_hash != report.head_hash {
+            bail!("{}: broken chain (previous entry modified or removed)", at);
+        }
+        if entry.compute_hash() != entry.hash {
+            bail!("{}: content does not match its hash", at);
+        }
+        entry.verify_signature().with_context(|| at.clone())?;
+        match report.signers.last() {
+            Some(previous) if *previous == entry.signer => {}
+            Some(previous) => {
+                if successor_of(previous) != Some(entry.signer.as_str()) {
+                    bail!("{}: signer changed from {} to {} without a key succession", at, previous, entry.signer);
+                }
+                report.signers.push(entry.signer.clone());
+            }
+            None => report.signers.push(entry.signer.clone()),
+        }
+        report.entries += 1;
+        report.head_hash = entry.hash;
+    }
+
+    // Le dernier signataire est le nœud, ou une de ses identités retirées depuis
+    if let Some(last) = report.signers.last() {
+        let mut current = last.as_str();
+        for _ in 0..successions.len() {
+            if current == identity {
+                break;
+            }
+            match successor_of(current) {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        if current != identity {
+            bail!("Audit log is signed by {}, not by this node ({})", last, identity);
+        }
+    }
+    Ok(report)
+}
+
+/// Entrées dont la date est dans `[since, until]` (bornes incluses, optionnelles)
+pub fn export_range(path: &Path, since: Option<u64>, until: Option<u64>) -> Result<Vec<AuditEntry>> {
+    Ok(read_entries(path)?
+        .into_iter()
+        .filter(|e| since.map_or(true, |s| e.timestamp >= s) && until.map_or(true, |u| e.timestamp <= u))
+        .collect())
+}
+
+/// Lit toutes les entrées (journal absent: aucune)
+pub fn read_entries(path: &Path) -> Result<Vec<AuditEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| serde_json::from_str(line).with_context(|| format!("Invalid audit entry on line {}", i + 1)))
+        .collect()
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -5,36 +5,67 @@ use libp2p::gossipsub::{
     IdentTopic,
     MessageAuthenticity,
 };
-use libp2p::identity::Keypair;
+use libp2p::identity::{ed25519, Keypair};
 use libp2p::PeerId;
 use serde::{Serialize, Deserialize};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use crate::envelope::SealedEnvelope;
 use crate::registry::{Registry, AnnounceMsg};
+use crate::topics::{Channel, TopicNamespace};
 
 pub type SharedCommunicator = Arc<Mutex<Communicator>>;
 
 /// Message standard pour la communication entre nœuds
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommunicatorMessage {
     pub sender: String,
     pub payload: String,
     pub timestamp: u64,
 }
 
+/// Message déchiffré, avec l'expéditeur authentifié par la signature de l'enveloppe
+#[derive(Debug, Clone)]
+pub struct InboundMessage {
+    pub from: PeerId,
+    pub message: CommunicatorMessage,
+}
+
+/// Scelle un message pour ses destinataires: rien n'est lisible en transit
+pub fn seal_message(
+    identity: &ed25519::Keypair,
+    recipients: &[PeerId],
+    msg: &CommunicatorMessage,
+) -> Result<SealedEnvelope> {
+    SealedEnvelope::seal(identity, recipients, &serde_json::to_vec(msg)?)
+}
+
+/// Ouvre une enveloppe reçue (`None` si elle est adressée à d'autres nœuds)
+pub fn open_message(identity: &ed25519::Keypair, envelope: &SealedEnvelope) -> Result<Option<InboundMessage>> {
+    match envelope.open(identity)? {
+        Some(opened) => Ok(Some(InboundMessage {
+            from: opened.sender,
+            message: serde_json::from_slice(&opened.payload)?,
+        })),
+        None => Ok(None),
+    }
+}
+
 /// Le Communicator encapsule la logique d’envoi et de réception de messages
 pub struct Communicator {
     pub gossipsub: Gossipsub,
     pub topic: IdentTopic,
+    identity: ed25519::Keypair,
 }
 
 impl Communicator {
     /// Crée un nouveau Communicator en initialisant gossipsub avec la clé et
-    /// en s’abonnant au topic dédié aux communications.
-    pub fn new(keypair: &Keypair) -> Result<Self> {
-        let _peer_id = PeerId::from(keypair.public());
+    /// en s’abonnant aux topics de communication de l'espace de noms.
+    pub fn new(keypair: &Keypair, namespace: &TopicNamespace) -> Result<Self> {
+        let identity = keypair.clone().try_into_ed25519()
+            .map_err(|_| anyhow!("Sealed messages need an ed25519 identity"))?;
         
         // Configuration de Gossipsub via Config (v0.53)
         let gossipsub_config = GossipsubConfig::default();
@@ -43,17 +74,20 @@ impl Communicator {
             gossipsub_config
         ).expect("Erreur lors de la création de Gossipsub");
         
-        // Définir un topic pour la communication (par exemple "cortex/communicator")
-        let topic = IdentTopic::new("cortex/communicator");
-        gossipsub.subscribe(&topic)?;
+        // Topic courant (ex. "cortex/default/v2/communicator") + versions encore acceptées
+        let topic = namespace.communicator();
+        for t in namespace.subscriptions(Channel::Communicator) {
+            gossipsub.subscribe(&t)?;
+        }
         
-        Ok(Communicator { gossipsub, topic })
+        Ok(Communicator { gossipsub, topic, identity })
     }
 
     /// Envoie un message via Gossipsub.
-    /// La fonction sérialise le message en JSON et le publie sur le topic défini.
-    pub fn send_message(&mut self, msg: &CommunicatorMessage) -> Result<()> {
-        let json = serde_json::to_vec(msg)?;
+    /// Le message est scellé pour `recipients` puis publié sur le topic défini.
+    pub fn send_message(&mut self, msg: &CommunicatorMessage, recipients: &[PeerId]) -> Result<()> {
+        let envelope = seal_message(&self.identity, recipients, msg)?;
+        let json = serde_json::to_vec(&envelope)?;
         self.gossipsub.publish(self.topic.clone(), json)?;
         Ok(())
     }
@@ -66,9 +100,18 @@ impl Communicator {
                 println!("Message gossipsub reçu de {:?}, taille: {} octets", 
                         message.source, message.data.len());
                 
-                // Essayer de désérialiser le message comme message du communicator
-                if let Ok(comm_msg) = serde_json::from_slice::<CommunicatorMessage>(&message.data) {
-                    println!("Message reçu de {}: {}", comm_msg.sender, comm_msg.payload);
+                // Essayer de désérialiser le message comme enveloppe scellée
+                if let Ok(envelope) = serde_json::from_slice::<SealedEnvelope>(&message.data) {
+                    match open_message(&self.identity, &envelope) {
+                        // Contenu jamais journalisé ici: voir `policy.log_content` côté nœud
+                        Ok(Some(inbound)) => println!("Message reçu de {} ({} octets)", inbound.from, inbound.message.payload.len()),
+                        Ok(None) => println!("Enveloppe adressée à d'autres nœuds"),
+                        Err(e) => println!("Enveloppe invalide: {:?}", e),
+                    }
+                }
+                // Les anciens messages en clair ne sont plus affichés
+                else if serde_json::from_slice::<CommunicatorMessage>(&message.data).is_ok() {
+                    println!("Message en clair ignoré (enveloppe scellée requise)");
                 } 
                 // Essayer comme message d'annonce de nœud
                 else if let Ok(announce) = serde_json::from_slice::<AnnounceMsg>(&message.data) {
@@ -83,8 +126,13 @@ impl Communicator {
                                 return;
                             }
                         };
-                        reg_lock.update_from_announce(announce);
-                        println!("Registry mis à jour avec le nœud");
+                        if announce.leaving {
+                            reg_lock.remove_node(&announce.node_id);
+                            println!("Nœud retiré du registry (départ annoncé)");
+                        } else {
+                            reg_lock.update_from_announce(announce);
+                            println!("Registry mis à jour avec le nœud");
+                        }
                     } else {
                         println!("Pas de registry disponible pour l'annonce");
                     }
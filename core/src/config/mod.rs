@@ -0,0 +1,243 @@
+// src/config/mod.rs
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::access::AccessLists;
+use crate::executor::ShardSpec;
+use crate::moe::MoeConfig;
+use crate::quorum::QuorumConfig;
+use crate::reputation::ReputationConfig;
+use crate::policy::PolicyConfig;
+use crate::admission::MAX_DIFFICULTY;
+use crate::identity::CortexPaths;
+use crate::registry::NODE_TTL_SECS;
+use crate::trust::{load_json, NodeCertificate, TrustStore};
+
+/// Version du protocole mesh parlée par ce binaire
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Plus ancienne version du protocole encore acceptée (traduite si besoin)
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Configuration du nœud, lue depuis `~/.cortex/config.yaml` (généré par install.sh)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CortexConfig {
+    pub hostname: Option<String>,
+    pub mesh: MeshConfig,
+    pub limits: LimitsConfig,
+    pub trust: TrustConfig,
+    pub access: AccessLists,
+    pub api: ApiConfig,
+    /// Consentement et localité des données
+    pub policy: PolicyConfig,
+    /// Shards de modèle chargés au démarrage et annoncés au mesh
+    pub shards: Vec<ShardSpec>,
+    /// Routage des couches Mixture-of-Experts
+    pub moe: MoeConfig,
+    /// Validation croisée des calculs de shards
+    pub quorum: QuorumConfig,
+    /// Réputation des pairs et pondération de l'ordonnancement
+    pub reputation: ReputationConfig,
+}
+
+/// Section `api:` — API HTTP du nœud (désactivée si aucun port)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    pub port: Option<u16>,
+    /// Adresse d'écoute (défaut: 127.0.0.1, l'API modifie les listes d'accès)
+    pub bind: Option<std::net::IpAddr>,
+}
+
+/// Section `mesh:` de la configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeshConfig {
+    /// Nom du mesh, utilisé pour isoler les topics et les clés DHT
+    pub name: String,
+    /// Version du protocole publiée par ce nœud
+    pub protocol_version: u32,
+    /// Version minimale acceptée en réception
+    pub min_protocol_version: u32,
+    /// Continuer à publier/écouter sur les topics v1 pendant une migration
+    pub legacy_compat: bool,
+    /// Ancien champ (`cortex-v1`), conservé pour lire les configs existantes
+    pub pubsub_topic: Option<String>,
+    /// Délai sans annonce après lequel un nœud est retiré du registre
+    pub node_ttl_secs: u64,
+    /// Bits de preuve de travail exigés de chaque identité (0: pas de coût d'admission)
+    pub admission_difficulty: u8,
+}
+
+impl Default for MeshConfig {
+    fn default() -> Self {
+        MeshConfig {
+            name: "default".into(),
+            protocol_version: PROTOCOL_VERSION,
+            min_protocol_version: MIN_PROTOCOL_VERSION,
+            legacy_compat: true,
+            pubsub_topic: None,
+            node_ttl_secs: NODE_TTL_SECS,
+            admission_difficulty: 0,
+        }
+    }
+}
+
+/// Section `limits:` — protège les petites machines contre un afflux de connexions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimitsConfig {
+    pub max_pending_incoming: Option<u32>,
+    pub max_pending_outgoing: Option<u32>,
+    pub max_established_incoming: Option<u32>,
+    pub max_established_outgoing: Option<u32>,
+    pub max_established_total: Option<u32>,
+    pub max_established_per_peer: Option<u32>,
+    /// Seuil mémoire absolu du processus (Mo) au-delà duquel les nouvelles connexions sont refusées
+    pub max_memory_mb: Option<usize>,
+    /// Seuil mémoire relatif à la RAM totale (0.0 - 1.0), ignoré si `max_memory_mb` est défini
+    pub max_memory_fraction: Option<f64>,
+    /// Fermeture des connexions sans flux actif après ce délai
+    pub idle_connection_timeout_secs: u64,
+    /// Mémoire réservée aux shards (Mo); sans valeur, la RAM disponible du système est annoncée
+    pub shard_memory_mb: Option<u64>,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        LimitsConfig {
+            max_pending_incoming: Some(32),
+            max_pending_outgoing: Some(32),
+            max_established_incoming: Some(64),
+            max_established_outgoing: Some(64),
+            max_established_total: Some(128),
+            max_established_per_peer: Some(2),
+            max_memory_mb: None,
+            max_memory_fraction: Some(0.8),
+            idle_connection_timeout_secs: 60,
+            shard_memory_mb: None,
+        }
+    }
+}
+
+/// Section `trust:` — domaines de confiance d'organisation (désactivée si aucune racine)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrustConfig {
+    /// Organisations reconnues et leur clé publique ed25519
+    pub roots: Vec<TrustRootConfig>,
+    /// Certificat de ce nœud (défaut: `node-cert.json` dans le répertoire du profil)
+    pub certificate: Option<PathBuf>,
+    /// Listes de révocation à charger au démarrage
+    pub revocation_lists: Vec<PathBuf>,
+    /// N'inscrire au registre que les nœuds certifiés par une des organisations
+    pub require_certificate: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustRootConfig {
+    pub domain: String,
+    /// Clé publique de l'organisation, en base64
+    pub public_key: String,
+}
+
+impl TrustConfig {
+    /// Chemin du certificat du nœud (par défaut celui du profil)
+    pub fn certificate_path(&self, paths: &CortexPaths) -> PathBuf {
+        self.certificate.clone().unwrap_or_else(|| paths.certificate())
+    }
+
+    /// Charge le certificat du nœud s'il existe
+    pub fn load_certificate(&self, paths: &CortexPaths) -> Result<Option<NodeCertificate>> {
+        let path = self.certificate_path(paths);
+        if !path.exists() {
+            if self.certificate.is_some() {
+                anyhow::bail!("Node certificate {:?} not found", path);
+            }
+            return Ok(None);
+        }
+        Ok(Some(load_json(&path)?))
+    }
+}
+
+impl CortexConfig {
+    /// Charge la configuration du profil, ou les valeurs par défaut si absente
+    pub fn load(paths: &CortexPaths) -> Result<Self> {
+        let path = paths.config();
+        if !path.exists() {
+            println!("⚠️ Aucune configuration trouvée ({:?}), valeurs par défaut utilisées", path);
+            return Ok(CortexConfig::default());
+        }
+        Self::load_from(&path)
+    }
+
+    /// Charge la configuration depuis un fichier YAML donné
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config {:?}", path))?;
+        let config: CortexConfig = serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse config {:?}", path))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let mesh = &self.mesh;
+        if mesh.name.is_empty() || mesh.name.contains('/') {
+            anyhow::bail!("Invalid mesh name: {:?}", mesh.name);
+        }
+        if mesh.protocol_version > PROTOCOL_VERSION {
+            anyhow::bail!(
+                "Protocol version {} not supported by this binary (max {})",
+                mesh.protocol_version,
+                PROTOCOL_VERSION
+            );
+        }
+        if mesh.node_ttl_secs == 0 {
+            anyhow::bail!("mesh.node_ttl_secs must be greater than 0");
+        }
+        if mesh.min_protocol_version > mesh.protocol_version {
+            anyhow::bail!(
+                "min_protocol_version ({}) greater than protocol_version ({})",
+                mesh.min_protocol_version,
+                mesh.protocol_version
+            );
+        }
+        if mesh.admission_difficulty > MAX_DIFFICULTY {
+            anyhow::bail!(
+                "mesh.admission_difficulty ({}) greater than {}",
+                mesh.admission_difficulty,
+                MAX_DIFFICULTY
+            );
+        }
+        let mut trust = TrustStore::default();
+        for root in &self.trust.roots {
+            trust.add_root(&root.domain, &root.public_key)
+                .with_context(|| format!("Invalid trust root for domain {}", root.domain))?;
+        }
+        if self.trust.require_certificate && !trust.is_enabled() {
+            anyhow::bail!("trust.require_certificate needs at least one trust root");
+        }
+        self.access.validate().context("Invalid access lists")?;
+        self.policy.validate()?;
+        self.moe.validate()?;
+        self.quorum.validate()?;
+        self.reputation.validate()?;
+        let mut shard_ids = std::collections::HashSet::new();
+        for shard in &self.shards {
+            if shard.id.is_empty() || !shard_ids.insert(&shard.id) {
+                anyhow::bail!("Invalid or duplicate shard id: {:?}", shard.id);
+            }
+        }
+        if let Some(fraction) = self.limits.max_memory_fraction {
+            if fraction <= 0.0 || fraction > 1.0 {
+                anyhow::bail!("limits.max_memory_fraction must be in (0, 1], got {}", fraction);
+            }
+        }
+        Ok(())
+    }
+}
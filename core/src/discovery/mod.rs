@@ -1,11 +1,29 @@
-use crate::registry::{AnnounceMsg, Registry};
+mod node;
+
+pub use node::{MeshNode, NodeCommand, NodeHandle, NodeOptions};
+
+use crate::access::AccessLists;
+use crate::admission;
+use crate::api_interface::run_node_api;
+use crate::config::{CortexConfig, LimitsConfig};
+use crate::identity::CortexPaths;
+use crate::identity::succession::KeySuccession;
+use crate::registry::AnnounceMsg;
+use crate::rpc::{build_rpc_behaviour, RpcBehaviour, RpcEvent};
+use crate::shutdown::wait_for_signal;
+use crate::topics::{Channel, TopicNamespace};
 use libp2p::{
+    allow_block_list::{self, AllowedPeers, BlockedPeers},
+    connection_limits::{self, ConnectionLimits},
+    core::upgrade::Version,
     gossipsub::{
         Behaviour as Gossipsub,
         ConfigBuilder as GossipsubConfigBuilder,
         Event as GossipsubEvent,
-        IdentTopic,
+        Message as GossipsubMessage,
         MessageAuthenticity,
+        PeerScoreParams,
+        PeerScoreThresholds,
     },
     identity::Keypair,
     kad::{
@@ -13,30 +31,36 @@ use libp2p::{
         Behaviour as Kademlia,
         Config as KademliaConfig,
         Event as KademliaEvent,
-        RecordKey,
+        Mode as KademliaMode,
     },
     mdns::{tokio::Behaviour as Mdns, Event as MdnsEvent},
+    memory_connection_limits,
+    ping,
     multiaddr::{Multiaddr, Protocol},
+    noise,
     quic::{tokio::Transport as QuicTransport, Config as QuicConfig},
-    swarm::{NetworkBehaviour, Swarm, SwarmEvent, Config as SwarmConfig},
-    core::muxing::StreamMuxerBox,
+    swarm::{behaviour::toggle::Toggle, NetworkBehaviour},
+    core::{muxing::StreamMuxerBox, transport::{Boxed, MemoryTransport}},
+    yamux,
     PeerId, Transport,
 };
-use libp2p::futures::StreamExt;
-use tokio::time::{sleep, Duration};
-use anyhow::Result;
-use std::sync::{Arc, Mutex};
+use anyhow::{anyhow, Result};
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::str::FromStr;
 
-const CORTEX_SHARED_KEY: &[u8; 14] = b"cortex-mesh:v1";
-const ANNOUNCE_TOPIC: &str = "cortex/announce";
-const BOOTSTRAP_INTERVAL: u64 = 30; // secondes
+/// Transport commun à tous les nœuds (QUIC en production, mémoire en test)
+pub type BoxedTransport = Boxed<(PeerId, StreamMuxerBox)>;
 
 #[derive(Debug)]
 pub enum MeshEvent {
     Gossipsub(GossipsubEvent),
     Mdns(MdnsEvent),
     Kad(KademliaEvent),
+    Rpc(RpcEvent),
+    Ping(ping::Event),
+    /// Les flux de tenseurs se lisent via leur `Control`, sans événement du swarm
+    Stream,
 }
 
 impl From<MdnsEvent> for MeshEvent {
@@ -57,22 +81,52 @@ impl From<KademliaEvent> for MeshEvent {
     }
 }
 
+impl From<RpcEvent> for MeshEvent {
+    fn from(event: RpcEvent) -> Self {
+        MeshEvent::Rpc(event)
+    }
+}
+
+impl From<ping::Event> for MeshEvent {
+    fn from(event: ping::Event) -> Self {
+        MeshEvent::Ping(event)
+    }
+}
+
+impl From<()> for MeshEvent {
+    fn from(_: ()) -> Self {
+        MeshEvent::Stream
+    }
+}
+
+// Les comportements de limitation n'émettent aucun événement
+impl From<void::Void> for MeshEvent {
+    fn from(event: void::Void) -> Self {
+        void::unreachable(event)
+    }
+}
+
 #[derive(NetworkBehaviour)]
 #[behaviour(to_swarm = "MeshEvent", event_process = false)]
 pub struct MeshBehaviour {
     pub gossipsub: Gossipsub,
-    pub mdns: Mdns,
+    pub mdns: Toggle<Mdns>,
     pub kad: Kademlia<MemoryStore>,
-}
-
-#[derive(Debug)]
-enum Command {
-    GetProviders,
-    AnnounceNode,
+    pub limits: connection_limits::Behaviour,
+    pub memory_limits: Toggle<memory_connection_limits::Behaviour>,
+    pub blocked: allow_block_list::Behaviour<BlockedPeers>,
+    /// Actif seulement en mode liste d'autorisation
+    pub allowed: Toggle<allow_block_list::Behaviour<AllowedPeers>>,
+    /// Requêtes directes entre deux nœuds (étapes de pipeline)
+    pub rpc: RpcBehaviour,
+    /// Flux dédiés aux trames de tenseurs
+    pub stream: libp2p_stream::Behaviour,
+    /// Mesure de la latence vers les pairs connectés, reportée au registre
+    pub ping: ping::Behaviour,
 }
 
 /// Fonction utilitaire pour convertir une chaîne bootstrap en multiaddr et peer_id
-fn parse_bootstrap_addr(addr_str: &str) -> Option<(Multiaddr, PeerId)> {
+pub fn parse_bootstrap_addr(addr_str: &str) -> Option<(Multiaddr, PeerId)> {
     match Multiaddr::from_str(addr_str) {
         Ok(addr) => {
             // Extraire le PeerId de la multiaddr
@@ -94,14 +148,173 @@ fn parse_bootstrap_addr(addr_str: &str) -> Option<(Multiaddr, PeerId)> {
 }
 
 /// Crée un transport QUIC commun pour tous les nœuds
-fn create_transport(keypair: &Keypair) -> libp2p::core::transport::Boxed<(PeerId, StreamMuxerBox)> {
+pub fn create_transport(keypair: &Keypair) -> BoxedTransport {
     QuicTransport::new(QuicConfig::new(keypair))
         .map(|(peer_id, conn), _| (peer_id, StreamMuxerBox::new(conn)))
         .boxed()
 }
 
+/// Crée un transport en mémoire (adresses `/memory/<n>`), pour faire tourner
+/// plusieurs nœuds dans un même processus. Noise + Yamux comme sur un vrai lien.
+pub fn create_memory_transport(keypair: &Keypair) -> Result<BoxedTransport> {
+    Ok(MemoryTransport::default()
+        .upgrade(Version::V1)
+        .authenticate(noise::Config::new(keypair)?)
+        .multiplex(yamux::Config::default())
+        .boxed())
+}
+
+/// Décode une annonce reçue en vérifiant le topic, l'auteur et la version du protocole.
+/// Les annonces v1 sont traduites (champs manquants à leur valeur par défaut),
+/// celles d'une version incompatible ou d'un autre mesh sont refusées. Une annonce n'est
+/// acceptée que de son propre nœud (auteur gossipsub signé): sans quoi n'importe quel pair
+/// pourrait retirer un nœud du registre ou rejouer son annonce sous son identité.
+pub(crate) fn decode_announce(namespace: &TopicNamespace, message: &GossipsubMessage) -> Option<AnnounceMsg> {
+    let parsed = TopicNamespace::parse_hash(&message.topic)?;
+    if parsed.channel != Channel::Announce || !namespace.accepts_topic(&parsed) {
+        println!("🚫 Annonce ignorée sur un topic incompatible: {}", message.topic);
+        return None;
+    }
+
+    // Les nœuds v1 ne renseignent pas `protocol_version`: la valeur par défaut (1) s'applique
+    let msg = serde_json::from_slice::<AnnounceMsg>(&message.data).ok()?;
+    if message.source.map(|source| source.to_string()).as_ref() != Some(&msg.node_id) {
+        println!("🚫 Annonce de {} ignorée: publiée par un autre pair ({:?})", msg.node_id, message.source);
+        return None;
+    }
+    if parsed.version == 1 && msg.protocol_version > 1 && namespace.accepts(msg.protocol_version) {
+        // Copie de compatibilité d'un nœud récent: déjà reçue sur son topic natif
+        return None;
+    }
+    if !namespace.accepts(msg.protocol_version) {
+        println!(
+            "🚫 Annonce de {} refusée: protocole v{} non supporté (accepté: v{})",
+            msg.node_id, msg.protocol_version, namespace.version()
+        );
+        return None;
+    }
+    Some(msg)
+}
+
+/// Publie une annonce sur tous les topics du canal (courant + compatibilité)
+pub(crate) fn publish_announce(gossipsub: &mut Gossipsub, namespace: &TopicNamespace, announce: &AnnounceMsg) {
+    let data = match serde_json::to_vec(announce) {
+        Ok(data) => data,
+        Err(e) => {
+            println!("⚠️ Erreur de sérialisation de l'annonce: {:?}", e);
+            return;
+        }
+    };
+
+    for topic in namespace.publications(Channel::Announce) {
+        if let Err(e) = gossipsub.publish(topic.clone(), data.clone()) {
+            println!("⚠️ Erreur lors de l'annonce sur {}: {:?}", topic, e);
+        } else {
+            println!("📢 Annonce publiée sur {}", topic);
+        }
+    }
+}
+
+/// Décode et vérifie un certificat de succession reçu; les certificats invalides sont refusés
+pub(crate) fn decode_succession(message: &GossipsubMessage) -> Option<KeySuccession> {
+    let succession = serde_json::from_slice::<KeySuccession>(&message.data).ok()?;
+    match succession.verify() {
+        Ok(()) => Some(succession),
+        Err(e) => {
+            println!("🚫 Succession de clé refusée ({} -> {}): {}", succession.old_peer_id, succession.new_peer_id, e);
+            None
+        }
+    }
+}
+
+/// Publie un document signé (succession, révocation) sur les topics d'un canal
+pub(crate) fn publish_signed<T: serde::Serialize>(
+    gossipsub: &mut Gossipsub,
+    namespace: &TopicNamespace,
+    channel: Channel,
+    document: &T,
+) {
+    let data = match serde_json::to_vec(document) {
+        Ok(data) => data,
+        Err(e) => {
+            println!("⚠️ Erreur de sérialisation ({:?}): {:?}", channel, e);
+            return;
+        }
+    };
+
+    for topic in namespace.publications(channel) {
+        if let Err(e) = gossipsub.publish(topic.clone(), data.clone()) {
+            println!("⚠️ Erreur lors de la publication sur {}: {:?}", topic, e);
+        }
+    }
+}
+
+/// Construit les limites de connexions du swarm à partir de la configuration
+fn build_connection_limits(limits: &LimitsConfig) -> connection_limits::Behaviour {
+    let connection_limits = ConnectionLimits::default()
+        .with_max_pending_incoming(limits.max_pending_incoming)
+        .with_max_pending_outgoing(limits.max_pending_outgoing)
+        .with_max_established_incoming(limits.max_established_incoming)
+        .with_max_established_outgoing(limits.max_established_outgoing)
+        .with_max_established(limits.max_established_total)
+        .with_max_established_per_peer(limits.max_established_per_peer);
+    connection_limits::Behaviour::new(connection_limits)
+}
+
+/// Construit la limite mémoire (désactivée si aucun seuil n'est configuré)
+fn build_memory_limits(limits: &LimitsConfig) -> Toggle<memory_connection_limits::Behaviour> {
+    let behaviour = match (limits.max_memory_mb, limits.max_memory_fraction) {
+        (Some(mb), _) => Some(memory_connection_limits::Behaviour::with_max_bytes(mb * 1024 * 1024)),
+        (None, Some(fraction)) => Some(memory_connection_limits::Behaviour::with_max_percentage(fraction)),
+        (None, None) => None,
+    };
+    if let Some(b) = &behaviour {
+        println!("🧠 Limite mémoire des connexions: {} Mo", b.max_allowed_bytes() / (1024 * 1024));
+    }
+    Toggle::from(behaviour)
+}
+
+/// Construit les listes de blocage et d'autorisation appliquées aux connexions
+fn build_access_behaviours(
+    access: &AccessLists,
+) -> (allow_block_list::Behaviour<BlockedPeers>, Toggle<allow_block_list::Behaviour<AllowedPeers>>) {
+    let mut blocked = allow_block_list::Behaviour::<BlockedPeers>::default();
+    for peer in access.denied_peers() {
+        blocked.block_peer(peer);
+    }
+    let allowed = access.allowlist_only.then(|| {
+        let mut allowed = allow_block_list::Behaviour::<AllowedPeers>::default();
+        for peer in access.allowed_peers() {
+            allowed.allow_peer(peer);
+        }
+        println!("🔐 Mode liste d'autorisation: {} pairs autorisés", access.allow.len());
+        allowed
+    });
+    (blocked, Toggle::from(allowed))
+}
+
+/// Active le score des pairs de gossipsub, alimenté par leur réputation. Seul le score
+/// applicatif compte: plusieurs nœuds d'un même hôte sont légitimes dans un mesh d'entreprise.
+pub(crate) fn enable_peer_scoring(gossipsub: &mut Gossipsub) -> Result<()> {
+    let params = PeerScoreParams {
+        app_specific_weight: 1.0,
+        ip_colocation_factor_weight: 0.0,
+        ..PeerScoreParams::default()
+    };
+    gossipsub.with_peer_score(params, PeerScoreThresholds::default())
+        .map_err(|e| anyhow!("Invalid gossipsub peer scoring: {}", e))
+}
+
 /// Construit le comportement mesh de base (commun à tous les nœuds)
-async fn build_mesh_behaviour(keypair: Keypair, local_peer_id: PeerId) -> Result<MeshBehaviour> {
+async fn build_mesh_behaviour(
+    keypair: Keypair,
+    local_peer_id: PeerId,
+    role: NodeRole,
+    namespace: &TopicNamespace,
+    limits: &LimitsConfig,
+    access: &AccessLists,
+    enable_mdns: bool,
+) -> Result<MeshBehaviour> {
     // Configuration de Gossipsub améliorée
     let gossipsub_config = GossipsubConfigBuilder::default()
         .flood_publish(true)
@@ -110,306 +323,105 @@ async fn build_mesh_behaviour(keypair: Keypair, local_peer_id: PeerId) -> Result
     let mut gossipsub = Gossipsub::new(MessageAuthenticity::Signed(keypair.clone()), gossipsub_config)
         .expect("Échec de création de gossipsub");
     
-    let topic = IdentTopic::new(ANNOUNCE_TOPIC);
-    gossipsub.subscribe(&topic)?;
+    for channel in [
+        Channel::Announce,
+        Channel::Communicator,
+        Channel::Succession,
+        Channel::Trust,
+        Channel::Plan,
+        Channel::Reputation,
+    ] {
+        for topic in namespace.subscriptions(channel) {
+            gossipsub.subscribe(&topic)?;
+        }
+    }
     
-    // mDNS pour découverte locale (LAN)
-    let mdns = Mdns::new(Default::default(), local_peer_id)?;
+    // mDNS pour découverte locale (LAN), inutile sur un transport en mémoire
+    let mdns = if enable_mdns {
+        Some(Mdns::new(Default::default(), local_peer_id)?)
+    } else {
+        None
+    };
     
     // Kademlia pour DHT
     let store = MemoryStore::new(local_peer_id);
     let mut kad_cfg = KademliaConfig::default();
     kad_cfg.set_provider_record_ttl(Some(std::time::Duration::from_secs(60)));
 
-    let kad = Kademlia::with_config(local_peer_id.clone(), store, kad_cfg);
+    let mut kad = Kademlia::with_config(local_peer_id, store, kad_cfg);
+    // Un bootstrap sert la DHT même sans adresse externe confirmée
+    if role == NodeRole::Bootstrap {
+        kad.set_mode(Some(KademliaMode::Server));
+    }
 
-    Ok(MeshBehaviour { gossipsub, mdns, kad })
+    let (blocked, allowed) = build_access_behaviours(access);
+    Ok(MeshBehaviour {
+        gossipsub,
+        mdns: Toggle::from(mdns),
+        kad,
+        limits: build_connection_limits(limits),
+        memory_limits: build_memory_limits(limits),
+        blocked,
+        allowed,
+        rpc: build_rpc_behaviour(namespace),
+        stream: libp2p_stream::Behaviour::new(),
+        ping: ping::Behaviour::default(),
+    })
 }
 
-/// Lancement d'un nœud bootstrap qui reste en écoute même en l'absence de pairs.
-pub async fn run_bootstrap_node(keypair: Keypair) -> Result<()> {
-    let local_peer_id = PeerId::from(keypair.public());
-    println!("🌐 Nœud bootstrap avec PeerId: {}", local_peer_id);
-    
-    // Création du transport
-    let transport = create_transport(&keypair);
-    
-    // Construction du comportement
-    let mut behaviour = build_mesh_behaviour(keypair.clone(), local_peer_id).await?;
-    
-    // Configuration spécifique bootstrap: démarrer en tant que fournisseur DHT
-    let discovery_key = RecordKey::new(&CORTEX_SHARED_KEY);
-    match behaviour.kad.start_providing(discovery_key.clone()) {
-        Ok(query_id) => println!("✅ DHT StartProviding lancé avec succès, QueryId: {:?}", query_id),
-        Err(e) => println!("⚠️ Échec de DHT StartProviding : {:?}. Continuité en mode bootstrap.", e),
-    }
-    
-    // Configuration et démarrage du swarm
-    let config = SwarmConfig::with_tokio_executor();
-    let mut swarm = Swarm::new(transport, behaviour, local_peer_id, config);
-    
-    // Écoute sur tous les interfaces (port aléatoire UDP pour QUIC)
-    for addr in ["/ip4/0.0.0.0/udp/0/quic-v1", "/ip6/::/udp/0/quic-v1"] {
-        match swarm.listen_on(addr.parse()?) {
-            Ok(_) => println!("Écoute démarrée sur {}", addr),
-            Err(e) => println!("⚠️ Impossible d'écouter sur {}: {}", addr, e),
-        }
-    }
-    
-    // Registre partagé
-    let registry = Arc::new(Mutex::new(Registry::default()));
-    
-    // Canal pour les commandes planifiées
-    let (cmd_tx, mut cmd_rx) = tokio::sync::mpsc::channel::<Command>(10);
-    
-    // Tâche pour DHT bootstrap périodique
-    let cmd_tx_clone = cmd_tx.clone();
-    tokio::spawn(async move {
-        loop {
-            sleep(Duration::from_secs(BOOTSTRAP_INTERVAL)).await;
-            println!("🔍 Recherche DHT pour les fournisseurs...");
-            if let Err(e) = cmd_tx_clone.send(Command::GetProviders).await {
-                println!("Erreur lors de l'envoi de la commande DHT: {:?}", e);
-            }
-        }
-    });
-    
-    // Tâche pour annonce périodique
-    let cmd_tx_clone = cmd_tx.clone();
-    tokio::spawn(async move {
-        loop {
-            sleep(Duration::from_secs(45)).await;
-            if let Err(e) = cmd_tx_clone.send(Command::AnnounceNode).await {
-                println!("Erreur lors de l'envoi de la commande d'annonce: {:?}", e);
-            }
-        }
-    });
-    
-    // Tâche pour afficher le registre
-    let reg_clone = Arc::clone(&registry);
-    tokio::spawn(async move {
-        loop {
-            sleep(Duration::from_secs(60)).await;
-            if let Ok(r) = reg_clone.lock() {
-                println!("\n📊 Registry Snapshot:");
-                println!("{}", r.snapshot_json());
-            }
-        }
-    });
-    
-    // Attendre que les addresses d'écoute soient établies
-    let mut listening = false;
-    while !listening {
-        if let SwarmEvent::NewListenAddr { address, .. } = swarm.select_next_some().await {
-            println!("📡 Bootstrap en écoute sur: {}", address);
-            listening = true;
-        }
-    }
-    
-    // Boucle principale
-    let topic = IdentTopic::new(ANNOUNCE_TOPIC);
-    let discovery_key_clone = discovery_key.clone();
-    
-    loop {
-        tokio::select! {
-            Some(cmd) = cmd_rx.recv() => {
-                match cmd {
-                    Command::GetProviders => {
-                        swarm.behaviour_mut().kad.get_providers(discovery_key_clone.clone());
-                    },
-                    Command::AnnounceNode => {
-                        // Création d'un message d'annonce
-                        let announce = AnnounceMsg {
-                            node_id: local_peer_id.to_string(),
-                            shards: vec!["bootstrap".into()],
-                            version: env!("CARGO_PKG_VERSION").to_string(),
-                            vram_free_mb: 0,
-                        };
-                        
-                        if let Ok(data) = serde_json::to_vec(&announce) {
-                            if let Err(e) = swarm.behaviour_mut().gossipsub.publish(topic.clone(), data) {
-                                println!("⚠️ Erreur lors de l'annonce: {:?}", e);
-                            } else {
-                                println!("📢 Annonce publiée sur {}", topic);
-                            }
-                        }
-                    }
-                }
-            },
-            event = swarm.select_next_some() => {
-                match event {
-                    SwarmEvent::Behaviour(MeshEvent::Gossipsub(GossipsubEvent::Message { message, .. })) => {
-                        if let Ok(msg) = serde_json::from_slice::<AnnounceMsg>(&message.data) {
-                            if msg.node_id != local_peer_id.to_string() {
-                                println!("📨 Message reçu de: {}", msg.node_id);
-                                if let Ok(mut reg) = registry.lock() {
-                                    reg.update_from_announce(msg);
-                                }
-                            }
-                        }
-                    },
-                    SwarmEvent::Behaviour(MeshEvent::Mdns(MdnsEvent::Discovered(peers))) => {
-                        for (peer_id, addr) in peers {
-                            println!("🔍 Pair découvert via mDNS: {} à {}", peer_id, addr);
-                            swarm.behaviour_mut().kad.add_address(&peer_id, addr);
-                        }
-                    },
-                    SwarmEvent::Behaviour(MeshEvent::Kad(KademliaEvent::RoutingUpdated { peer, .. })) => {
-                        println!("📝 Table de routage mise à jour avec: {}", peer);
-                    },
-                    SwarmEvent::Behaviour(MeshEvent::Kad(KademliaEvent::OutboundQueryProgressed { result, .. })) => {
-                        println!("📊 Progression requête DHT: {:?}", result);
-                    },
-                    SwarmEvent::NewListenAddr { address, .. } => {
-                        println!("📡 En écoute sur: {}", address);
-                    },
-                    _ => {}
-                }
-            }
+/// Rôle du nœud dans le mesh
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeRole {
+    /// Reste en écoute et fournit la clé de découverte DHT
+    Bootstrap,
+    /// Rejoint le réseau via un bootstrap ou mDNS
+    Light,
+}
+
+impl NodeRole {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            NodeRole::Bootstrap => "bootstrap",
+            NodeRole::Light => "light",
         }
     }
 }
 
-/// Fonction pour lancer un nœud "léger" qui rejoint le réseau
-pub async fn run_light_node(keypair: Keypair) -> Result<()> {
-    let local_peer_id = PeerId::from(keypair.public());
-    println!("🔹 Nœud léger avec PeerId: {}", local_peer_id);
-    
-    // Création du transport
+/// Lancement d'un nœud bootstrap qui reste en écoute même en l'absence de pairs.
+/// Rend la main proprement sur SIGINT/SIGTERM.
+pub async fn run_bootstrap_node(keypair: Keypair, config: &CortexConfig, paths: &CortexPaths) -> Result<()> {
+    run_node(keypair, config, paths, NodeRole::Bootstrap, wait_for_signal()).await
+}
+
+/// Fonction pour lancer un nœud "léger" qui rejoint le réseau.
+/// Rend la main proprement sur SIGINT/SIGTERM.
+pub async fn run_light_node(keypair: Keypair, config: &CortexConfig, paths: &CortexPaths) -> Result<()> {
+    run_node(keypair, config, paths, NodeRole::Light, wait_for_signal()).await
+}
+
+/// Lance un nœud QUIC réel, avec les fichiers du profil `paths`, jusqu'à ce que `shutdown` se termine.
+pub async fn run_node<F>(keypair: Keypair, config: &CortexConfig, paths: &CortexPaths, role: NodeRole, shutdown: F) -> Result<()>
+where
+    F: Future<Output = ()>,
+{
     let transport = create_transport(&keypair);
-    
-    // Construction du comportement
-    let behaviour = build_mesh_behaviour(keypair.clone(), local_peer_id).await?;
-    
-    // Configuration et démarrage du swarm
-    let config = SwarmConfig::with_tokio_executor();
-    let mut swarm = Swarm::new(transport, behaviour, local_peer_id, config);
-    
-    // Écoute sur tous les interfaces
-    for addr in ["/ip4/0.0.0.0/udp/0/quic-v1", "/ip6/::/udp/0/quic-v1"] {
-        match swarm.listen_on(addr.parse()?) {
-            Ok(_) => println!("Écoute démarrée sur {}", addr),
-            Err(e) => println!("⚠️ Impossible d'écouter sur {}: {}", addr, e),
-        }
+    let mut options = NodeOptions::from_env(role, paths);
+    options.certificate = config.trust.load_certificate(paths)?;
+    let difficulty = config.mesh.admission_difficulty;
+    if difficulty > 0 {
+        let mesh = config.mesh.name.clone();
+        let peer_id = keypair.public().to_peer_id();
+        let path = paths.admission();
+        let proof = tokio::task::spawn_blocking(move || {
+            admission::load_or_solve(&path, &mesh, &peer_id, difficulty)
+        })
+        .await??;
+        options.admission = Some(proof);
     }
-    
-    // Ajout du nœud bootstrap si spécifié
-    if let Ok(bootstrap_addr) = std::env::var("CORTEX_BOOTSTRAP_PEER") {
-        println!("🔌 Bootstrap avec: {}", bootstrap_addr);
-        
-        if let Some((addr, peer_id)) = parse_bootstrap_addr(&bootstrap_addr) {
-            println!("🌐 Connexion au nœud bootstrap: {} @ {}", peer_id, addr);
-            swarm.behaviour_mut().kad.add_address(&peer_id, addr.clone());
-            
-            // Tentative de connexion directe
-            match swarm.dial(addr.clone()) {
-                Ok(_) => println!("✅ Tentative de connexion à {}", addr),
-                Err(e) => println!("❌ Échec de connexion à {}: {:?}", addr, e),
-            }
-        } else {
-            println!("❌ Format d'adresse bootstrap invalide");
-        }
-    } else {
-        println!("⚠️ Aucun nœud bootstrap spécifié. Utilisation de mDNS uniquement.");
+    let (node, handle) = MeshNode::new(keypair, config, options, transport).await?;
+    if let Some(port) = config.api.port {
+        let bind = config.api.bind.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        tokio::spawn(run_node_api(SocketAddr::new(bind, port), handle));
     }
-    
-    // Mise en place des clés DHT
-    let discovery_key = RecordKey::new(&CORTEX_SHARED_KEY);
-    
-    // Canal pour les commandes
-    let (cmd_tx, mut cmd_rx) = tokio::sync::mpsc::channel::<Command>(10);
-    
-    // Tâche pour recherche DHT périodique
-    let cmd_tx_clone = cmd_tx.clone();
-    tokio::spawn(async move {
-        // Attente initiale pour laisser le réseau s'établir
-        sleep(Duration::from_secs(2)).await;
-        
-        loop {
-            if let Err(e) = cmd_tx_clone.send(Command::GetProviders).await {
-                println!("Erreur lors de l'envoi de la commande DHT: {:?}", e);
-            }
-            
-            // Annonce après 5 secondes
-            sleep(Duration::from_secs(5)).await;
-            
-            if let Err(e) = cmd_tx_clone.send(Command::AnnounceNode).await {
-                println!("Erreur lors de l'envoi de la commande d'annonce: {:?}", e);
-            }
-            
-            sleep(Duration::from_secs(BOOTSTRAP_INTERVAL)).await;
-        }
-    });
-    
-    // Attendre que les addresses d'écoute soient établies
-    let mut listening = false;
-    while !listening {
-        if let SwarmEvent::NewListenAddr { address, .. } = swarm.select_next_some().await {
-            println!("📡 Nœud léger en écoute sur: {}", address);
-            listening = true;
-        }
-    }
-    
-    // Boucle principale
-    let topic = IdentTopic::new(ANNOUNCE_TOPIC);
-    let discovery_key_clone = discovery_key.clone();
-    
-    loop {
-        tokio::select! {
-            Some(cmd) = cmd_rx.recv() => {
-                match cmd {
-                    Command::GetProviders => {
-                        println!("🔍 Recherche de fournisseurs pour la clé: {:?}", discovery_key_clone);
-                        swarm.behaviour_mut().kad.get_providers(discovery_key_clone.clone());
-                    },
-                    Command::AnnounceNode => {
-                        // Création d'un message d'annonce
-                        let announce = AnnounceMsg {
-                            node_id: local_peer_id.to_string(),
-                            shards: vec!["light".into()],
-                            version: env!("CARGO_PKG_VERSION").to_string(),
-                            vram_free_mb: 0,
-                        };
-                        
-                        if let Ok(data) = serde_json::to_vec(&announce) {
-                            if let Err(e) = swarm.behaviour_mut().gossipsub.publish(topic.clone(), data) {
-                                println!("⚠️ Erreur lors de l'annonce: {:?}", e);
-                            } else {
-                                println!("📢 Annonce publiée sur {}", topic);
-                            }
-                        }
-                    }
-                }
-            },
-            event = swarm.select_next_some() => {
-                match event {
-                    SwarmEvent::Behaviour(MeshEvent::Gossipsub(GossipsubEvent::Message { message, .. })) => {
-                        println!("📨 Message gossipsub reçu");
-                        if let Ok(msg) = serde_json::from_slice::<AnnounceMsg>(&message.data) {
-                            println!("📨 Annonce reçue de: {}", msg.node_id);
-                        }
-                    },
-                    SwarmEvent::Behaviour(MeshEvent::Mdns(MdnsEvent::Discovered(peers))) => {
-                        for (peer_id, addr) in peers {
-                            println!("🔍 Pair découvert via mDNS: {} à {}", peer_id, addr);
-                            swarm.behaviour_mut().kad.add_address(&peer_id, addr);
-                        }
-                    },
-                    SwarmEvent::Behaviour(MeshEvent::Kad(KademliaEvent::RoutingUpdated { peer, .. })) => {
-                        println!("📝 Table de routage mise à jour avec: {}", peer);
-                    },
-                    SwarmEvent::Behaviour(MeshEvent::Kad(KademliaEvent::OutboundQueryProgressed { result, .. })) => {
-                        println!("📊 Progression requête DHT: {:?}", result);
-                    },
-                    SwarmEvent::ConnectionEstablished { peer_id, .. } => {
-                        println!("🔗 Connexion établie avec: {}", peer_id);
-                    },
-                    SwarmEvent::ConnectionClosed { peer_id, .. } => {
-                        println!("❌ Connexion fermée avec: {}", peer_id);
-                    },
-                    _ => {}
-                }
-            }
-        }
-    }
-}
\ No newline at end of file
+    node.run(shutdown).await
+}
@@ -0,0 +1,1901 @@
+// src/discovery/node.rs
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use libp2p::futures::StreamExt;
+use libp2p::gossipsub::Event as GossipsubEvent;
+use libp2p::identity::{ed25519, Keypair};
+use libp2p::gossipsub::Message as GossipsubMessage;
+use libp2p::kad::{
+    store::RecordStore, Event as KademliaEvent, GetProvidersOk, GetRecordOk, Quorum, QueryId, QueryResult, Record,
+    RecordKey,
+};
+use libp2p::mdns::Event as MdnsEvent;
+use libp2p::ping;
+use libp2p::request_response::{Event as RpcEvent, Message as RpcMessage, OutboundRequestId, ResponseChannel};
+use libp2p::swarm::dial_opts::DialOpts;
+use libp2p::swarm::{Config as SwarmConfig, DialError, ListenError, Swarm, SwarmEvent};
+use libp2p::{Multiaddr, PeerId};
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::time::{sleep, sleep_until, Duration, Instant};
+
+use super::{
+    build_mesh_behaviour, decode_announce, decode_succession, enable_peer_scoring, parse_bootstrap_addr,
+    publish_announce, publish_signed, BoxedTransport, MeshBehaviour, MeshEvent, NodeRole,
+};
+use crate::access::{AccessLists, AccessUpdate};
+use crate::audit::AuditLog;
+use crate::admission::AdmissionProof;
+use crate::communicator::{open_message, seal_message, CommunicatorMessage, InboundMessage};
+use crate::config::CortexConfig;
+use crate::envelope::SealedEnvelope;
+use crate::executor::{BackendRegistry, ShardHost, ShardSpec, Tensor};
+use crate::identity::succession::{load_successions, KeySuccession};
+use crate::identity::CortexPaths;
+use crate::metrics::{DenialReason, NodeMetrics};
+use crate::scheduler::{SchedulePlan, SignedPlan, ROUTER_ROLE};
+use crate::signing::unix_now;
+use crate::frame::stream::{self as tensor_stream, FrameReceiver, FrameRouter, TensorLink, TensorStream, FRAME_TIMEOUT_SECS};
+use crate::moe::{serve_expert, MoeConfig};
+use crate::pipeline::{serve_stage, StageSessions};
+use crate::policy::{DataClass, EgressRequest, PolicyEngine};
+use crate::quorum::{serve_replica, QuorumConfig};
+use crate::reputation::{Observation, Reputation, ReputationAttestation};
+use crate::registry::{AnnounceMsg, Registry};
+use crate::rpc::{RpcCall, RpcOutcome, RpcRequest, RpcResponse};
+use crate::topics::{Channel, TopicNamespace};
+use crate::trust::{NodeCertificate, RevocationList, TrustStore};
+
+const BOOTSTRAP_INTERVAL: u64 = 30; // secondes
+const BOOTSTRAP_ANNOUNCE_INTERVAL: u64 = 45; // secondes
+const DRAIN_TIMEOUT: u64 = 5; // secondes
+const DRAIN_MIN_FLUSH_MS: u64 = 500;
+const MAX_PRUNE_INTERVAL: u64 = 30; // secondes
+/// Messages déchiffrés en attente par abonné avant que les plus anciens soient perdus
+const INBOX_CAPACITY: usize = 256;
+/// Fréquence de vérification de `access.json` (modifié par la CLI)
+const ACCESS_RELOAD_INTERVAL: u64 = 2; // secondes
+/// Durée pendant laquelle un nœud republie ses certificats de succession avec ses annonces
+const SUCCESSION_REPUBLISH_SECS: u64 = 7 * 24 * 3600;
+
+/// Paramètres de lancement d'un nœud, indépendants de la configuration du mesh
+#[derive(Debug, Clone)]
+pub struct NodeOptions {
+    pub role: NodeRole,
+    /// Adresses d'écoute du swarm
+    pub listen_addrs: Vec<Multiaddr>,
+    /// Pairs à contacter au démarrage (multiaddr contenant `/p2p/<PeerId>`)
+    pub bootstrap_peers: Vec<Multiaddr>,
+    /// Découverte LAN via mDNS
+    pub enable_mdns: bool,
+    /// Annonces et recherches DHT périodiques (désactivées pour piloter un nœud pas à pas)
+    pub periodic_tasks: bool,
+    /// Fichier de persistance du registre (aucune persistance si `None`)
+    pub registry_path: Option<PathBuf>,
+    /// Fichier de persistance de la réputation des pairs (aucune persistance si `None`)
+    pub reputation_path: Option<PathBuf>,
+    /// Délai maximal entre deux tentatives de reconnexion à un bootstrap
+    pub reconnect_max_backoff: Duration,
+    /// Certificats de succession émis par ce nœud (rotations de clé), republiés avec les annonces
+    pub successions: Vec<KeySuccession>,
+    /// Certificat d'organisation présenté dans les annonces
+    pub certificate: Option<NodeCertificate>,
+    /// Listes d'accès persistées et surveillées (aucune persistance si `None`)
+    pub access_path: Option<PathBuf>,
+    /// Backends d'exécution disponibles pour les shards
+    pub backends: BackendRegistry,
+    /// Répertoire du journal d'audit signé (aucun journal si `None`)
+    pub audit_dir: Option<PathBuf>,
+    /// Preuve d'admission précalculée (calculée au démarrage si le mesh l'exige et qu'elle manque)
+    pub admission: Option<AdmissionProof>,
+}
+
+impl NodeOptions {
+    /// Options d'un nœud réel: QUIC sur toutes les interfaces, mDNS,
+    /// bootstrap depuis `CORTEX_BOOTSTRAP_PEER`, fichiers d'état dans le répertoire du profil
+    pub fn from_env(role: NodeRole, paths: &CortexPaths) -> Self {
+        let listen_addrs = ["/ip4/0.0.0.0/udp/0/quic-v1", "/ip6/::/udp/0/quic-v1"]
+            .iter()
+            .filter_map(|a| a.parse().ok())
+            .collect();
+
+        let mut bootstrap_peers = Vec::new();
+        if role == NodeRole::Light {
+            if let Ok(bootstrap_addr) = std::env::var("CORTEX_BOOTSTRAP_PEER") {
+                println!("🔌 Bootstrap avec: {}", bootstrap_addr);
+                match parse_bootstrap_addr(&bootstrap_addr) {
+                    Some((addr, _)) => bootstrap_peers.push(addr),
+                    None => println!("❌ Format d'adresse bootstrap invalide"),
+                }
+            } else {
+                println!("⚠️ Aucun nœud bootstrap spécifié. Utilisation de mDNS uniquement.");
+            }
+        }
+
+        let successions = match load_successions(&paths.successions()) {
+            Ok(successions) => successions,
+            Err(e) => {
+                println!("⚠️ Certificats de succession illisibles: {:?}", e);
+                Vec::new()
+            }
+        };
+
+        NodeOptions {
+            role,
+            listen_addrs,
+            bootstrap_peers,
+            enable_mdns: true,
+            periodic_tasks: true,
+            registry_path: Some(paths.registry()),
+            reputation_path: Some(paths.reputation()),
+            reconnect_max_backoff: Duration::from_secs(30),
+            successions,
+            certificate: None,
+            access_path: Some(paths.access()),
+            backends: BackendRegistry::builtin(),
+            audit_dir: Some(paths.audit_dir()),
+            admission: None,
+        }
+    }
+
+    /// Options d'un nœud en mémoire, piloté par commandes (banc de test multi-nœuds)
+    pub fn in_memory(role: NodeRole, listen_addr: Multiaddr, bootstrap_peers: Vec<Multiaddr>) -> Self {
+        NodeOptions {
+            role,
+            listen_addrs: vec![listen_addr],
+            bootstrap_peers,
+            enable_mdns: false,
+            periodic_tasks: false,
+            registry_path: None,
+            reputation_path: None,
+            reconnect_max_backoff: Duration::from_secs(2),
+            successions: Vec::new(),
+            certificate: None,
+            access_path: None,
+            backends: BackendRegistry::builtin(),
+            audit_dir: None,
+            admission: None,
+        }
+    }
+}
+
+/// Commandes acceptées par la boucle d'un nœud
+#[derive(Debug)]
+pub enum NodeCommand {
+    /// Lance une recherche des fournisseurs de la clé de découverte
+    GetProviders,
+    /// Publie l'annonce du nœud
+    AnnounceNode,
+    /// Publie une annonce arbitraire sous l'identité gossipsub du nœud (banc de test)
+    #[cfg(any(test, feature = "harness"))]
+    PublishAnnounce(AnnounceMsg),
+    /// Ouvre une connexion vers une adresse
+    Dial(Multiaddr),
+    /// Recherche les fournisseurs de la clé de découverte et renvoie le résultat complet
+    FindProviders { reply: oneshot::Sender<HashSet<PeerId>> },
+    /// Liste les pairs actuellement connectés
+    ConnectedPeers { reply: oneshot::Sender<Vec<PeerId>> },
+    /// Liste les adresses d'écoute effectives
+    ListenAddrs { reply: oneshot::Sender<Vec<Multiaddr>> },
+    /// Diffuse un certificat de succession émis par ce nœud (gossip + DHT)
+    PublishSuccession(KeySuccession),
+    /// Cherche dans la DHT le successeur d'une identité retirée
+    FindSuccessor { old: PeerId, reply: oneshot::Sender<Option<KeySuccession>> },
+    /// Applique et diffuse une liste de révocation d'organisation
+    ApplyRevocation(RevocationList),
+    /// Modifie les listes d'accès (effet immédiat, persisté) et renvoie le nouvel état
+    UpdateAccess { update: AccessUpdate, reply: oneshot::Sender<Result<AccessLists>> },
+    /// État courant des listes d'accès
+    AccessLists { reply: oneshot::Sender<AccessLists> },
+    /// Scelle un message pour ses destinataires et le diffuse sur le canal communicator,
+    /// si la politique autorise chacun d'eux à recevoir cette catégorie de données
+    SendSealed {
+        data: DataClass,
+        recipients: Vec<PeerId>,
+        message: CommunicatorMessage,
+        reply: oneshot::Sender<Result<()>>,
+    },
+    /// Envoie une requête de contrôle à un pair et attend sa réponse
+    Rpc {
+        peer: PeerId,
+        call: RpcCall,
+        reply: RpcReply,
+    },
+    /// Répond à une requête directe reçue, une fois traitée hors de la boucle
+    RespondRpc { id: u64, outcome: RpcOutcome },
+    /// Établit une connexion avec un pair, via ses adresses annoncées si besoin
+    Connect {
+        peer: PeerId,
+        reply: oneshot::Sender<Result<()>>,
+    },
+    /// Signe un plan d'affectation avec l'identité du nœud, le retient et le diffuse
+    PublishPlan {
+        plan: SchedulePlan,
+        reply: oneshot::Sender<SignedPlan>,
+    },
+}
+
+/// Poignée pour piloter et observer un nœud depuis une autre tâche (API, tests)
+#[derive(Clone)]
+pub struct NodeHandle {
+    peer_id: PeerId,
+    commands: mpsc::Sender<NodeCommand>,
+    registry: Arc<Mutex<Registry>>,
+    metrics: Arc<NodeMetrics>,
+    policy: Arc<PolicyEngine>,
+    audit: Option<Arc<AuditLog>>,
+    shards: Arc<ShardHost>,
+    backends: BackendRegistry,
+    inbox: broadcast::Sender<InboundMessage>,
+    stages: Arc<StageSessions>,
+    moe: MoeConfig,
+    quorum: QuorumConfig,
+    reputation: Arc<Reputation>,
+    streams: libp2p_stream::Control,
+    tensor_protocol: libp2p::StreamProtocol,
+    frames: Arc<FrameRouter>,
+    /// Flux de tenseurs sortants, un par pair
+    tensor_links: Arc<Mutex<HashMap<PeerId, TensorLink>>>,
+    /// Plan signé le plus récent de chaque modèle
+    plans: Arc<Mutex<HashMap<String, SignedPlan>>>,
+    /// Domaine de confiance de ce nœud, si son certificat a été validé au démarrage
+    trust_domain: Option<String>,
+}
+
+impl NodeHandle {
+    pub fn peer_id(&self) -> PeerId {
+        self.peer_id
+    }
+
+    pub fn registry(&self) -> Arc<Mutex<Registry>> {
+        Arc::clone(&self.registry)
+    }
+
+    pub fn metrics(&self) -> Arc<NodeMetrics> {
+        Arc::clone(&self.metrics)
+    }
+
+    /// Envoie une commande sans attendre de réponse
+    pub async fn send(&self, cmd: NodeCommand) -> Result<()> {
+        self.commands
+            .send(cmd)
+            .await
+            .map_err(|_| anyhow!("Node {} is stopped", self.peer_id))
+    }
+
+    pub async fn announce(&self) -> Result<()> {
+        self.send(NodeCommand::AnnounceNode).await
+    }
+
+    /// Publie une annonce composée par l'appelant, ex. pour usurper un autre nœud (banc de test)
+    #[cfg(any(test, feature = "harness"))]
+    pub async fn publish_announce(&self, announce: AnnounceMsg) -> Result<()> {
+        self.send(NodeCommand::PublishAnnounce(announce)).await
+    }
+
+    pub async fn dial(&self, addr: Multiaddr) -> Result<()> {
+        self.send(NodeCommand::Dial(addr)).await
+    }
+
+    pub async fn find_providers(&self) -> Result<HashSet<PeerId>> {
+        let (reply, rx) = oneshot::channel();
+        self.send(NodeCommand::FindProviders { reply }).await?;
+        Ok(rx.await?)
+    }
+
+    pub async fn connected_peers(&self) -> Result<Vec<PeerId>> {
+        let (reply, rx) = oneshot::channel();
+        self.send(NodeCommand::ConnectedPeers { reply }).await?;
+        Ok(rx.await?)
+    }
+
+    pub async fn listen_addrs(&self) -> Result<Vec<Multiaddr>> {
+        let (reply, rx) = oneshot::channel();
+        self.send(NodeCommand::ListenAddrs { reply }).await?;
+        Ok(rx.await?)
+    }
+
+    pub async fn publish_succession(&self, succession: KeySuccession) -> Result<()> {
+        self.send(NodeCommand::PublishSuccession(succession)).await
+    }
+
+    pub fn policy(&self) -> Arc<PolicyEngine> {
+        Arc::clone(&self.policy)
+    }
+
+    pub fn shards(&self) -> Arc<ShardHost> {
+        Arc::clone(&self.shards)
+    }
+
+    /// Exécute un shard chargé sur ce nœud, hors de la boucle d'événements
+    pub async fn execute(&self, shard_id: &str, input: Tensor) -> Result<Tensor> {
+        let shards = Arc::clone(&self.shards);
+        let shard_id = shard_id.to_string();
+        tokio::task::spawn_blocking(move || shards.forward(&shard_id, &input)).await?
+    }
+
+    /// Exécute un shard depuis un état de séquence vide, sans toucher à celui du shard chargé
+    pub async fn execute_isolated(&self, shard_id: &str, input: Tensor) -> Result<Tensor> {
+        let shards = Arc::clone(&self.shards);
+        let shard_id = shard_id.to_string();
+        tokio::task::spawn_blocking(move || shards.forward_isolated(&shard_id, &input)).await?
+    }
+
+    /// Charge un shard et l'annonce aussitôt
+    pub async fn load_shard(&self, spec: ShardSpec) -> Result<()> {
+        let shards = Arc::clone(&self.shards);
+        let backends = self.backends.clone();
+        tokio::task::spawn_blocking(move || shards.load(&backends, spec)).await??;
+        self.announce().await
+    }
+
+    /// Décharge un shard et retire son annonce
+    pub async fn unload_shard(&self, shard_id: &str) -> Result<bool> {
+        let unloaded = self.shards.unload(shard_id);
+        if unloaded {
+            self.announce().await?;
+        }
+        Ok(unloaded)
+    }
+
+    /// Journal d'audit du nœud, s'il est activé
+    pub fn audit(&self) -> Option<Arc<AuditLog>> {
+        self.audit.clone()
+    }
+
+    /// Trames de tenseurs vers `peer` pour la requête `request_id`, sur le flux de ce pair
+    /// (ouvert au premier usage)
+    pub async fn open_tensor_stream(&self, peer: PeerId, request_id: u64) -> Result<TensorStream> {
+        let link = self.tensor_links.lock()
+            .map(|mut links| Arc::clone(links.entry(peer).or_default()))
+            .map_err(|_| anyhow!("Tensor links poisoned"))?;
+        {
+            let mut stream = link.lock().await;
+            if stream.is_none() {
+                *stream = Some(self.connect_tensor_stream(peer).await?);
+            }
+        }
+        Ok(TensorStream::new(self.clone(), peer, link, request_id))
+    }
+
+    /// Ouvre un nouveau flux de tenseurs vers `peer`, connexion établie au besoin
+    pub(crate) async fn connect_tensor_stream(&self, peer: PeerId) -> Result<libp2p::Stream> {
+        let (reply, rx) = oneshot::channel();
+        self.send(NodeCommand::Connect { peer, reply }).await?;
+        rx.await??;
+        self.streams.clone().open_stream(peer, self.tensor_protocol.clone()).await
+            .map_err(|e| anyhow!("Cannot open tensor stream to {}: {}", peer, e))
+    }
+
+    /// Trames reçues de `peer` pour la requête `request_id`, y compris celles déjà arrivées
+    pub fn frames(&self, peer: PeerId, request_id: u64) -> Result<FrameReceiver> {
+        self.frames.subscribe(peer, request_id)
+    }
+
+    /// Routage MoE par défaut (section `moe:` de la configuration)
+    pub fn moe_config(&self) -> MoeConfig {
+        self.moe.clone()
+    }
+
+    /// Validation croisée par défaut (section `quorum:` de la configuration)
+    pub fn quorum_config(&self) -> QuorumConfig {
+        self.quorum.clone()
+    }
+
+    /// Réputation des pairs observée par ce nœud
+    pub fn reputation(&self) -> Arc<Reputation> {
+        Arc::clone(&self.reputation)
+    }
+
+    pub fn trust_domain(&self) -> Option<&str> {
+        self.trust_domain.as_deref()
+    }
+
+    /// Ordre des micro-lots de pipeline sur les shards de ce nœud
+    pub fn stage_sessions(&self) -> Arc<StageSessions> {
+        Arc::clone(&self.stages)
+    }
+
+    /// Requête directe à un pair (connexion ouverte au besoin avec ses adresses annoncées).
+    /// Les activations partent en trame sur un flux de tenseurs, la requête JSON ne porte que
+    /// le contrôle; la sortie revient de même. La sortie vers `peer` doit être autorisée.
+    pub async fn rpc(&self, peer: PeerId, mut request: RpcRequest) -> Result<RpcResponse> {
+        let request_id = rand::random();
+        let mut output = self.frames(peer, request_id)?;
+        let sent = Instant::now();
+        let input = std::mem::take(request.input_mut());
+        self.open_tensor_stream(peer, request_id).await?.send_authorized(&input).await?;
+
+        let (reply, rx) = oneshot::channel();
+        self.send(NodeCommand::Rpc { peer, call: RpcCall { request_id, request }, reply }).await?;
+        match rx.await?? {
+            RpcOutcome::Error { message, unavailable } => Ok(RpcResponse::Error { message, unavailable }),
+            RpcOutcome::Tensor => {
+                // La trame décodée remplit sa forme; une trame manquante est une faute du pair
+                let result = output.recv_timeout(Duration::from_secs(FRAME_TIMEOUT_SECS)).await
+                    .map_err(|e| anyhow!("Invalid tensor from {}: {:#}", peer, e));
+                let observation = match &result {
+                    Ok(_) => Observation::RpcSuccess { latency: sent.elapsed() },
+                    Err(_) => Observation::RpcFailure,
+                };
+                self.reputation.observe(&peer.to_string(), observation);
+                result.map(RpcResponse::Tensor)
+            },
+        }
+    }
+
+    /// Autorise une sortie directe de données vers `peer`: politique, puis journal d'audit
+    pub fn authorize_egress(&self, data: DataClass, peer: &PeerId, split: bool, content: &[u8]) -> Result<()> {
+        let trust_domain = trust_domain_of(&self.registry, peer);
+        self.policy.check(EgressRequest { data, peer: peer.to_string(), trust_domain, split })?;
+        if let Some(audit) = &self.audit {
+            audit.record_egress(peer, data, content)?;
+        }
+        Ok(())
+    }
+
+    pub async fn send_sealed(&self, data: DataClass, recipients: Vec<PeerId>, message: CommunicatorMessage) -> Result<()> {
+        let (reply, rx) = oneshot::channel();
+        self.send(NodeCommand::SendSealed { data, recipients, message, reply }).await?;
+        rx.await?
+    }
+
+    /// Messages scellés adressés à ce nœud, une fois déchiffrés
+    pub fn subscribe_messages(&self) -> broadcast::Receiver<InboundMessage> {
+        self.inbox.subscribe()
+    }
+
+    pub async fn update_access(&self, update: AccessUpdate) -> Result<AccessLists> {
+        let (reply, rx) = oneshot::channel();
+        self.send(NodeCommand::UpdateAccess { update, reply }).await?;
+        rx.await?
+    }
+
+    pub async fn access_lists(&self) -> Result<AccessLists> {
+        let (reply, rx) = oneshot::channel();
+        self.send(NodeCommand::AccessLists { reply }).await?;
+        Ok(rx.await?)
+    }
+
+    pub async fn apply_revocation(&self, list: RevocationList) -> Result<()> {
+        self.send(NodeCommand::ApplyRevocation(list)).await
+    }
+
+    /// Signe et diffuse un plan d'affectation calculé par ce nœud
+    pub async fn publish_plan(&self, plan: SchedulePlan) -> Result<SignedPlan> {
+        let (reply, rx) = oneshot::channel();
+        self.send(NodeCommand::PublishPlan { plan, reply }).await?;
+        Ok(rx.await?)
+    }
+
+    /// Plan en vigueur pour un modèle, publié par ce nœud ou reçu d'un routeur admis
+    pub fn plan(&self, model: &str) -> Option<SignedPlan> {
+        self.plans.lock().ok()?.get(model).cloned()
+    }
+
+    /// Successeur vérifié de `old` d'après la DHT (appliqué au registre s'il est trouvé)
+    pub async fn find_successor(&self, old: PeerId) -> Result<Option<KeySuccession>> {
+        let (reply, rx) = oneshot::channel();
+        self.send(NodeCommand::FindSuccessor { old, reply }).await?;
+        Ok(rx.await?)
+    }
+}
+
+/// Recherche de fournisseurs en cours, avec les résultats déjà reçus
+struct PendingProviders {
+    found: HashSet<PeerId>,
+    reply: oneshot::Sender<HashSet<PeerId>>,
+}
+
+/// Un nœud du mesh: swarm, registre et état de la boucle d'événements
+pub struct MeshNode {
+    swarm: Swarm<MeshBehaviour>,
+    local_peer_id: PeerId,
+    /// Clé d'identité, pour ouvrir et sceller les enveloppes
+    identity: ed25519::Keypair,
+    inbox: broadcast::Sender<InboundMessage>,
+    policy: Arc<PolicyEngine>,
+    audit: Option<Arc<AuditLog>>,
+    /// Shards chargés, exécutés pour ce nœud et annoncés au mesh
+    shards: Arc<ShardHost>,
+    options: NodeOptions,
+    namespace: TopicNamespace,
+    registry: Arc<Mutex<Registry>>,
+    metrics: Arc<NodeMetrics>,
+    trust: TrustStore,
+    access: AccessLists,
+    /// Bits de preuve de travail exigés des autres nœuds (0: admission libre)
+    admission_difficulty: u8,
+    /// Date de modification de `access.json` lors de la dernière lecture/écriture
+    access_mtime: Option<SystemTime>,
+    cmd_tx: mpsc::Sender<NodeCommand>,
+    cmd_rx: mpsc::Receiver<NodeCommand>,
+    discovery_key: RecordKey,
+    provided_keys: Vec<RecordKey>,
+    pending_providers: HashMap<QueryId, PendingProviders>,
+    pending_successors: HashMap<QueryId, oneshot::Sender<Option<KeySuccession>>>,
+    /// Poignée passée aux tâches qui traitent les requêtes directes reçues
+    handle: NodeHandle,
+    /// Requêtes directes émises, avec leur date d'envoi pour la réputation du pair
+    pending_rpc: HashMap<OutboundRequestId, (Instant, RpcReply)>,
+    /// Opérations vers des pairs non connectés, reprises dès que la connexion s'établit
+    awaiting_connection: HashMap<PeerId, Vec<AwaitingConnection>>,
+    tensor_streams: libp2p_stream::IncomingStreams,
+    /// Requêtes reçues en cours de traitement, en attente de leur réponse
+    inbound_rpc: HashMap<u64, ResponseChannel<RpcOutcome>>,
+    next_inbound_rpc: u64,
+    /// Bootstraps à recontacter après une coupure, et nombre d'échecs consécutifs
+    bootstrap_peers: HashMap<PeerId, Multiaddr>,
+    reconnect_attempts: HashMap<PeerId, u32>,
+    /// Budget mémoire des shards (Mo), annoncé moins les shards chargés
+    shard_memory_mb: Option<u64>,
+}
+
+impl MeshNode {
+    /// Construit le nœud sur le transport fourni et renvoie une poignée pour le piloter
+    pub async fn new(
+        keypair: Keypair,
+        config: &CortexConfig,
+        options: NodeOptions,
+        transport: BoxedTransport,
+    ) -> Result<(Self, NodeHandle)> {
+        let local_peer_id = PeerId::from(keypair.public());
+        let identity = keypair.clone().try_into_ed25519()
+            .map_err(|_| anyhow!("Mesh nodes need an ed25519 identity"))?;
+        let namespace = TopicNamespace::from_config(&config.mesh);
+        let role = options.role;
+        match role {
+            NodeRole::Bootstrap => println!("🌐 Nœud bootstrap avec PeerId: {}", local_peer_id),
+            NodeRole::Light => println!("🔹 Nœud léger avec PeerId: {}", local_peer_id),
+        }
+        println!("🏷️ Mesh: {} (protocole v{})", namespace.mesh(), namespace.version());
+
+        let trust = TrustStore::from_config(&config.trust)?;
+        let mut options = options;
+        let mut trust_domain = None;
+        if let Some(cert) = &options.certificate {
+            if cert.peer_id != local_peer_id.to_string() {
+                println!("⚠️ Certificat émis pour {}, ignoré", cert.peer_id);
+                options.certificate = None;
+            } else {
+                match trust.validate(cert, &cert.peer_id) {
+                    Ok(info) => {
+                        println!("🏛️ Certificat {} valide (rôles: {:?})", info.domain, info.roles);
+                        trust_domain = Some(info.domain);
+                    },
+                    Err(e) if trust.is_enabled() => println!("⚠️ Certificat {} non reconnu: {}", cert.trust_domain, e),
+                    Err(_) => println!("🏛️ Certificat du domaine {} présenté", cert.trust_domain),
+                }
+            }
+        }
+
+        // Coût d'admission: ce nœud doit lui-même présenter une preuve valide
+        let admission_difficulty = config.mesh.admission_difficulty;
+        if admission_difficulty > 0 {
+            let mesh = namespace.mesh().to_string();
+            let valid = options.admission.filter(|p| p.verify(&mesh, &local_peer_id, admission_difficulty));
+            let proof = match valid {
+                Some(proof) => proof,
+                None => tokio::task::spawn_blocking(move || {
+                    AdmissionProof::solve(&mesh, &local_peer_id, admission_difficulty)
+                })
+                .await?,
+            };
+            println!("⛏️ Preuve d'admission prête (difficulté {})", admission_difficulty);
+            options.admission = Some(proof);
+        }
+
+        // Listes d'accès: configuration, remplacée par l'état modifié à chaud s'il existe
+        let mut access = config.access.clone();
+        let mut access_mtime = None;
+        if let Some(path) = &options.access_path {
+            if let Some(saved) = AccessLists::load(path)? {
+                access = saved;
+                access_mtime = modified_at(path);
+            }
+        }
+        if !access.deny.is_empty() || access.allowlist_only {
+            println!("🚧 Listes d'accès: {} bloqués, {} autorisés", access.deny.len(), access.allow.len());
+        }
+
+        // Construction du comportement
+        let mut behaviour = build_mesh_behaviour(
+            keypair.clone(),
+            local_peer_id,
+            role,
+            &namespace,
+            &config.limits,
+            &access,
+            options.enable_mdns,
+        )
+        .await?;
+        if config.reputation.gossip_scoring {
+            enable_peer_scoring(&mut behaviour.gossipsub)?;
+        }
+
+        // Configuration spécifique bootstrap: démarrer en tant que fournisseur DHT
+        let discovery_key = namespace.discovery_key();
+        let mut provided_keys = Vec::new();
+        if role == NodeRole::Bootstrap {
+            provided_keys.push(discovery_key.clone());
+            provided_keys.extend(namespace.legacy_discovery_key());
+            for key in &provided_keys {
+                match behaviour.kad.start_providing(key.clone()) {
+                    Ok(query_id) => println!("✅ DHT StartProviding lancé avec succès, QueryId: {:?}", query_id),
+                    Err(e) => println!("⚠️ Échec de DHT StartProviding : {:?}. Continuité en mode bootstrap.", e),
+                }
+            }
+        }
+
+        // Les connexions inactives sont fermées pour ménager les petites machines
+        let swarm_config = SwarmConfig::with_tokio_executor()
+            .with_idle_connection_timeout(Duration::from_secs(config.limits.idle_connection_timeout_secs));
+        let mut swarm = Swarm::new(transport, behaviour, local_peer_id, swarm_config);
+
+        for addr in &options.listen_addrs {
+            match swarm.listen_on(addr.clone()) {
+                Ok(_) => println!("Écoute démarrée sur {}", addr),
+                Err(e) => println!("⚠️ Impossible d'écouter sur {}: {}", addr, e),
+            }
+        }
+
+        let mut bootstrap_peers = HashMap::new();
+        for addr in &options.bootstrap_peers {
+            if let Some((addr, peer_id)) = parse_bootstrap_addr(&addr.to_string()) {
+                bootstrap_peers.insert(peer_id, addr.clone());
+                // Un bootstrap configuré est un pair connu, même en mode liste d'autorisation
+                if let Some(allowed) = swarm.behaviour_mut().allowed.as_mut() {
+                    allowed.allow_peer(peer_id);
+                }
+                println!("🌐 Connexion au nœud bootstrap: {} @ {}", peer_id, addr);
+                swarm.behaviour_mut().kad.add_address(&peer_id, addr.clone());
+
+                // Tentative de connexion directe
+                match swarm.dial(addr.clone()) {
+                    Ok(_) => println!("✅ Tentative de connexion à {}", addr),
+                    Err(e) => println!("❌ Échec de connexion à {}: {:?}", addr, e),
+                }
+            }
+        }
+
+        // Registre partagé, repris depuis le dernier arrêt propre
+        let ttl = Duration::from_secs(config.mesh.node_ttl_secs);
+        let registry = match options.registry_path.as_deref().map(|path| Registry::load_from(path, ttl)) {
+            Some(Ok(reg)) => {
+                println!("📂 Registry restauré ({} nœuds)", reg.nodes.len());
+                reg
+            }
+            _ => Registry::with_ttl(ttl),
+        };
+        let registry = Arc::new(Mutex::new(registry));
+        let metrics = Arc::new(NodeMetrics::default());
+        let reputation = match options.reputation_path.as_deref().map(|path| Reputation::load_from(path, config.reputation.clone())) {
+            Some(Ok(reputation)) => {
+                println!("📂 Réputation restaurée ({} pairs)", reputation.scores().len());
+                reputation
+            }
+            _ => Reputation::new(config.reputation.clone()),
+        };
+
+        // Canal pour les commandes planifiées et externes
+        let (cmd_tx, cmd_rx) = mpsc::channel::<NodeCommand>(32);
+        let (inbox, _) = broadcast::channel(INBOX_CAPACITY);
+        let tensor_protocol = tensor_stream::protocol(&namespace);
+        let mut streams = swarm.behaviour().stream.new_control();
+        let tensor_streams = streams.accept(tensor_protocol.clone())
+            .map_err(|_| anyhow!("Tensor stream protocol already registered"))?;
+        let audit = match &options.audit_dir {
+            Some(dir) => {
+                let log = AuditLog::open(dir, identity.clone())?;
+                println!("📜 Journal d'audit: {:?}", log.path());
+                Some(Arc::new(log))
+            }
+            None => None,
+        };
+        let mut policy = PolicyEngine::new(config.policy.clone());
+        if let Some(audit) = &audit {
+            policy = policy.with_audit(Arc::clone(audit));
+        }
+        let policy = Arc::new(policy);
+
+        // Un shard qui ne se charge pas n'est pas annoncé, le nœud démarre quand même
+        let shards = Arc::new(ShardHost::new());
+        for spec in &config.shards {
+            match shards.load(&options.backends, spec.clone()) {
+                Ok(()) => println!("🧩 Shard {} chargé (backend {})", spec.id, spec.backend),
+                Err(e) => println!("⚠️ Shard {} non chargé: {:?}", spec.id, e),
+            }
+        }
+
+        let handle = NodeHandle {
+            peer_id: local_peer_id,
+            commands: cmd_tx.clone(),
+            registry: Arc::clone(&registry),
+            metrics: Arc::clone(&metrics),
+            policy: Arc::clone(&policy),
+            audit: audit.clone(),
+            shards: Arc::clone(&shards),
+            backends: options.backends.clone(),
+            inbox: inbox.clone(),
+            stages: Arc::new(StageSessions::default()),
+            moe: config.moe.clone(),
+            quorum: config.quorum.clone(),
+            reputation: Arc::new(reputation),
+            streams,
+            tensor_protocol,
+            frames: Arc::new(FrameRouter::default()),
+            tensor_links: Arc::new(Mutex::new(HashMap::new())),
+            plans: Arc::new(Mutex::new(HashMap::new())),
+            trust_domain,
+        };
+
+        let node = MeshNode {
+            swarm,
+            local_peer_id,
+            identity,
+            inbox,
+            policy,
+            audit,
+            shards,
+            options,
+            namespace,
+            registry,
+            metrics,
+            trust,
+            access,
+            admission_difficulty,
+            access_mtime,
+            cmd_tx,
+            cmd_rx,
+            discovery_key,
+            provided_keys,
+            pending_providers: HashMap::new(),
+            pending_successors: HashMap::new(),
+            handle: handle.clone(),
+            pending_rpc: HashMap::new(),
+            awaiting_connection: HashMap::new(),
+            tensor_streams,
+            inbound_rpc: HashMap::new(),
+            next_inbound_rpc: 0,
+            bootstrap_peers,
+            reconnect_attempts: HashMap::new(),
+            shard_memory_mb: config.limits.shard_memory_mb,
+        };
+        Ok((node, handle))
+    }
+
+    /// Boucle principale d'un nœud, jusqu'à ce que `shutdown` se termine.
+    ///
+    /// À l'arrêt, le nœud publie une annonce de départ, cesse de fournir ses clés DHT,
+    /// laisse les requêtes en cours se terminer (au plus `DRAIN_TIMEOUT`) puis persiste le registre
+    /// et la réputation des pairs.
+    pub async fn run<F>(mut self, shutdown: F) -> Result<()>
+    where
+        F: Future<Output = ()>,
+    {
+        let role = self.options.role;
+        if self.options.periodic_tasks {
+            spawn_periodic_tasks(role, self.cmd_tx.clone(), Arc::clone(&self.registry), Arc::clone(&self.metrics));
+        }
+
+        // Attendre que les addresses d'écoute soient établies
+        let mut listening = false;
+        while !listening {
+            if let SwarmEvent::NewListenAddr { address, .. } = self.swarm.select_next_some().await {
+                println!("📡 Nœud {} en écoute sur: {}", role.label(), address);
+                listening = true;
+            }
+        }
+
+        // Nettoyage régulier des nœuds expirés
+        let ttl = self.registry.lock().map(|r| r.ttl()).unwrap_or(Duration::from_secs(MAX_PRUNE_INTERVAL));
+        let mut prune_interval = tokio::time::interval((ttl / 4).min(Duration::from_secs(MAX_PRUNE_INTERVAL)));
+        let mut access_interval = tokio::time::interval(Duration::from_secs(ACCESS_RELOAD_INTERVAL));
+
+        // Boucle principale
+        tokio::pin!(shutdown);
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => {
+                    println!("🛑 Signal d'arrêt reçu");
+                    break;
+                },
+                _ = prune_interval.tick() => self.prune_registry(),
+                _ = access_interval.tick(), if self.options.access_path.is_some() => self.reload_access(),
+                Some(cmd) = self.cmd_rx.recv() => self.handle_command(cmd),
+                Some((peer, stream)) = self.tensor_streams.next() => self.on_tensor_stream(peer, stream),
+                event = self.swarm.select_next_some() => self.handle_swarm_event(event),
+            }
+        }
+
+        self.shutdown().await;
+        // Les tâches périodiques s'arrêtent d'elles-mêmes une fois le canal fermé
+        self.cmd_rx.close();
+        self.shards.unload_all();
+        Ok(())
+    }
+
+    fn prune_registry(&mut self) {
+        let present: Vec<String> = match self.registry.lock() {
+            Ok(mut reg) => {
+                for node_id in reg.prune() {
+                    println!("⌛ Nœud expiré retiré du registry: {}", node_id);
+                }
+                reg.nodes.keys().cloned().collect()
+            }
+            Err(_) => return,
+        };
+        self.update_reputation(&present);
+    }
+
+    /// Relevé de disponibilité des pairs, reporté dans le score applicatif de gossipsub
+    fn update_reputation(&mut self, present: &[String]) {
+        let reputation = self.handle.reputation();
+        reputation.record_presence(present);
+        if !reputation.config().gossip_scoring {
+            return;
+        }
+        let gossipsub = &mut self.swarm.behaviour_mut().gossipsub;
+        let peers: Vec<PeerId> = gossipsub.all_peers().map(|(peer, _)| *peer).collect();
+        for peer in peers {
+            gossipsub.set_application_score(&peer, reputation.gossip_score(&peer.to_string()));
+        }
+    }
+
+    /// Message gossip invalide: pénalise le pair qui l'a émis
+    fn report_invalid(&self, source: Option<PeerId>) {
+        if let Some(peer) = source {
+            self.handle.reputation.observe(&peer.to_string(), Observation::InvalidMessage);
+        }
+    }
+
+    /// Programme une nouvelle tentative de connexion à un bootstrap, avec attente exponentielle
+    fn schedule_reconnect(&mut self, peer_id: PeerId) {
+        let Some(addr) = self.bootstrap_peers.get(&peer_id).cloned() else {
+            return;
+        };
+        let attempts = self.reconnect_attempts.entry(peer_id).or_insert(0);
+        let backoff = Duration::from_secs(1u64 << (*attempts).min(6)).min(self.options.reconnect_max_backoff);
+        *attempts += 1;
+
+        println!("🔁 Reconnexion au bootstrap {} dans {:?}", peer_id, backoff);
+        let cmd_tx = self.cmd_tx.clone();
+        tokio::spawn(async move {
+            sleep(backoff).await;
+            let _ = cmd_tx.send(NodeCommand::Dial(addr)).await;
+        });
+    }
+
+    fn handle_command(&mut self, cmd: NodeCommand) {
+        match cmd {
+            NodeCommand::GetProviders => {
+                println!("🔍 Recherche de fournisseurs pour la clé: {:?}", self.discovery_key);
+                self.swarm.behaviour_mut().kad.get_providers(self.discovery_key.clone());
+            },
+            NodeCommand::AnnounceNode => {
+                let announce = self.build_announce(false);
+                publish_announce(&mut self.swarm.behaviour_mut().gossipsub, &self.namespace, &announce);
+                self.publish_successions();
+                self.publish_revocations();
+                self.publish_plans();
+                self.publish_attestation();
+            },
+            #[cfg(any(test, feature = "harness"))]
+            NodeCommand::PublishAnnounce(announce) => {
+                publish_announce(&mut self.swarm.behaviour_mut().gossipsub, &self.namespace, &announce);
+            },
+            NodeCommand::Dial(addr) => {
+                if let Err(e) = self.swarm.dial(addr.clone()) {
+                    println!("❌ Échec de connexion à {}: {:?}", addr, e);
+                }
+            },
+            NodeCommand::FindProviders { reply } => {
+                let query_id = self.swarm.behaviour_mut().kad.get_providers(self.discovery_key.clone());
+                self.pending_providers.insert(query_id, PendingProviders { found: HashSet::new(), reply });
+            },
+            NodeCommand::ConnectedPeers { reply } => {
+                let _ = reply.send(self.swarm.connected_peers().copied().collect());
+            },
+            NodeCommand::ListenAddrs { reply } => {
+                let _ = reply.send(self.swarm.listeners().cloned().collect());
+            },
+            NodeCommand::PublishSuccession(succession) => {
+                if !self.options.successions.contains(&succession) {
+                    self.options.successions.push(succession);
+                }
+                self.publish_successions();
+            },
+            NodeCommand::UpdateAccess { update, reply } => {
+                let mut access = self.access.clone();
+                let result = access.apply(&update).map(|_| {
+                    self.set_access(access, true);
+                    self.access.clone()
+                });
+                let _ = reply.send(result);
+            },
+            NodeCommand::AccessLists { reply } => {
+                let _ = reply.send(self.access.clone());
+            },
+            NodeCommand::SendSealed { data, recipients, message, reply } => {
+                let _ = reply.send(self.send_sealed(data, &recipients, &message));
+            },
+            NodeCommand::ApplyRevocation(list) => {
+                if self.apply_revocation(list) {
+                    self.publish_revocations();
+                }
+            },
+            NodeCommand::FindSuccessor { old, reply } => {
+                let key = self.namespace.succession_key(&old.to_string());
+                let query_id = self.swarm.behaviour_mut().kad.get_record(key);
+                self.pending_successors.insert(query_id, reply);
+            },
+            NodeCommand::Rpc { peer, call, reply } => self.send_rpc(peer, call, reply),
+            NodeCommand::Connect { peer, reply } => {
+                if self.swarm.is_connected(&peer) {
+                    let _ = reply.send(Ok(()));
+                } else {
+                    self.connect_then(peer, AwaitingConnection::Connect(reply));
+                }
+            },
+            NodeCommand::PublishPlan { plan, reply } => {
+                let signed = SignedPlan::sign(&self.identity, plan);
+                println!("🗺️ Plan publié pour {} ({})", signed.plan.model, signed.digest);
+                self.store_plan(signed.clone());
+                publish_signed(&mut self.swarm.behaviour_mut().gossipsub, &self.namespace, Channel::Plan, &signed);
+                let _ = reply.send(signed);
+            },
+            NodeCommand::RespondRpc { id, outcome } => {
+                if let Some(channel) = self.inbound_rpc.remove(&id) {
+                    if self.swarm.behaviour_mut().rpc.send_response(channel, outcome).is_err() {
+                        println!("⚠️ Réponse RPC perdue: le pair s'est déconnecté");
+                    }
+                }
+            },
+        }
+    }
+
+    /// Envoie une requête directe; un pair non connecté est d'abord joint via ses adresses annoncées
+    fn send_rpc(&mut self, peer: PeerId, call: RpcCall, reply: RpcReply) {
+        if self.swarm.is_connected(&peer) {
+            let request_id = self.swarm.behaviour_mut().rpc.send_request(&peer, call);
+            self.pending_rpc.insert(request_id, (Instant::now(), reply));
+        } else {
+            self.connect_then(peer, AwaitingConnection::Rpc(call, reply));
+        }
+    }
+
+    /// Compose une connexion vers un pair non connecté, puis reprend `then` une fois établie
+    fn connect_then(&mut self, peer: PeerId, then: AwaitingConnection) {
+        if let Some(queued) = self.awaiting_connection.get_mut(&peer) {
+            queued.push(then);
+            return;
+        }
+        let addrs: Vec<Multiaddr> = self.registry.lock().ok()
+            .and_then(|reg| reg.nodes.get(&peer.to_string()).map(|entry| entry.addrs.clone()))
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|addr| addr.parse().ok())
+            .collect();
+        if addrs.is_empty() {
+            then.fail(anyhow!("No known address for {}", peer));
+            return;
+        }
+        if let Err(e) = self.swarm.dial(DialOpts::peer_id(peer).addresses(addrs).build()) {
+            then.fail(anyhow!("Cannot dial {}: {}", peer, e));
+            return;
+        }
+        self.awaiting_connection.insert(peer, vec![then]);
+    }
+
+    /// Connexion établie: reprend les opérations en attente
+    fn resume_awaiting(&mut self, peer: &PeerId) {
+        for awaiting in self.awaiting_connection.remove(peer).unwrap_or_default() {
+            match awaiting {
+                AwaitingConnection::Rpc(call, reply) => {
+                    let request_id = self.swarm.behaviour_mut().rpc.send_request(peer, call);
+                    self.pending_rpc.insert(request_id, (Instant::now(), reply));
+                },
+                AwaitingConnection::Connect(reply) => {
+                    let _ = reply.send(Ok(()));
+                },
+            }
+        }
+    }
+
+    /// Connexion impossible: échoue les opérations en attente
+    fn fail_awaiting(&mut self, peer: &PeerId, error: &str) {
+        for awaiting in self.awaiting_connection.remove(peer).unwrap_or_default() {
+            if matches!(awaiting, AwaitingConnection::Rpc(..)) {
+                self.handle.reputation.observe(&peer.to_string(), Observation::RpcFailure);
+            }
+            awaiting.fail(anyhow!("Connection to {} failed: {}", peer, error));
+        }
+    }
+
+    /// Flux de tenseurs entrant: lu dans une tâche séparée si le pair est autorisé
+    fn on_tensor_stream(&mut self, peer: PeerId, stream: libp2p::Stream) {
+        if !self.access.permits(&peer.to_string()) {
+            println!("🚫 Flux de tenseurs d'un pair refusé ignoré: {}", peer);
+            return;
+        }
+        tokio::spawn(tensor_stream::receive_frames(peer, stream, Arc::clone(&self.handle.frames)));
+    }
+
+    /// Traite une requête directe dans une tâche séparée: une étape de pipeline peut attendre
+    /// les micro-lots précédents puis l'étape suivante, sans bloquer la boucle du nœud
+    fn on_rpc_request(&mut self, peer: PeerId, call: RpcCall, channel: ResponseChannel<RpcOutcome>) {
+        if !self.access.permits(&peer.to_string()) {
+            println!("🚫 Requête RPC d'un pair refusé ignorée: {}", peer);
+            return;
+        }
+        let id = self.next_inbound_rpc;
+        self.next_inbound_rpc += 1;
+        self.inbound_rpc.insert(id, channel);
+
+        let handle = self.handle.clone();
+        let cmd_tx = self.cmd_tx.clone();
+        tokio::spawn(async move {
+            // Trame décodée: ses données remplissent la forme annoncée
+            let RpcCall { request_id, mut request } = call;
+            let timeout = Duration::from_secs(FRAME_TIMEOUT_SECS);
+            let result = match handle.frames(peer, request_id) {
+                Ok(mut frames) => frames.recv_timeout(timeout).await,
+                Err(e) => Err(e),
+            };
+            let result = match result {
+                Err(e) => Err(e),
+                Ok(input) => {
+                    *request.input_mut() = input;
+                    match request {
+                        RpcRequest::Stage(stage) => serve_stage(handle.clone(), peer, stage).await,
+                        RpcRequest::Expert(expert) => serve_expert(handle.clone(), peer, expert).await,
+                        RpcRequest::Replica(replica) => serve_replica(handle.clone(), peer, replica).await,
+                    }
+                },
+            };
+            // La sortie repart sur un flux de tenseurs avant la réponse de contrôle qui l'annonce
+            let result = match result {
+                Ok(output) => reply_frame(&handle, peer, request_id, &output).await,
+                Err(e) => Err(e),
+            };
+            let outcome = match result {
+                Ok(()) => RpcOutcome::Tensor,
+                Err(e) => RpcOutcome::error(&e),
+            };
+            let _ = cmd_tx.send(NodeCommand::RespondRpc { id, outcome }).await;
+        });
+    }
+
+    fn on_rpc_event(&mut self, event: RpcEvent<RpcCall, RpcOutcome>) {
+        match event {
+            RpcEvent::Message { peer, message: RpcMessage::Request { request, channel, .. } } => {
+                self.on_rpc_request(peer, request, channel);
+            },
+            RpcEvent::Message { peer, message: RpcMessage::Response { request_id, response } } => {
+                if let Some((_, reply)) = self.pending_rpc.remove(&request_id) {
+                    // Un succès n'est compté qu'à la réception de la trame de sortie (`NodeHandle::rpc`);
+                    // un étage suivant injoignable n'est pas imputable au pair qui le signale
+                    if let RpcOutcome::Error { unavailable: None, .. } = response {
+                        self.handle.reputation.observe(&peer.to_string(), Observation::RpcFailure);
+                    }
+                    let _ = reply.send(Ok(response));
+                }
+            },
+            RpcEvent::OutboundFailure { peer, request_id, error } => {
+                println!("⚠️ Requête RPC vers {} échouée: {}", peer, error);
+                self.handle.reputation.observe(&peer.to_string(), Observation::RpcFailure);
+                if let Some((_, reply)) = self.pending_rpc.remove(&request_id) {
+                    let _ = reply.send(Err(anyhow!("RPC to {} failed: {}", peer, error)));
+                }
+            },
+            RpcEvent::InboundFailure { peer, error, .. } => {
+                println!("⚠️ Requête RPC de {} non aboutie: {}", peer, error);
+            },
+            RpcEvent::ResponseSent { .. } => {}
+        }
+    }
+
+    /// Republie les certificats de succession récents de ce nœud (gossip + enregistrement DHT)
+    fn publish_successions(&mut self) {
+        let recent: Vec<KeySuccession> = self.options.successions.iter()
+            .filter(|s| s.age_secs() < SUCCESSION_REPUBLISH_SECS)
+            .cloned()
+            .collect();
+        for succession in recent {
+            let behaviour = self.swarm.behaviour_mut();
+            publish_signed(&mut behaviour.gossipsub, &self.namespace, Channel::Succession, &succession);
+
+            let Ok(value) = serde_json::to_vec(&succession) else { continue };
+            let record = Record::new(self.namespace.succession_key(&succession.old_peer_id), value);
+            if let Err(e) = behaviour.kad.put_record(record, Quorum::One) {
+                println!("⚠️ Échec de l'enregistrement DHT de la succession: {:?}", e);
+            }
+        }
+    }
+
+    /// Remplace les listes d'accès: synchronise le swarm, purge le registre et persiste si demandé
+    fn set_access(&mut self, access: AccessLists, persist: bool) {
+        let old = std::mem::replace(&mut self.access, access);
+        let behaviour = self.swarm.behaviour_mut();
+        for peer in old.denied_peers() {
+            if !self.access.deny.contains(&peer.to_string()) {
+                behaviour.blocked.unblock_peer(peer);
+                println!("✅ Pair débloqué: {}", peer);
+            }
+        }
+        for peer in self.access.denied_peers() {
+            if !old.deny.contains(&peer.to_string()) {
+                behaviour.blocked.block_peer(peer);
+                println!("⛔ Pair bloqué: {}", peer);
+            }
+        }
+        if let Some(allowed) = behaviour.allowed.as_mut() {
+            for peer in old.allowed_peers() {
+                if !self.access.allow.contains(&peer.to_string()) {
+                    allowed.disallow_peer(peer);
+                }
+            }
+            for peer in self.access.allowed_peers() {
+                allowed.allow_peer(peer);
+            }
+        }
+        if old.allowlist_only != self.access.allowlist_only {
+            println!("⚠️ Changement du mode liste d'autorisation: pris en compte au prochain démarrage");
+        }
+
+        // Les annonces déjà reçues de pairs désormais refusés sont oubliées
+        if let Ok(mut reg) = self.registry.lock() {
+            let refused: Vec<String> = reg.nodes.keys().filter(|id| !self.access.permits(id)).cloned().collect();
+            for id in refused {
+                reg.remove_node(&id);
+            }
+        }
+
+        if persist {
+            if let Some(path) = &self.options.access_path {
+                match self.access.save(path) {
+                    Ok(()) => self.access_mtime = modified_at(path),
+                    Err(e) => println!("⚠️ Impossible de sauvegarder les listes d'accès: {:?}", e),
+                }
+            }
+        }
+    }
+
+    /// Recharge `access.json` s'il a été modifié hors du nœud (CLI)
+    fn reload_access(&mut self) {
+        let Some(path) = self.options.access_path.clone() else {
+            return;
+        };
+        let mtime = modified_at(&path);
+        if mtime.is_none() || mtime == self.access_mtime {
+            return;
+        }
+        match AccessLists::load(&path) {
+            Ok(Some(access)) => {
+                println!("🔄 Listes d'accès rechargées depuis {:?}", path);
+                self.access_mtime = mtime;
+                self.set_access(access, false);
+            }
+            Ok(None) => {}
+            Err(e) => println!("⚠️ Listes d'accès illisibles, conservées telles quelles: {:?}", e),
+        }
+    }
+
+    /// Rediffuse les listes de révocation connues, pour les nœuds arrivés après leur émission
+    fn publish_revocations(&mut self) {
+        let lists: Vec<RevocationList> = self.trust.revocation_lists().cloned().collect();
+        for list in lists {
+            publish_signed(&mut self.swarm.behaviour_mut().gossipsub, &self.namespace, Channel::Trust, &list);
+        }
+    }
+
+    /// Applique une liste de révocation signée; renvoie `true` si elle est nouvelle
+    fn apply_revocation(&mut self, list: RevocationList) -> bool {
+        match self.trust.apply_revocation(list.clone()) {
+            Ok(true) => {
+                if let Ok(mut reg) = self.registry.lock() {
+                    for node_id in reg.apply_revocation(&list) {
+                        println!("⛔ Certificat révoqué pour {} ({})", node_id, list.trust_domain);
+                    }
+                }
+                true
+            }
+            Ok(false) => false,
+            Err(e) => {
+                println!("🚫 Liste de révocation refusée ({}): {}", list.trust_domain, e);
+                false
+            }
+        }
+    }
+
+    /// Envoi scellé: politique, puis journal d'audit (aucune sortie non consignée), puis diffusion
+    fn send_sealed(&mut self, data: DataClass, recipients: &[PeerId], message: &CommunicatorMessage) -> Result<()> {
+        self.check_egress(data, recipients)?;
+        let envelope = seal_message(&self.identity, recipients, message)?;
+        if let Some(audit) = &self.audit {
+            let content = serde_json::to_vec(message)?;
+            for peer in recipients {
+                audit.record_egress(peer, data, &content)?;
+            }
+        }
+        let gossipsub = &mut self.swarm.behaviour_mut().gossipsub;
+        publish_signed(gossipsub, &self.namespace, Channel::Communicator, &envelope);
+        Ok(())
+    }
+
+    /// Consulte la politique pour chaque destinataire; un seul refus bloque tout l'envoi
+    fn check_egress(&self, data: DataClass, recipients: &[PeerId]) -> Result<()> {
+        for peer in recipients {
+            let trust_domain = trust_domain_of(&self.registry, peer);
+            self.policy.check(EgressRequest { data, peer: peer.to_string(), trust_domain, split: false })?;
+        }
+        Ok(())
+    }
+
+    /// Ouvre les enveloppes adressées à ce nœud; les autres restent illisibles et sont ignorées
+    fn on_communicator_message(&mut self, message: &GossipsubMessage) {
+        let Ok(envelope) = serde_json::from_slice::<SealedEnvelope>(&message.data) else {
+            println!("🚫 Message communicator non scellé ignoré");
+            self.report_invalid(message.source);
+            return;
+        };
+        match open_message(&self.identity, &envelope) {
+            Ok(Some(inbound)) => {
+                if !self.access.permits(&inbound.from.to_string()) {
+                    println!("🚫 Message d'un pair refusé ignoré: {}", inbound.from);
+                    return;
+                }
+                println!("✉️ Message scellé reçu de {}: {}", inbound.from, self.policy.loggable(&inbound.message.payload));
+                let _ = self.inbox.send(inbound);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                println!("⚠️ Enveloppe invalide: {:?}", e);
+                self.report_invalid(message.source);
+            }
+        }
+    }
+
+    fn on_trust_message(&mut self, message: &GossipsubMessage) {
+        let accepted = TopicNamespace::parse_hash(&message.topic)
+            .is_some_and(|parsed| self.namespace.accepts_topic(&parsed));
+        if !accepted {
+            return;
+        }
+        if let Ok(list) = serde_json::from_slice::<RevocationList>(&message.data) {
+            self.apply_revocation(list);
+        }
+    }
+
+    /// Plan d'affectation reçu: retenu s'il est signé par un routeur certifié, daté d'avant
+    /// maintenant (au décalage d'horloge près) et plus récent que le plan en vigueur
+    fn on_plan_message(&mut self, message: &GossipsubMessage) {
+        let accepted = TopicNamespace::parse_hash(&message.topic)
+            .is_some_and(|parsed| self.namespace.accepts_topic(&parsed));
+        if !accepted {
+            return;
+        }
+        let Ok(signed) = serde_json::from_slice::<SignedPlan>(&message.data) else {
+            return;
+        };
+        if let Err(e) = signed.verify() {
+            println!("🚫 Plan refusé: {}", e);
+            self.report_invalid(message.source);
+            return;
+        }
+        if signed.is_future_dated(unix_now()) {
+            println!("🚫 Plan de {} ignoré: daté du futur ({})", signed.signer, signed.issued_at);
+            return;
+        }
+        let (admitted, router, retired) = self.registry.lock()
+            .map(|reg| {
+                let router = reg.holds_role(&signed.signer, ROUTER_ROLE);
+                let retired = reg.successors.keys().any(|old| *old == signed.signer || signed.plan.involves(old));
+                (reg.eligible_nodes().contains(&signed.signer), router, retired)
+            })
+            .unwrap_or((false, false, false));
+        if !admitted {
+            println!("🚫 Plan de {} ignoré: signataire non admis", signed.signer);
+            return;
+        }
+        if !router {
+            println!("🚫 Plan de {} ignoré: signataire sans certificat de routeur", signed.signer);
+            return;
+        }
+        if retired {
+            println!("🚫 Plan de {} ignoré: il désigne une identité retirée", signed.signer);
+            return;
+        }
+        if self.store_plan(signed.clone()) {
+            println!("🗺️ Plan reçu pour {} de {} ({})", signed.plan.model, signed.signer, signed.digest);
+        }
+    }
+
+    /// Publie les scores observés par ce nœud, signés, si la configuration le prévoit
+    fn publish_attestation(&mut self) {
+        let reputation = self.handle.reputation();
+        if !reputation.config().share_attestations {
+            return;
+        }
+        let attestation = ReputationAttestation::sign(&self.identity, reputation.scores());
+        publish_signed(&mut self.swarm.behaviour_mut().gossipsub, &self.namespace, Channel::Reputation, &attestation);
+    }
+
+    /// Attestation de réputation reçue: retenue si elle est signée par un nœud admis
+    fn on_reputation_message(&mut self, message: &GossipsubMessage) {
+        let accepted = TopicNamespace::parse_hash(&message.topic)
+            .is_some_and(|parsed| self.namespace.accepts_topic(&parsed));
+        if !accepted {
+            return;
+        }
+        let Ok(attestation) = serde_json::from_slice::<ReputationAttestation>(&message.data) else {
+            self.report_invalid(message.source);
+            return;
+        };
+        if let Err(e) = attestation.verify() {
+            println!("🚫 Attestation de réputation refusée: {}", e);
+            self.report_invalid(message.source);
+            return;
+        }
+        if attestation.issuer == self.local_peer_id.to_string() {
+            return;
+        }
+        let admitted = self.registry.lock()
+            .map(|reg| reg.eligible_nodes().contains(&attestation.issuer))
+            .unwrap_or(false);
+        if !admitted {
+            println!("🚫 Attestation de {} ignorée: émetteur non admis", attestation.issuer);
+            return;
+        }
+        self.handle.reputation.store_attestation(attestation);
+    }
+
+    /// Retient un plan s'il remplace le précédent du même modèle; renvoie `true` s'il est nouveau
+    fn store_plan(&self, signed: SignedPlan) -> bool {
+        let Ok(mut plans) = self.handle.plans.lock() else {
+            return false;
+        };
+        let newer = plans.get(&signed.plan.model)
+            .map_or(true, |current| (signed.issued_at, &signed.digest) > (current.issued_at, &current.digest));
+        if newer {
+            plans.insert(signed.plan.model.clone(), signed);
+        }
+        newer
+    }
+
+    /// Après une succession `old` -> `new`: les plans signés par ce nœud qui confient des shards
+    /// à `old`, ou signés par l'ancienne identité de ce nœud, sont réécrits pour `new`, signés de
+    /// nouveau et rediffusés. Les plans des autres routeurs restent en vigueur jusqu'à l'arrivée
+    /// de leur remplaçant.
+    fn reissue_plans(&mut self, old: &str, new: &str) -> usize {
+        let own = self.local_peer_id.to_string();
+        let reissued: Vec<SignedPlan> = match self.handle.plans.lock() {
+            Ok(mut plans) => plans.values_mut()
+                .filter_map(|signed| {
+                    let mut plan = signed.plan.clone();
+                    let moved = plan.replace_peer(old, new);
+                    let inherited = signed.signer == old && new == own;
+                    if !(inherited || (moved && signed.signer == own)) {
+                        return None;
+                    }
+                    *signed = signed.reissue(&self.identity, plan);
+                    Some(signed.clone())
+                })
+                .collect(),
+            Err(_) => return 0,
+        };
+        for signed in &reissued {
+            publish_signed(&mut self.swarm.behaviour_mut().gossipsub, &self.namespace, Channel::Plan, signed);
+        }
+        reissued.len()
+    }
+
+    /// Rediffuse les plans signés par ce nœud, pour les nœuds arrivés après leur émission
+    fn publish_plans(&mut self) {
+        let own = self.local_peer_id.to_string();
+        let plans: Vec<SignedPlan> = self.handle.plans.lock()
+            .map(|plans| plans.values().filter(|p| p.signer == own).cloned().collect())
+            .unwrap_or_default();
+        for signed in plans {
+            publish_signed(&mut self.swarm.behaviour_mut().gossipsub, &self.namespace, Channel::Plan, &signed);
+        }
+    }
+
+    /// Valide le certificat joint à une annonce (`None` si absent ou refusé)
+    fn validate_certificate(&self, msg: &AnnounceMsg) -> Option<crate::trust::TrustInfo> {
+        let cert = msg.certificate.as_ref()?;
+        match self.trust.validate(cert, &msg.node_id) {
+            Ok(info) => Some(info),
+            Err(e) => {
+                println!("🚫 Certificat de {} refusé: {}", msg.node_id, e);
+                None
+            }
+        }
+    }
+
+    /// Vérifie la preuve d'admission d'une annonce; un nœud non admis est retiré de la table
+    /// Kademlia pour qu'une multitude d'identités gratuites ne puisse pas l'envahir
+    fn verify_admission(&mut self, msg: &AnnounceMsg) -> bool {
+        if self.admission_difficulty == 0 {
+            return true;
+        }
+        let Ok(peer_id) = msg.node_id.parse::<PeerId>() else {
+            return false;
+        };
+        let admitted = msg.admission
+            .is_some_and(|proof| proof.verify(self.namespace.mesh(), &peer_id, self.admission_difficulty));
+        if !admitted {
+            println!("🚫 Preuve d'admission absente ou insuffisante: {}", msg.node_id);
+            self.handle.reputation.observe(&msg.node_id, Observation::InvalidMessage);
+            self.swarm.behaviour_mut().kad.remove_peer(&peer_id);
+        }
+        admitted
+    }
+
+    /// Applique une succession vérifiée: migration du registre et copie locale pour la DHT
+    fn apply_succession(&mut self, succession: &KeySuccession) {
+        let local = self.local_peer_id.to_string();
+        if succession.new_peer_id == local {
+            self.reissue_plans(&succession.old_peer_id, &local);
+            return;
+        }
+        if succession.old_peer_id == local {
+            println!("🚨 Une succession désigne {} comme successeur de ce nœud: clé compromise ?", succession.new_peer_id);
+            return;
+        }
+
+        if let Ok(mut reg) = self.registry.lock() {
+            match reg.successors.get(&succession.old_peer_id) {
+                Some(known) if *known == succession.new_peer_id => return,
+                Some(known) => {
+                    println!(
+                        "⚠️ Succession concurrente pour {} ignorée: {} déjà retenu, {} proposé",
+                        succession.old_peer_id, known, succession.new_peer_id
+                    );
+                    return;
+                }
+                None => {}
+            }
+            let moved = reg.migrate(&succession.old_peer_id, &succession.new_peer_id);
+            self.handle.reputation.migrate(&succession.old_peer_id, &succession.new_peer_id);
+            println!(
+                "🔑 Succession de clé: {} -> {} (registre migré: {})",
+                succession.old_peer_id, succession.new_peer_id, moved
+            );
+        }
+        let reissued = self.reissue_plans(&succession.old_peer_id, &succession.new_peer_id);
+        if reissued > 0 {
+            println!("🗺️ {} plan(s) réémis pour {}", reissued, succession.new_peer_id);
+        }
+
+        if let Ok(value) = serde_json::to_vec(succession) {
+            let record = Record::new(self.namespace.succession_key(&succession.old_peer_id), value);
+            let _ = self.swarm.behaviour_mut().kad.store_mut().put(record);
+        }
+    }
+
+    fn on_succession_message(&mut self, message: &GossipsubMessage) {
+        let accepted = TopicNamespace::parse_hash(&message.topic)
+            .is_some_and(|parsed| self.namespace.accepts_topic(&parsed));
+        if !accepted {
+            return;
+        }
+        if let Some(succession) = decode_succession(message) {
+            self.apply_succession(&succession);
+        }
+    }
+
+    fn on_announce_message(&mut self, message: &GossipsubMessage) {
+        if let Some(msg) = decode_announce(&self.namespace, message) {
+            if msg.node_id == self.local_peer_id.to_string() {
+                return;
+            }
+            if !self.access.permits(&msg.node_id) {
+                println!("🚫 Annonce d'un pair refusé ignorée: {}", msg.node_id);
+                return;
+            }
+            let trust = if msg.leaving { None } else { self.validate_certificate(&msg) };
+            let admitted = msg.leaving || self.verify_admission(&msg);
+            if !msg.leaving && trust.is_none() && self.trust.require_certificate {
+                println!("🚫 Annonce de {} ignorée: certificat d'organisation requis", msg.node_id);
+                return;
+            }
+            if let Ok(mut reg) = self.registry.lock() {
+                if msg.leaving {
+                    println!("👋 Départ annoncé par: {}", msg.node_id);
+                    reg.remove_node(&msg.node_id);
+                } else if reg.successors.contains_key(&msg.node_id) {
+                    println!("🚫 Annonce d'une identité retirée ignorée: {}", msg.node_id);
+                } else {
+                    println!("📨 Annonce reçue de: {} (protocole v{})", msg.node_id, msg.protocol_version);
+                    let node_id = msg.node_id.clone();
+                    reg.update_from_announce(msg);
+                    reg.set_trust(&node_id, trust);
+                    reg.set_admission(&node_id, admitted);
+                }
+            }
+        }
+    }
+
+    /// Répond à une recherche de successeur dès qu'un certificat valide est trouvé
+    fn on_record_progress(
+        &mut self,
+        id: QueryId,
+        res: Result<GetRecordOk, libp2p::kad::GetRecordError>,
+        last: bool,
+    ) {
+        if !self.pending_successors.contains_key(&id) {
+            return;
+        }
+        let found = match res {
+            Ok(GetRecordOk::FoundRecord(peer_record)) => {
+                serde_json::from_slice::<KeySuccession>(&peer_record.record.value)
+                    .ok()
+                    .filter(|s| s.verify().is_ok())
+                    .filter(|s| peer_record.record.key == self.namespace.succession_key(&s.old_peer_id))
+            }
+            _ => None,
+        };
+        if let Some(succession) = found {
+            self.apply_succession(&succession);
+            if let Some(reply) = self.pending_successors.remove(&id) {
+                let _ = reply.send(Some(succession));
+            }
+        } else if last {
+            if let Some(reply) = self.pending_successors.remove(&id) {
+                let _ = reply.send(None);
+            }
+        }
+    }
+
+    /// Mémoire encore disponible pour des shards: le budget configuré moins les shards chargés,
+    /// sinon la mémoire disponible du système
+    fn free_memory_mb(&self) -> u32 {
+        let free = match self.shard_memory_mb {
+            Some(budget) => {
+                let used: usize = self.shards.loaded().iter().map(|s| s.memory_bytes).sum();
+                budget.saturating_sub((used / (1024 * 1024)) as u64)
+            }
+            None => available_memory_mb().unwrap_or(0),
+        };
+        free.min(u32::MAX as u64) as u32
+    }
+
+    /// Compose l'annonce de ce nœud (ou son annonce de départ si `leaving`)
+    fn build_announce(&self, leaving: bool) -> AnnounceMsg {
+        AnnounceMsg {
+            shards: std::iter::once(self.options.role.label().to_string())
+                .chain(self.shards.loaded().into_iter().map(|s| s.id))
+                .collect(),
+            ram_free_mb: self.free_memory_mb(),
+            load: self.shards.in_flight() as u32,
+            protocol_version: self.namespace.version(),
+            leaving,
+            certificate: if leaving { None } else { self.options.certificate.clone() },
+            admission: if leaving { None } else { self.options.admission },
+            addrs: if leaving {
+                Vec::new()
+            } else {
+                self.swarm.listeners().chain(self.swarm.external_addresses()).map(|a| a.to_string()).collect()
+            },
+            ..AnnounceMsg::new(self.local_peer_id.to_string())
+        }
+    }
+
+    /// Traite un événement du swarm pendant le fonctionnement normal du nœud
+    fn handle_swarm_event(&mut self, event: SwarmEvent<MeshEvent>) {
+        match event {
+            SwarmEvent::Behaviour(MeshEvent::Gossipsub(GossipsubEvent::Message { message, .. })) => {
+                match TopicNamespace::parse_hash(&message.topic).map(|t| t.channel) {
+                    Some(Channel::Succession) => self.on_succession_message(&message),
+                    Some(Channel::Trust) => self.on_trust_message(&message),
+                    Some(Channel::Plan) => self.on_plan_message(&message),
+                    Some(Channel::Communicator) => self.on_communicator_message(&message),
+                    Some(Channel::Reputation) => self.on_reputation_message(&message),
+                    _ => self.on_announce_message(&message),
+                }
+            },
+            SwarmEvent::Behaviour(MeshEvent::Rpc(event)) => self.on_rpc_event(event),
+            SwarmEvent::Behaviour(MeshEvent::Ping(ping::Event { peer, result: Ok(rtt), .. })) => {
+                if let Ok(mut reg) = self.registry.lock() {
+                    reg.record_latency(&peer.to_string(), rtt);
+                }
+            },
+            SwarmEvent::Behaviour(MeshEvent::Mdns(MdnsEvent::Discovered(peers))) => {
+                for (peer_id, addr) in peers {
+                    println!("🔍 Pair découvert via mDNS: {} à {}", peer_id, addr);
+                    self.swarm.behaviour_mut().kad.add_address(&peer_id, addr);
+                }
+            },
+            SwarmEvent::Behaviour(MeshEvent::Kad(KademliaEvent::RoutingUpdated { peer, .. })) => {
+                println!("📝 Table de routage mise à jour avec: {}", peer);
+            },
+            SwarmEvent::Behaviour(MeshEvent::Kad(KademliaEvent::OutboundQueryProgressed { id, result, step, .. })) => {
+                println!("📊 Progression requête DHT: {:?}", result);
+                match result {
+                    QueryResult::GetProviders(res) => self.on_providers_progress(id, res, step.last),
+                    QueryResult::GetRecord(res) => self.on_record_progress(id, res, step.last),
+                    _ => {}
+                }
+            },
+            SwarmEvent::NewListenAddr { address, .. } => {
+                println!("📡 En écoute sur: {}", address);
+            },
+            SwarmEvent::ConnectionEstablished { peer_id, .. } => {
+                self.metrics.record_established();
+                self.reconnect_attempts.remove(&peer_id);
+                println!("🔗 Connexion établie avec: {}", peer_id);
+                self.resume_awaiting(&peer_id);
+            },
+            SwarmEvent::ConnectionClosed { peer_id, num_established, .. } => {
+                self.metrics.record_closed();
+                println!("❌ Connexion fermée avec: {}", peer_id);
+                if num_established == 0 {
+                    self.schedule_reconnect(peer_id);
+                }
+            },
+            SwarmEvent::IncomingConnectionError { send_back_addr, error: ListenError::Denied { cause }, .. } => {
+                let reason = DenialReason::from_cause(&cause);
+                self.metrics.record_inbound_denied(reason);
+                println!("⛔ Connexion entrante refusée ({:?}) depuis {}: {}", reason, send_back_addr, cause);
+            },
+            SwarmEvent::OutgoingConnectionError { peer_id, error: DialError::Denied { cause }, .. } => {
+                let reason = DenialReason::from_cause(&cause);
+                self.metrics.record_outbound_denied(reason);
+                println!("⛔ Connexion sortante refusée ({:?}) vers {:?}: {}", reason, peer_id, cause);
+                if let Some(peer_id) = peer_id {
+                    self.fail_awaiting(&peer_id, &cause.to_string());
+                }
+            },
+            SwarmEvent::OutgoingConnectionError { peer_id: Some(peer_id), error, .. } => {
+                println!("❌ Échec de connexion à {}: {}", peer_id, error);
+                self.fail_awaiting(&peer_id, &error.to_string());
+                if !self.swarm.is_connected(&peer_id) {
+                    self.schedule_reconnect(peer_id);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// Accumule les fournisseurs trouvés et répond une fois la requête terminée
+    fn on_providers_progress(
+        &mut self,
+        id: QueryId,
+        res: Result<GetProvidersOk, libp2p::kad::GetProvidersError>,
+        last: bool,
+    ) {
+        let Some(pending) = self.pending_providers.get_mut(&id) else {
+            return;
+        };
+        if let Ok(GetProvidersOk::FoundProviders { providers, .. }) = res {
+            pending.found.extend(providers);
+        }
+        if last {
+            if let Some(pending) = self.pending_providers.remove(&id) {
+                let _ = pending.reply.send(pending.found);
+            }
+        }
+    }
+
+    /// Séquence d'arrêt: annonce de départ, retrait des clés DHT, drainage, persistance
+    async fn shutdown(&mut self) {
+        let role = self.options.role;
+        println!("👋 Publication de l'annonce de départ...");
+        let goodbye = self.build_announce(true);
+        publish_announce(&mut self.swarm.behaviour_mut().gossipsub, &self.namespace, &goodbye);
+
+        for key in &self.provided_keys {
+            self.swarm.behaviour_mut().kad.stop_providing(key);
+            println!("🗑️ Fin de fourniture DHT pour la clé: {:?}", key);
+        }
+
+        // Laisser partir l'annonce et se terminer les requêtes DHT et RPC en cours: les tâches
+        // qui servent les requêtes reçues répondent encore par le canal de commandes
+        let started = Instant::now();
+        let deadline = started + Duration::from_secs(DRAIN_TIMEOUT);
+        // Réponses confiées au swarm, pas encore parties vers leur pair
+        let mut unsent = 0;
+        loop {
+            let in_flight = self.swarm.behaviour_mut().kad.iter_queries().count();
+            let rpc_in_flight = self.inbound_rpc.len() + self.pending_rpc.len() + unsent;
+            if in_flight == 0 && rpc_in_flight == 0 && started.elapsed() >= Duration::from_millis(DRAIN_MIN_FLUSH_MS) {
+                break;
+            }
+            tokio::select! {
+                _ = sleep_until(deadline) => {
+                    println!(
+                        "⏱️ Délai de drainage écoulé ({} requêtes DHT et {} requêtes RPC abandonnées)",
+                        in_flight, rpc_in_flight
+                    );
+                    break;
+                },
+                _ = sleep(Duration::from_millis(DRAIN_MIN_FLUSH_MS)) => {},
+                Some(cmd) = self.cmd_rx.recv() => {
+                    // Seules les réponses aux requêtes en cours sont encore traitées, avec la
+                    // connexion qui renvoie leur sortie sur un flux de tenseurs
+                    match cmd {
+                        NodeCommand::RespondRpc { id, .. } => {
+                            if self.inbound_rpc.get(&id).is_some_and(|channel| channel.is_open()) {
+                                unsent += 1;
+                            }
+                            self.handle_command(cmd);
+                        },
+                        NodeCommand::Connect { .. } => self.handle_command(cmd),
+                        _ => {}
+                    }
+                },
+                event = self.swarm.select_next_some() => match event {
+                    SwarmEvent::Behaviour(MeshEvent::Kad(KademliaEvent::OutboundQueryProgressed { result, .. })) => {
+                        println!("📊 Requête DHT terminée pendant l'arrêt: {:?}", result);
+                    },
+                    SwarmEvent::Behaviour(MeshEvent::Rpc(RpcEvent::Message { peer, message: RpcMessage::Request { .. } })) => {
+                        println!("🚫 Requête RPC de {} refusée: nœud en cours d'arrêt", peer);
+                    },
+                    SwarmEvent::Behaviour(MeshEvent::Rpc(event)) => {
+                        if matches!(event, RpcEvent::ResponseSent { .. } | RpcEvent::InboundFailure { .. }) {
+                            unsent = unsent.saturating_sub(1);
+                        }
+                        self.on_rpc_event(event);
+                    },
+                    _ => {}
+                },
+            }
+        }
+        for (_, (_, reply)) in self.pending_rpc.drain() {
+            let _ = reply.send(Err(anyhow!("Node {} is shutting down", self.local_peer_id)));
+        }
+        for (_, queued) in self.awaiting_connection.drain() {
+            for awaiting in queued {
+                awaiting.fail(anyhow!("Node {} is shutting down", self.local_peer_id));
+            }
+        }
+        self.inbound_rpc.clear();
+
+        // Persistance du registre pour le prochain démarrage
+        if let Some(registry_path) = &self.options.registry_path {
+            let saved = match self.registry.lock() {
+                Ok(reg) => reg.save_to(registry_path),
+                Err(_) => Err(anyhow!("Registry verrouillé")),
+            };
+            match saved {
+                Ok(_) => println!("💾 Registry sauvegardé dans {:?}", registry_path),
+                Err(e) => println!("⚠️ Impossible de sauvegarder le registry: {:?}", e),
+            }
+        }
+
+        if let Some(reputation_path) = &self.options.reputation_path {
+            match self.handle.reputation.save_to(reputation_path) {
+                Ok(_) => println!("💾 Réputation sauvegardée dans {:?}", reputation_path),
+                Err(e) => println!("⚠️ Impossible de sauvegarder la réputation: {:?}", e),
+            }
+        }
+
+        println!("✅ Nœud {} arrêté proprement", role.label());
+    }
+}
+
+type RpcReply = oneshot::Sender<Result<RpcOutcome>>;
+
+/// Opération suspendue à l'établissement d'une connexion sortante
+enum AwaitingConnection {
+    Rpc(RpcCall, RpcReply),
+    Connect(oneshot::Sender<Result<()>>),
+}
+
+impl AwaitingConnection {
+    fn fail(self, error: anyhow::Error) {
+        match self {
+            AwaitingConnection::Rpc(_, reply) => {
+                let _ = reply.send(Err(error));
+            },
+            AwaitingConnection::Connect(reply) => {
+                let _ = reply.send(Err(error));
+            },
+        }
+    }
+}
+
+/// Renvoie la sortie d'une requête reçue sur un flux de tenseurs vers son émetteur
+async fn reply_frame(handle: &NodeHandle, peer: PeerId, request_id: u64, output: &Tensor) -> Result<()> {
+    handle.open_tensor_stream(peer, request_id).await?.send_authorized(output).await
+}
+
+/// Domaine de confiance d'un pair, s'il a présenté un certificat encore valide
+fn trust_domain_of(registry: &Mutex<Registry>, peer: &PeerId) -> Option<String> {
+    registry.lock().ok()
+        .and_then(|reg| reg.nodes.get(&peer.to_string()).and_then(|entry| entry.trust.clone()))
+        .filter(|trust| trust.is_valid())
+        .map(|trust| trust.domain)
+}
+
+/// Mémoire disponible du système (Mo), lue dans /proc/meminfo
+fn available_memory_mb() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|l| l.starts_with("MemAvailable:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb / 1024)
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Lance les tâches de fond propres à chaque rôle. Elles s'arrêtent quand le canal se ferme.
+fn spawn_periodic_tasks(
+    role: NodeRole,
+    cmd_tx: mpsc::Sender<NodeCommand>,
+    registry: Arc<Mutex<Registry>>,
+    metrics: Arc<NodeMetrics>,
+) {
+    match role {
+        NodeRole::Bootstrap => {
+            // Tâche pour DHT bootstrap périodique
+            let cmd_tx_clone = cmd_tx.clone();
+            tokio::spawn(async move {
+                loop {
+                    sleep(Duration::from_secs(BOOTSTRAP_INTERVAL)).await;
+                    println!("🔍 Recherche DHT pour les fournisseurs...");
+                    if cmd_tx_clone.send(NodeCommand::GetProviders).await.is_err() {
+                        break;
+                    }
+                }
+            });
+
+            // Tâche pour annonce périodique
+            let cmd_tx_clone = cmd_tx.clone();
+            tokio::spawn(async move {
+                loop {
+                    sleep(Duration::from_secs(BOOTSTRAP_ANNOUNCE_INTERVAL)).await;
+                    if cmd_tx_clone.send(NodeCommand::AnnounceNode).await.is_err() {
+                        break;
+                    }
+                }
+            });
+
+            // Tâche pour afficher le registre, arrêtée avec la boucle du nœud (canal fermé)
+            tokio::spawn(async move {
+                loop {
+                    tokio::select! {
+                        _ = cmd_tx.closed() => break,
+                        _ = sleep(Duration::from_secs(60)) => {},
+                    }
+                    if let Ok(r) = registry.lock() {
+                        println!("\n📊 Registry Snapshot:");
+                        println!("{}", r.snapshot_json());
+                    }
+                    println!("📈 Métriques: {}", metrics.snapshot_json());
+                }
+            });
+        }
+        NodeRole::Light => {
+            drop((registry, metrics));
+
+            // Tâche pour recherche DHT périodique
+            tokio::spawn(async move {
+                // Attente initiale pour laisser le réseau s'établir
+                sleep(Duration::from_secs(2)).await;
+
+                loop {
+                    if cmd_tx.send(NodeCommand::GetProviders).await.is_err() {
+                        break;
+                    }
+
+                    // Annonce après 5 secondes
+                    sleep(Duration::from_secs(5)).await;
+
+                    if cmd_tx.send(NodeCommand::AnnounceNode).await.is_err() {
+                        break;
+                    }
+
+                    sleep(Duration::from_secs(BOOTSTRAP_INTERVAL)).await;
+                }
+            });
+        }
+    }
+}
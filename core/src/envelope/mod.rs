@@ -0,0 +1,217 @@
+// src/envelope/mod.rs
+//! Enveloppes scellées: chiffrement de bout en bout des messages entre nœuds.
+//!
+//! La clé X25519 de chaque nœud est dérivée de son identité ed25519: connaître le PeerId
+//! suffit pour chiffrer. Une clé de contenu aléatoire chiffre la charge utile
+//! (XChaCha20-Poly1305), puis est emballée pour chaque destinataire via une clé
+//! éphémère (ECDH + HKDF). L'expéditeur et les destinataires ne figurent que dans la
+//! partie chiffrée: relais et pairs gossip ne voient que la signature.
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use hkdf::Hkdf;
+use libp2p::identity::{ed25519, PeerId, PublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+
+const ENVELOPE_DOMAIN: &str = "cortex-envelope/v1";
+const ENVELOPE_VERSION: u32 = 1;
+/// Borne le coût du déchiffrement par essai à la réception
+pub const MAX_RECIPIENTS: usize = 64;
+
+/// Message chiffré pour un ensemble de destinataires
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedEnvelope {
+    pub version: u32,
+    /// Clé publique X25519 éphémère (base64)
+    pub ephemeral_key: String,
+    /// Clé de contenu emballée pour chaque destinataire, sans identifiant en clair
+    pub recipients: Vec<WrappedKey>,
+    pub nonce: String,
+    pub ciphertext: String,
+    /// Signature ed25519 de l'expéditeur sur l'enveloppe
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WrappedKey {
+    pub nonce: String,
+    pub key: String,
+}
+
+/// Enveloppe ouverte par l'un de ses destinataires
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenedEnvelope {
+    pub sender: PeerId,
+    pub payload: Vec<u8>,
+}
+
+/// Contenu chiffré: l'expéditeur n'est visible que des destinataires
+#[derive(Serialize, Deserialize)]
+struct Inner {
+    sender: String,
+    payload: String,
+}
+
+impl SealedEnvelope {
+    /// Chiffre `payload` pour `recipients` (PeerId ed25519) et signe avec la clé de l'expéditeur
+    pub fn seal(sender: &ed25519::Keypair, recipients: &[PeerId], payload: &[u8]) -> Result<Self> {
+        if recipients.is_empty() {
+            bail!("Sealed envelope needs at least one recipient");
+        }
+        if recipients.len() > MAX_RECIPIENTS {
+            bail!("Too many recipients ({} > {})", recipients.len(), MAX_RECIPIENTS);
+        }
+
+        let content_key: [u8; 32] = rand::random();
+        let nonce: [u8; 24] = rand::random();
+        let inner = Inner {
+            sender: PeerId::from(PublicKey::from(sender.public())).to_string(),
+            payload: STANDARD.encode(payload),
+        };
+        let ciphertext = cipher(&content_key)
+            .encrypt(XNonce::from_slice(&nonce), serde_json::to_vec(&inner)?.as_slice())
+            .map_err(|_| anyhow!("Payload encryption failed"))?;
+
+        let ephemeral_secret: [u8; 32] = rand::random();
+        let ephemeral_public = MontgomeryPoint::mul_base_clamped(ephemeral_secret);
+        let mut wrapped = Vec::with_capacity(recipients.len());
+        for recipient in recipients {
+            let recipient_key = x25519_public_of(recipient)?;
+            let shared = recipient_key.mul_clamped(ephemeral_secret);
+            let wrap_nonce: [u8; 24] = rand::random();
+            let key = cipher(&wrapping_key(&shared, &ephemeral_public, &recipient_key)?)
+                .encrypt(XNonce::from_slice(&wrap_nonce), content_key.as_slice())
+                .map_err(|_| anyhow!("Key wrapping failed"))?;
+            wrapped.push(WrappedKey {
+                nonce: STANDARD.encode(wrap_nonce),
+                key: STANDARD.encode(key),
+            });
+        }
+        // L'ordre des destinataires ne doit rien révéler
+        wrapped.sort_by(|a, b| a.key.cmp(&b.key));
+
+        let mut envelope = SealedEnvelope {
+            version: ENVELOPE_VERSION,
+            ephemeral_key: STANDARD.encode(ephemeral_public.as_bytes()),
+            recipients: wrapped,
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+            signature: String::new(),
+        };
+        envelope.signature = STANDARD.encode(sender.sign(&envelope.signing_bytes()));
+        Ok(envelope)
+    }
+
+    /// Ouvre l'enveloppe avec la clé du nœud local.
+    /// `None` si elle ne lui est pas adressée; erreur si elle est falsifiée.
+    pub fn open(&self, recipient: &ed25519::Keypair) -> Result<Option<OpenedEnvelope>> {
+        if self.version != ENVELOPE_VERSION {
+            bail!("Unsupported envelope version {}", self.version);
+        }
+        if self.recipients.len() > MAX_RECIPIENTS {
+            bail!("Too many recipients ({})", self.recipients.len());
+        }
+        let ephemeral_public = MontgomeryPoint(decode_array(&self.ephemeral_key)?);
+        let secret = x25519_secret_of(recipient);
+        let own_key = MontgomeryPoint::mul_base_clamped(secret);
+        let shared = ephemeral_public.mul_clamped(secret);
+        let wrap_cipher = cipher(&wrapping_key(&shared, &ephemeral_public, &own_key)?);
+
+        let content_key = self.recipients.iter().find_map(|wrapped| {
+            let nonce: [u8; 24] = decode_array(&wrapped.nonce).ok()?;
+            let key = STANDARD.decode(&wrapped.key).ok()?;
+            wrap_cipher.decrypt(XNonce::from_slice(&nonce), key.as_slice()).ok()
+        });
+        let Some(content_key) = content_key else {
+            return Ok(None);
+        };
+        let content_key: [u8; 32] = content_key.try_into().map_err(|_| anyhow!("Invalid content key"))?;
+
+        let nonce: [u8; 24] = decode_array(&self.nonce)?;
+        let plaintext = cipher(&content_key)
+            .decrypt(XNonce::from_slice(&nonce), STANDARD.decode(&self.ciphertext)?.as_slice())
+            .map_err(|_| anyhow!("Envelope decryption failed"))?;
+        let inner: Inner = serde_json::from_slice(&plaintext).context("Invalid envelope content")?;
+
+        let sender: PeerId = inner.sender.parse().map_err(|e| anyhow!("Invalid sender PeerId: {:?}", e))?;
+        let sender_key = ed25519_public_of(&sender)?;
+        if !sender_key.verify(&self.signing_bytes(), &STANDARD.decode(&self.signature)?) {
+            bail!("Invalid envelope signature from {}", sender);
+        }
+        Ok(Some(OpenedEnvelope {
+            sender,
+            payload: STANDARD.decode(&inner.payload)?,
+        }))
+    }
+
+    fn signing_bytes(&self) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(ENVELOPE_DOMAIN.as_bytes());
+        hasher.update(self.version.to_le_bytes());
+        for field in [&self.ephemeral_key, &self.nonce, &self.ciphertext] {
+            hasher.update(field.as_bytes());
+            hasher.update([0]);
+        }
+        for wrapped in &self.recipients {
+            hasher.update(wrapped.nonce.as_bytes());
+            hasher.update([0]);
+            hasher.update(wrapped.key.as_bytes());
+            hasher.update([0]);
+        }
+        hasher.finalize().to_vec()
+    }
+}
+
+/// Clé ed25519 portée par un PeerId (identité inline, comme tous les nœuds Cortex)
+pub fn ed25519_public_of(peer_id: &PeerId) -> Result<ed25519::PublicKey> {
+    let multihash = peer_id.as_ref();
+    if multihash.code() != 0 {
+        bail!("PeerId {} does not embed its public key", peer_id);
+    }
+    PublicKey::try_decode_protobuf(multihash.digest())?
+        .try_into_ed25519()
+        .map_err(|_| anyhow!("PeerId {} is not an ed25519 identity", peer_id))
+}
+
+/// Clé publique X25519 d'un nœud, dérivée de sa clé ed25519 (forme de Montgomery)
+pub fn x25519_public_of(peer_id: &PeerId) -> Result<MontgomeryPoint> {
+    let point = CompressedEdwardsY(ed25519_public_of(peer_id)?.to_bytes())
+        .decompress()
+        .ok_or_else(|| anyhow!("Invalid ed25519 point for {}", peer_id))?;
+    Ok(point.to_montgomery())
+}
+
+/// Scalaire X25519 correspondant: même dérivation que la signature ed25519
+fn x25519_secret_of(keypair: &ed25519::Keypair) -> [u8; 32] {
+    let hash = Sha512::digest(keypair.secret().as_ref());
+    let mut secret = [0u8; 32];
+    secret.copy_from_slice(&hash[..32]);
+    secret
+}
+
+fn wrapping_key(shared: &MontgomeryPoint, ephemeral: &MontgomeryPoint, recipient: &MontgomeryPoint) -> Result<[u8; 32]> {
+    if shared.as_bytes().iter().all(|b| *b == 0) {
+        bail!("Degenerate key exchange");
+    }
+    let mut info = ENVELOPE_DOMAIN.as_bytes().to_vec();
+    info.extend_from_slice(recipient.as_bytes());
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(ephemeral.as_bytes()), shared.as_bytes())
+        .expand(&info, &mut key)
+        .map_err(|_| anyhow!("Key derivation failed"))?;
+    Ok(key)
+}
+
+fn cipher(key: &[u8; 32]) -> XChaCha20Poly1305 {
+    XChaCha20Poly1305::new(Key::from_slice(key))
+}
+
+fn decode_array<const N: usize>(encoded: &str) -> Result<[u8; N]> {
+    STANDARD.decode(encoded)?
+        .try_into()
+        .map_err(|_| anyhow!("Invalid field length (expected {} bytes)", N))
+}
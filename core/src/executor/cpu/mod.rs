@@ -0,0 +1,423 @@
+// src/executor/cpu/mod.rs
+//! Backend CPU de référence en Rust pur: transformer décodeur (embedding, attention
+//! avec RoPE et têtes KV groupées, MLP SwiGLU, RMSNorm) chargé via `weights` (safetensors ou GGUF,
+//! poids quantifiés déquantifiés en f32).
+//! Monothread et sans réordonnancement des sommes: les résultats sont reproductibles au bit près,
+//! ce qui en fait la référence pour valider les autres backends et le découpage en shards.
+mod ops;
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{ShardExecutor, ShardSpec, Tensor};
+use crate::weights::ShardWeights;
+use ops::{Linear, Rope};
+
+pub const BACKEND_NAME: &str = "cpu";
+/// Hyperparamètres du modèle, à côté des poids (format `config.json` de Hugging Face)
+pub const CONFIG_FILE: &str = "config.json";
+/// Clé des métadonnées du fichier de poids pouvant porter la configuration en JSON
+const METADATA_CONFIG_KEY: &str = "config";
+
+pub fn create() -> Box<dyn ShardExecutor> {
+    Box::new(CpuTransformer::default())
+}
+
+/// Hyperparamètres d'un transformer décodeur de type Llama
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransformerConfig {
+    pub hidden_size: usize,
+    pub intermediate_size: usize,
+    pub num_attention_heads: usize,
+    /// Têtes clé/valeur (attention groupée); par défaut autant que de têtes de requête
+    #[serde(default)]
+    pub num_key_value_heads: Option<usize>,
+    pub vocab_size: usize,
+    #[serde(default = "default_rms_norm_eps")]
+    pub rms_norm_eps: f32,
+    #[serde(default = "default_rope_theta")]
+    pub rope_theta: f32,
+    /// Tête de sortie partagée avec l'embedding quand `lm_head.weight` est absent
+    #[serde(default)]
+    pub tie_word_embeddings: bool,
+}
+
+fn default_rms_norm_eps() -> f32 {
+    1e-6
+}
+
+fn default_rope_theta() -> f32 {
+    10000.0
+}
+
+impl TransformerConfig {
+    pub fn kv_heads(&self) -> usize {
+        self.num_key_value_heads.unwrap_or(self.num_attention_heads)
+    }
+
+    pub fn head_dim(&self) -> usize {
+        self.hidden_size / self.num_attention_heads
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.hidden_size == 0 || self.num_attention_heads == 0 || self.vocab_size == 0 {
+            bail!("hidden_size, num_attention_heads and vocab_size must be positive");
+        }
+        if self.hidden_size % self.num_attention_heads != 0 || self.head_dim() % 2 != 0 {
+            bail!("hidden_size must split into heads of even dimension");
+        }
+        if self.kv_heads() == 0 || self.num_attention_heads % self.kv_heads() != 0 {
+            bail!("num_attention_heads must be a multiple of num_key_value_heads");
+        }
+        Ok(())
+    }
+}
+
+/// Cache clé/valeur d'une couche: une ligne de `kv_dim` valeurs par position
+#[derive(Debug, Clone, Default)]
+struct KvCache {
+    keys: Vec<f32>,
+    values: Vec<f32>,
+}
+
+#[derive(Debug)]
+struct Layer {
+    index: usize,
+    input_norm: Vec<f32>,
+    q_proj: Linear,
+    k_proj: Linear,
+    v_proj: Linear,
+    o_proj: Linear,
+    post_norm: Vec<f32>,
+    gate_proj: Linear,
+    up_proj: Linear,
+    down_proj: Linear,
+}
+
+impl Layer {
+    fn weights_len(&self) -> usize {
+        self.input_norm.len()
+            + self.post_norm.len()
+            + [&self.q_proj, &self.k_proj, &self.v_proj, &self.o_proj, &self.gate_proj, &self.up_proj, &self.down_proj]
+                .iter()
+                .map(|l| l.len())
+                .sum::<usize>()
+    }
+
+    /// Applique la couche à l'état caché d'une position, en ajoutant sa clé/valeur à `cache`
+    fn forward(&self, x: &mut [f32], pos: usize, config: &TransformerConfig, rope: &Rope, cache: &mut KvCache) {
+        let head_dim = config.head_dim();
+        let group = config.num_attention_heads / config.kv_heads();
+        let kv_dim = config.kv_heads() * head_dim;
+
+        let h = ops::rms_norm(x, &self.input_norm, config.rms_norm_eps);
+        let mut q = self.q_proj.forward(&h);
+        let mut k = self.k_proj.forward(&h);
+        let v = self.v_proj.forward(&h);
+        rope.apply(&mut q, pos);
+        rope.apply(&mut k, pos);
+        cache.keys.extend_from_slice(&k);
+        cache.values.extend_from_slice(&v);
+
+        let positions = cache.keys.len() / kv_dim;
+        let scale = 1.0 / (head_dim as f32).sqrt();
+        let mut attention = vec![0.0f32; config.hidden_size];
+        let mut scores = vec![0.0f32; positions];
+        for head in 0..config.num_attention_heads {
+            let query = &q[head * head_dim..(head + 1) * head_dim];
+            let kv_offset = (head / group) * head_dim;
+            for (j, score) in scores.iter_mut().enumerate() {
+                let key = &cache.keys[j * kv_dim + kv_offset..j * kv_dim + kv_offset + head_dim];
+                *score = ops::dot(query, key) * scale;
+            }
+            ops::softmax(&mut scores);
+            let out = &mut attention[head * head_dim..(head + 1) * head_dim];
+            for (j, weight) in scores.iter().enumerate() {
+                let value = &cache.values[j * kv_dim + kv_offset..j * kv_dim + kv_offset + head_dim];
+                for (o, v) in out.iter_mut().zip(value) {
+                    *o += weight * v;
+                }
+            }
+        }
+        for (x, o) in x.iter_mut().zip(self.o_proj.forward(&attention)) {
+            *x += o;
+        }
+
+        let h = ops::rms_norm(x, &self.post_norm, config.rms_norm_eps);
+        let gate = self.gate_proj.forward(&h);
+        let up = self.up_proj.forward(&h);
+        let hidden: Vec<f32> = gate.iter().zip(&up).map(|(g, u)| ops::silu(*g) * u).collect();
+        for (x, d) in x.iter_mut().zip(self.down_proj.forward(&hidden)) {
+            *x += d;
+        }
+    }
+}
+
+/// Partie du modèle portée par ce shard
+#[derive(Debug)]
+struct Model {
+    config: TransformerConfig,
+    rope: Rope,
+    /// Présent sur le premier shard: l'entrée est alors une suite d'identifiants de tokens
+    embedding: Option<Linear>,
+    layers: Vec<Layer>,
+    /// Présents sur le dernier shard: la sortie est alors des logits
+    final_norm: Option<Vec<f32>>,
+    lm_head: Option<Linear>,
+}
+
+impl Model {
+    /// Prolonge la séquence dont `caches` porte les positions `0..position` (un cache par couche)
+    fn forward(&self, input: &Tensor, caches: &mut [KvCache], position: usize) -> Result<Tensor> {
+        let hidden_size = self.config.hidden_size;
+        // Une entrée reçue d'un pair peut annoncer une forme que ses données ne remplissent pas:
+        // la position du cache KV doit avancer d'exactement le nombre de lignes traitées
+        input.validate()?;
+
+        let (seq, mut hidden) = match &self.embedding {
+            Some(embedding) => {
+                if input.shape.len() != 1 {
+                    bail!("Expected token ids [seq], got shape {:?}", input.shape);
+                }
+                let mut hidden = Vec::with_capacity(input.len() * hidden_size);
+                for &id in &input.data {
+                    if id < 0.0 || id.fract() != 0.0 || id as usize >= embedding.rows {
+                        bail!("Invalid token id {}", id);
+                    }
+                    let row = id as usize;
+                    hidden.extend_from_slice(&embedding.weight[row * hidden_size..(row + 1) * hidden_size]);
+                }
+                (input.len(), hidden)
+            }
+            None => {
+                if input.shape.len() != 2 || input.shape[1] != hidden_size {
+                    bail!("Expected hidden states [seq, {}], got shape {:?}", hidden_size, input.shape);
+                }
+                (input.shape[0], input.data.clone())
+            }
+        };
+
+        // Couche par couche: chaque position ne voit que le cache des positions précédentes
+        for (layer, cache) in self.layers.iter().zip(caches.iter_mut()) {
+            for (t, x) in hidden.chunks_exact_mut(hidden_size).enumerate() {
+                layer.forward(x, position + t, &self.config, &self.rope, cache);
+            }
+        }
+
+        match (&self.final_norm, &self.lm_head) {
+            (Some(norm), Some(head)) => {
+                let mut logits = Vec::with_capacity(seq * head.rows);
+                for x in hidden.chunks_exact(hidden_size) {
+                    logits.extend(head.forward(&ops::rms_norm(x, norm, self.config.rms_norm_eps)));
+                }
+                Tensor::new(vec![seq, head.rows], logits)
+            }
+            _ => Tensor::new(vec![seq, hidden_size], hidden),
+        }
+    }
+}
+
+/// Exécuteur CPU d'un shard de transformer (plage contiguë de couches)
+///
+/// Entrée: identifiants de tokens `[seq]` si le shard porte l'embedding, sinon états cachés
+/// `[seq, hidden]`. Sortie: logits `[seq, vocab]` si le shard porte la tête, sinon états cachés.
+/// Le cache KV est conservé entre deux appels: chaque appel prolonge la séquence (`reset` la vide).
+/// `forward_stateless` part d'un cache vierge, jeté après l'appel.
+#[derive(Debug, Default)]
+pub struct CpuTransformer {
+    model: Option<Model>,
+    /// Cache KV de la séquence en cours, un par couche
+    caches: Vec<KvCache>,
+    position: usize,
+}
+
+impl CpuTransformer {
+    pub fn config(&self) -> Option<&TransformerConfig> {
+        self.model.as_ref().map(|m| &m.config)
+    }
+
+    /// Indices des couches portées par ce shard
+    pub fn layers(&self) -> Vec<usize> {
+        self.model.iter().flat_map(|m| m.layers.iter().map(|l| l.index)).collect()
+    }
+
+    /// Nombre de positions déjà dans le cache KV
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Décodage glouton, pour un shard portant le modèle entier (mode local sans mesh)
+    pub fn generate(&mut self, prompt: &[u32], max_new_tokens: usize) -> Result<Vec<u32>> {
+        if prompt.is_empty() {
+            bail!("Empty prompt");
+        }
+        let mut tokens: Vec<f32> = prompt.iter().map(|&t| t as f32).collect();
+        let mut generated = Vec::with_capacity(max_new_tokens);
+        for _ in 0..max_new_tokens {
+            let logits = self.forward(&Tensor::new(vec![tokens.len()], tokens)?)?;
+            let vocab = *logits.shape.last().unwrap_or(&0);
+            if logits.shape.len() != 2 || vocab == 0 {
+                bail!("Shard does not produce logits (needs embedding and head)");
+            }
+            let next = ops::argmax(&logits.data[logits.data.len() - vocab..]) as u32;
+            generated.push(next);
+            tokens = vec![next as f32];
+        }
+        Ok(generated)
+    }
+}
+
+impl ShardExecutor for CpuTransformer {
+    fn load(&mut self, spec: &ShardSpec) -> Result<()> {
+        let path = spec.path.as_deref()
+            .ok_or_else(|| anyhow!("Shard {} has no weights path", spec.id))?;
+        let model = load_model(path).with_context(|| format!("Failed to load shard {}", spec.id))?;
+        self.caches = vec![KvCache::default(); model.layers.len()];
+        self.model = Some(model);
+        self.position = 0;
+        Ok(())
+    }
+
+    fn forward(&mut self, input: &Tensor) -> Result<Tensor> {
+        let model = self.model.as_ref().ok_or_else(|| anyhow!("Shard not loaded"))?;
+        let output = model.forward(input, &mut self.caches, self.position)?;
+        self.position += output.shape[0];
+        Ok(output)
+    }
+
+    fn forward_stateless(&self, input: &Tensor) -> Result<Tensor> {
+        let model = self.model.as_ref().ok_or_else(|| anyhow!("Shard not loaded"))?;
+        let mut scratch = vec![KvCache::default(); model.layers.len()];
+        model.forward(input, &mut scratch, 0)
+    }
+
+    fn memory_usage(&self) -> usize {
+        let Some(model) = &self.model else {
+            return 0;
+        };
+        let weights = model.embedding.as_ref().map_or(0, Linear::len)
+            + model.layers.iter().map(Layer::weights_len).sum::<usize>()
+            + model.final_norm.as_ref().map_or(0, Vec::len)
+            + model.lm_head.as_ref().map_or(0, Linear::len);
+        let cache: usize = self.caches.iter().map(|c| c.keys.len() + c.values.len()).sum();
+        (weights + cache) * std::mem::size_of::<f32>()
+    }
+
+    fn unload(&mut self) {
+        self.model = None;
+        self.caches.clear();
+        self.position = 0;
+    }
+
+    fn reset(&mut self) {
+        self.caches.iter_mut().for_each(|cache| *cache = KvCache::default());
+        self.position = 0;
+    }
+}
+
+/// Charge un shard: manifeste, répertoire ou fichier de poids (voir `ShardWeights::open`)
+fn load_model(path: &Path) -> Result<Model> {
+    let weights = ShardWeights::open(path)?;
+    let config = read_config(&weights)?;
+    config.validate()?;
+    build_model(&weights, config)
+}
+
+/// Configuration: métadonnées du fichier de poids, sinon `config.json` à côté
+fn read_config(weights: &ShardWeights) -> Result<TransformerConfig> {
+    if let Some(json) = weights.metadata(METADATA_CONFIG_KEY) {
+        return serde_json::from_str(json).context("Invalid model config in weight metadata");
+    }
+    let path = weights.dir().join(CONFIG_FILE);
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("No model config in metadata and failed to read {:?}", path))?;
+    serde_json::from_str(&content).with_context(|| format!("Invalid model config {:?}", path))
+}
+
+fn build_model(tensors: &ShardWeights, config: TransformerConfig) -> Result<Model> {
+    let hidden = config.hidden_size;
+    let kv_dim = config.kv_heads() * config.head_dim();
+    let inter = config.intermediate_size;
+    let has = |name: &str| tensors.contains(name);
+
+    let mut indices: Vec<usize> = tensors.names()
+        .filter_map(|name| name.strip_prefix("model.layers.")?.split('.').next()?.parse().ok())
+        .collect();
+    indices.sort_unstable();
+    indices.dedup();
+    if indices.windows(2).any(|w| w[1] != w[0] + 1) {
+        bail!("Shard layers must be contiguous, got {:?}", indices);
+    }
+
+    let first_stage = indices.first().map_or(true, |&i| i == 0);
+    let embedding = if has("model.embed_tokens.weight") && first_stage {
+        Some(linear(tensors, "model.embed_tokens.weight", config.vocab_size, hidden)?)
+    } else {
+        None
+    };
+
+    let mut layers = Vec::with_capacity(indices.len());
+    for index in indices {
+        let p = format!("model.layers.{}", index);
+        layers.push(Layer {
+            index,
+            input_norm: vector(tensors, &format!("{}.input_layernorm.weight", p), hidden)?,
+            q_proj: linear(tensors, &format!("{}.self_attn.q_proj.weight", p), hidden, hidden)?,
+            k_proj: linear(tensors, &format!("{}.self_attn.k_proj.weight", p), kv_dim, hidden)?,
+            v_proj: linear(tensors, &format!("{}.self_attn.v_proj.weight", p), kv_dim, hidden)?,
+            o_proj: linear(tensors, &format!("{}.self_attn.o_proj.weight", p), hidden, hidden)?,
+            post_norm: vector(tensors, &format!("{}.post_attention_layernorm.weight", p), hidden)?,
+            gate_proj: linear(tensors, &format!("{}.mlp.gate_proj.weight", p), inter, hidden)?,
+            up_proj: linear(tensors, &format!("{}.mlp.up_proj.weight", p), inter, hidden)?,
+            down_proj: linear(tensors, &format!("{}.mlp.down_proj.weight", p), hidden, inter)?,
+        });
+    }
+
+    let (final_norm, lm_head) = if has("model.norm.weight") {
+        let head_name = if has("lm_head.weight") {
+            "lm_head.weight"
+        } else if config.tie_word_embeddings {
+            "model.embed_tokens.weight"
+        } else {
+            bail!("Shard has the final norm but no lm_head.weight");
+        };
+        (
+            Some(vector(tensors, "model.norm.weight", hidden)?),
+            Some(linear(tensors, head_name, config.vocab_size, hidden)?),
+        )
+    } else {
+        (None, None)
+    };
+
+    if embedding.is_none() && layers.is_empty() && lm_head.is_none() {
+        bail!("No transformer weights found");
+    }
+    Ok(Model {
+        rope: Rope::new(config.head_dim(), config.rope_theta),
+        config,
+        embedding,
+        layers,
+        final_norm,
+        lm_head,
+    })
+}
+
+fn linear(tensors: &ShardWeights, name: &str, rows: usize, cols: usize) -> Result<Linear> {
+    Ok(Linear { rows, cols, weight: read_f32(tensors, name, &[rows, cols])? })
+}
+
+fn vector(tensors: &ShardWeights, name: &str, len: usize) -> Result<Vec<f32>> {
+    read_f32(tensors, name, &[len])
+}
+
+/// Lit un tenseur en f32 après vérification de sa forme
+fn read_f32(tensors: &ShardWeights, name: &str, shape: &[usize]) -> Result<Vec<f32>> {
+    let tensor = tensors.tensor(name)?;
+    if tensor.shape != shape {
+        bail!("Tensor {} has shape {:?}, expected {:?}", name, tensor.shape, shape);
+    }
+    tensor.to_f32()
+}
@@ -0,0 +1,103 @@
+// src/executor/cpu/ops.rs
+//! Opérations de base en f32, séquentielles et dans un ordre de sommation fixe:
+//! le même calcul donne les mêmes bits, qu'il soit fait sur un nœud ou réparti.
+
+/// Matrice de poids `[rows, cols]` en ordre ligne (convention `nn.Linear`: `[out, in]`)
+#[derive(Debug, Clone)]
+pub struct Linear {
+    pub rows: usize,
+    pub cols: usize,
+    pub weight: Vec<f32>,
+}
+
+impl Linear {
+    /// `y = W x`
+    pub fn forward(&self, x: &[f32]) -> Vec<f32> {
+        debug_assert_eq!(x.len(), self.cols);
+        self.weight
+            .chunks_exact(self.cols)
+            .map(|row| dot(row, x))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.weight.len()
+    }
+}
+
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    let mut sum = 0.0f32;
+    for (x, y) in a.iter().zip(b) {
+        sum += x * y;
+    }
+    sum
+}
+
+/// RMSNorm: `x / sqrt(mean(x²) + eps) * weight`
+pub fn rms_norm(x: &[f32], weight: &[f32], eps: f32) -> Vec<f32> {
+    let mut sum = 0.0f32;
+    for v in x {
+        sum += v * v;
+    }
+    let scale = 1.0 / (sum / x.len() as f32 + eps).sqrt();
+    x.iter().zip(weight).map(|(v, w)| v * scale * w).collect()
+}
+
+pub fn silu(x: f32) -> f32 {
+    x / (1.0 + (-x).exp())
+}
+
+/// Softmax en place, stabilisé par le maximum
+pub fn softmax(x: &mut [f32]) {
+    let max = x.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let mut sum = 0.0f32;
+    for v in x.iter_mut() {
+        *v = (*v - max).exp();
+        sum += *v;
+    }
+    for v in x.iter_mut() {
+        *v /= sum;
+    }
+}
+
+/// Embeddings positionnels rotatifs (convention « rotate half » des modèles Llama)
+#[derive(Debug, Clone)]
+pub struct Rope {
+    head_dim: usize,
+    inv_freq: Vec<f64>,
+}
+
+impl Rope {
+    pub fn new(head_dim: usize, theta: f32) -> Self {
+        let half = head_dim / 2;
+        let inv_freq = (0..half)
+            .map(|i| (theta as f64).powf(-((2 * i) as f64) / head_dim as f64))
+            .collect();
+        Rope { head_dim, inv_freq }
+    }
+
+    /// Applique la rotation de la position `pos` à chaque tête de `x`
+    pub fn apply(&self, x: &mut [f32], pos: usize) {
+        let half = self.head_dim / 2;
+        for head in x.chunks_exact_mut(self.head_dim) {
+            for (i, freq) in self.inv_freq.iter().enumerate() {
+                let angle = pos as f64 * freq;
+                let (sin, cos) = (angle.sin() as f32, angle.cos() as f32);
+                let (a, b) = (head[i], head[i + half]);
+                head[i] = a * cos - b * sin;
+                head[i + half] = b * cos + a * sin;
+            }
+        }
+    }
+}
+
+/// Indice du maximum (premier en cas d'égalité)
+pub fn argmax(x: &[f32]) -> usize {
+    let mut best = 0;
+    for (i, v) in x.iter().enumerate() {
+        if *v > x[best] {
+            best = i;
+        }
+    }
+    best
+}
@@ -0,0 +1,43 @@
+// src/executor/identity.rs
+//! Backend de référence sans modèle: renvoie l'entrée telle quelle.
+//! Sert aux tests de bout en bout du mesh (pipeline, ordonnancement) sans poids.
+use anyhow::Result;
+
+use super::{ShardExecutor, ShardSpec, Tensor};
+
+pub const BACKEND_NAME: &str = "identity";
+
+#[derive(Debug, Default)]
+pub struct IdentityExecutor {
+    loaded: bool,
+}
+
+pub fn create() -> Box<dyn ShardExecutor> {
+    Box::new(IdentityExecutor::default())
+}
+
+impl ShardExecutor for IdentityExecutor {
+    fn load(&mut self, _spec: &ShardSpec) -> Result<()> {
+        self.loaded = true;
+        Ok(())
+    }
+
+    fn forward(&mut self, input: &Tensor) -> Result<Tensor> {
+        self.forward_stateless(input)
+    }
+
+    fn forward_stateless(&self, input: &Tensor) -> Result<Tensor> {
+        if !self.loaded {
+            anyhow::bail!("Shard not loaded");
+        }
+        Ok(input.clone())
+    }
+
+    fn memory_usage(&self) -> usize {
+        0
+    }
+
+    fn unload(&mut self) {
+        self.loaded = false;
+    }
+}
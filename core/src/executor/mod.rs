@@ -0,0 +1,282 @@
+// src/executor/mod.rs
+//! Exécution des shards de modèle: un trait commun aux backends, un registre de backends
+//! (sélectionnés par feature cargo) et l'hôte des shards chargés par le nœud.
+#[cfg(feature = "backend-cpu")]
+pub mod cpu;
+#[cfg(feature = "backend-identity")]
+pub mod identity;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::registry::ShardInfo;
+
+/// Tenseur d'activations dense (f32, ordre ligne)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tensor {
+    pub shape: Vec<usize>,
+    pub data: Vec<f32>,
+}
+
+impl Tensor {
+    pub fn new(shape: Vec<usize>, data: Vec<f32>) -> Result<Self> {
+        let tensor = Tensor { shape, data };
+        tensor.validate()?;
+        Ok(tensor)
+    }
+
+    /// Vérifie que les données remplissent exactement la forme (tenseur désérialisé d'un pair)
+    pub fn validate(&self) -> Result<()> {
+        let expected = self.shape.iter()
+            .try_fold(1usize, |acc, &dim| acc.checked_mul(dim))
+            .ok_or_else(|| anyhow!("Tensor shape {:?} overflows", self.shape))?;
+        if expected != self.data.len() {
+            bail!("Tensor shape {:?} needs {} values, got {}", self.shape, expected, self.data.len());
+        }
+        Ok(())
+    }
+
+    pub fn zeros(shape: Vec<usize>) -> Self {
+        let len = shape.iter().product();
+        Tensor { shape, data: vec![0.0; len] }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+/// Tenseur vide `[0]`, valide
+impl Default for Tensor {
+    fn default() -> Self {
+        Tensor::zeros(vec![0])
+    }
+}
+
+/// Shard à charger, depuis la section `shards:` de la configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardSpec {
+    /// Identifiant annoncé dans le mesh, ex: `llama-7b/layers-0-7`
+    pub id: String,
+    #[serde(default = "default_shard_version")]
+    pub version: String,
+    /// Backend d'exécution (voir `BackendRegistry::names`)
+    pub backend: String,
+    /// Poids du shard (fichier ou répertoire, selon le backend)
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+}
+
+fn default_shard_version() -> String {
+    "0".into()
+}
+
+/// Backend d'exécution d'un shard
+pub trait ShardExecutor: Send {
+    /// Charge les poids décrits par `spec`
+    fn load(&mut self, spec: &ShardSpec) -> Result<()>;
+    /// Applique le shard à un tenseur d'activations
+    fn forward(&mut self, input: &Tensor) -> Result<Tensor>;
+    /// Applique le shard depuis un état de séquence vide, sans lire ni modifier celui de `forward`
+    /// (répliques, experts): les poids chargés sont partagés, l'état est propre à l'appel
+    fn forward_stateless(&self, input: &Tensor) -> Result<Tensor>;
+    /// Mémoire occupée par le shard chargé, en octets
+    fn memory_usage(&self) -> usize;
+    /// Libère les poids
+    fn unload(&mut self);
+    /// Oublie l'état de la séquence en cours (cache KV), sans décharger les poids
+    fn reset(&mut self) {}
+}
+
+/// Fabrique d'un backend
+pub type BackendFactory = fn() -> Box<dyn ShardExecutor>;
+
+/// Backends disponibles, par nom
+#[derive(Debug, Clone, Default)]
+pub struct BackendRegistry {
+    backends: BTreeMap<String, BackendFactory>,
+}
+
+impl BackendRegistry {
+    /// Registre vide (tests, backends personnalisés)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Backends compilés dans ce binaire, selon les features cargo activées
+    pub fn builtin() -> Self {
+        #[allow(unused_mut)]
+        let mut registry = Self::new();
+        #[cfg(feature = "backend-identity")]
+        registry.register(identity::BACKEND_NAME, identity::create);
+        #[cfg(feature = "backend-cpu")]
+        registry.register(cpu::BACKEND_NAME, cpu::create);
+        registry
+    }
+
+    pub fn register(&mut self, name: &str, factory: BackendFactory) {
+        self.backends.insert(name.to_string(), factory);
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.backends.keys().map(String::as_str).collect()
+    }
+
+    pub fn create(&self, name: &str) -> Result<Box<dyn ShardExecutor>> {
+        let factory = self.backends.get(name)
+            .ok_or_else(|| anyhow!("Unknown backend {:?} (available: {:?})", name, self.names()))?;
+        Ok(factory())
+    }
+}
+
+/// Shard chargé, avec l'état mémoire rapporté par son backend
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoadedShard {
+    pub id: String,
+    pub version: String,
+    pub backend: String,
+    pub memory_bytes: usize,
+}
+
+struct HostedShard {
+    spec: ShardSpec,
+    executor: Arc<Mutex<Box<dyn ShardExecutor>>>,
+}
+
+/// Shards chargés par le nœud; chaque shard a son verrou, pour exécuter des shards différents en parallèle
+#[derive(Default)]
+pub struct ShardHost {
+    shards: Mutex<BTreeMap<String, HostedShard>>,
+    in_flight: AtomicUsize,
+}
+
+impl std::fmt::Debug for ShardHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShardHost").field("shards", &self.loaded()).finish()
+    }
+}
+
+impl ShardHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Charge un shard (remplace un shard de même identifiant)
+    pub fn load(&self, backends: &BackendRegistry, spec: ShardSpec) -> Result<()> {
+        let mut executor = backends.create(&spec.backend)?;
+        executor.load(&spec)?;
+        let hosted = HostedShard { spec, executor: Arc::new(Mutex::new(executor)) };
+        let mut shards = self.shards.lock().map_err(|_| anyhow!("Shard host lock poisoned"))?;
+        if let Some(previous) = shards.insert(hosted.spec.id.clone(), hosted) {
+            if let Ok(mut executor) = previous.executor.lock() {
+                executor.unload();
+            }
+        }
+        Ok(())
+    }
+
+    /// Décharge un shard; renvoie `false` s'il n'était pas chargé
+    pub fn unload(&self, id: &str) -> bool {
+        let removed = self.shards.lock().ok().and_then(|mut shards| shards.remove(id));
+        match removed {
+            Some(hosted) => {
+                if let Ok(mut executor) = hosted.executor.lock() {
+                    executor.unload();
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn unload_all(&self) {
+        let ids: Vec<String> = self.loaded().into_iter().map(|s| s.id).collect();
+        for id in ids {
+            self.unload(&id);
+        }
+    }
+
+    /// Exécute un shard chargé (bloquant: à appeler hors de la boucle d'événements)
+    pub fn forward(&self, id: &str, input: &Tensor) -> Result<Tensor> {
+        let executor = self.executor(id)?;
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        let output = executor.lock()
+            .map_err(|_| anyhow!("Shard {} executor poisoned", id))
+            .and_then(|mut executor| executor.forward(input));
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+        output
+    }
+
+    /// Exécute un shard chargé depuis un état de séquence vide, avec les poids déjà chargés:
+    /// l'état servi aux pipelines (cache KV) reste intact (bloquant)
+    pub fn forward_isolated(&self, id: &str, input: &Tensor) -> Result<Tensor> {
+        let executor = self.executor(id)?;
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        let output = executor.lock()
+            .map_err(|_| anyhow!("Shard {} executor poisoned", id))
+            .and_then(|executor| executor.forward_stateless(input));
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+        output
+    }
+
+    /// Exécutions en cours ou en attente d'un shard occupé (charge annoncée du nœud)
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Réinitialise l'état de séquence d'un shard chargé
+    pub fn reset(&self, id: &str) -> Result<()> {
+        let executor = self.executor(id)?;
+        let mut executor = executor.lock().map_err(|_| anyhow!("Shard {} executor poisoned", id))?;
+        executor.reset();
+        Ok(())
+    }
+
+    fn executor(&self, id: &str) -> Result<Arc<Mutex<Box<dyn ShardExecutor>>>> {
+        self.shards.lock()
+            .map_err(|_| anyhow!("Shard host lock poisoned"))?
+            .get(id)
+            .map(|hosted| Arc::clone(&hosted.executor))
+            .ok_or_else(|| anyhow!("Shard {} not loaded on this node", id))
+    }
+
+    pub fn is_loaded(&self, id: &str) -> bool {
+        self.shards.lock().is_ok_and(|shards| shards.contains_key(id))
+    }
+
+    /// Shards chargés et mémoire occupée
+    pub fn loaded(&self) -> Vec<LoadedShard> {
+        let Ok(shards) = self.shards.lock() else {
+            return Vec::new();
+        };
+        shards.values()
+            .map(|hosted| LoadedShard {
+                id: hosted.spec.id.clone(),
+                version: hosted.spec.version.clone(),
+                backend: hosted.spec.backend.clone(),
+                memory_bytes: hosted.executor.lock().map(|e| e.memory_usage()).unwrap_or(0),
+            })
+            .collect()
+    }
+
+    /// Shards à annoncer: uniquement ceux qui sont chargés, donc disponibles
+    pub fn shard_infos(&self) -> Vec<ShardInfo> {
+        self.loaded()
+            .into_iter()
+            .map(|shard| ShardInfo {
+                shard_id: shard.id,
+                version: shard.version,
+                available: true,
+            })
+            .collect()
+    }
+}
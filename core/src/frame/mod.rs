@@ -0,0 +1,316 @@
+// src/frame/mod.rs
+//! Format binaire des tenseurs échangés entre nœuds: en-tête (forme, type, disposition,
+//! requête, séquence), charge utile éventuellement réduite (fp16/bf16, int8) ou compressée
+//! (zstd), et somme de contrôle CRC32 sur l'ensemble de la trame.
+//!
+//! ```text
+//! "CXTF" | version u8 | dtype u8 | layout u8 | compression u8 | request_id u64 | sequence u64
+//! | ndim u8 | dims u32 × ndim | payload_len u32 | crc32 u32 | payload
+//! ```
+//! Entiers en little-endian. En int8, la charge utile contient une échelle f32 par ligne
+//! (dernière dimension), puis les valeurs quantifiées.
+pub mod stream;
+
+use anyhow::{anyhow, bail, Result};
+use half::{bf16, f16};
+use serde::{Deserialize, Serialize};
+
+use crate::executor::Tensor;
+
+pub const FRAME_MAGIC: &[u8] = b"CXTF";
+pub const FRAME_VERSION: u8 = 1;
+/// Octets lus avant de connaître la taille de l'en-tête (jusqu'à `ndim` inclus)
+pub const FRAME_PREFIX_LEN: usize = 25;
+/// Taille maximale d'une trame, en-tête compris
+pub const MAX_FRAME_BYTES: usize = 256 * 1024 * 1024;
+pub const MAX_DIMS: usize = 8;
+pub const DEFAULT_ZSTD_LEVEL: i32 = 3;
+
+/// Type des valeurs sur le fil
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WireDType {
+    #[default]
+    F32,
+    F16,
+    BF16,
+    /// Quantification symétrique par ligne
+    I8,
+}
+
+/// Ordre des valeurs dans la charge utile
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Layout {
+    #[default]
+    RowMajor,
+    ColumnMajor,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[default]
+    None,
+    Zstd,
+}
+
+/// Encodage choisi par l'émetteur
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FrameOptions {
+    pub dtype: WireDType,
+    pub layout: Layout,
+    pub compression: Compression,
+}
+
+/// En-tête décodé d'une trame
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameHeader {
+    pub dtype: WireDType,
+    pub layout: Layout,
+    pub compression: Compression,
+    /// Requête (génération, session) à laquelle appartient le tenseur
+    pub request_id: u64,
+    /// Rang de la trame dans le flux de la requête
+    pub sequence: u64,
+    pub shape: Vec<usize>,
+}
+
+impl WireDType {
+    fn code(self) -> u8 {
+        match self {
+            WireDType::F32 => 0,
+            WireDType::F16 => 1,
+            WireDType::BF16 => 2,
+            WireDType::I8 => 3,
+        }
+    }
+
+    fn from_code(code: u8) -> Result<Self> {
+        Ok(match code {
+            0 => WireDType::F32,
+            1 => WireDType::F16,
+            2 => WireDType::BF16,
+            3 => WireDType::I8,
+            other => bail!("Unknown frame dtype {}", other),
+        })
+    }
+
+    /// Taille de la charge utile non compressée
+    fn payload_len(self, shape: &[usize]) -> Option<usize> {
+        let elements = shape.iter().try_fold(1usize, |n, d| n.checked_mul(*d))?;
+        match self {
+            WireDType::F32 => elements.checked_mul(4),
+            WireDType::F16 | WireDType::BF16 => elements.checked_mul(2),
+            WireDType::I8 => rows(shape).checked_mul(4)?.checked_add(elements),
+        }
+    }
+}
+
+/// Nombre de lignes (produit des dimensions sauf la dernière) pour la quantification int8
+fn rows(shape: &[usize]) -> usize {
+    shape.split_last().map(|(_, outer)| outer.iter().product()).unwrap_or(1)
+}
+
+/// Passe d'un ordre ligne à un ordre colonne (`to_column`) ou l'inverse
+fn reorder(data: &[f32], shape: &[usize], to_column: bool) -> Vec<f32> {
+    if shape.len() < 2 {
+        return data.to_vec();
+    }
+    let mut out = vec![0.0; data.len()];
+    let mut index = vec![0usize; shape.len()];
+    for (row_major, value) in data.iter().enumerate() {
+        // Position en ordre colonne de l'indice courant
+        let mut column_major = 0;
+        for (i, d) in index.iter().zip(shape).rev() {
+            column_major = column_major * d + i;
+        }
+        if to_column {
+            out[column_major] = *value;
+        } else {
+            out[row_major] = data[column_major];
+        }
+        for (i, d) in index.iter_mut().zip(shape).rev() {
+            *i += 1;
+            if *i < *d {
+                break;
+            }
+            *i = 0;
+        }
+    }
+    out
+}
+
+fn quantize_i8(data: &[f32], shape: &[usize]) -> Vec<u8> {
+    let width = shape.last().copied().unwrap_or(1).max(1);
+    let mut scales = Vec::with_capacity(rows(shape) * 4);
+    let mut quants = Vec::with_capacity(data.len());
+    for row in data.chunks(width) {
+        let max = row.iter().fold(0.0f32, |m, v| m.max(v.abs()));
+        let scale = max / 127.0;
+        scales.extend(scale.to_le_bytes());
+        quants.extend(row.iter().map(|v| {
+            let q = if scale > 0.0 { (v / scale).round().clamp(-127.0, 127.0) } else { 0.0 };
+            q as i8 as u8
+        }));
+    }
+    scales.extend(quants);
+    scales
+}
+
+fn dequantize_i8(payload: &[u8], shape: &[usize]) -> Vec<f32> {
+    let width = shape.last().copied().unwrap_or(1).max(1);
+    let (scales, quants) = payload.split_at(rows(shape) * 4);
+    scales.chunks_exact(4)
+        .map(|s| f32::from_le_bytes([s[0], s[1], s[2], s[3]]))
+        .zip(quants.chunks(width))
+        .flat_map(|(scale, row)| row.iter().map(move |&q| q as i8 as f32 * scale))
+        .collect()
+}
+
+/// Encode un tenseur en trame
+pub fn encode_frame(tensor: &Tensor, request_id: u64, sequence: u64, options: &FrameOptions) -> Result<Vec<u8>> {
+    tensor.validate()?;
+    if tensor.shape.len() > MAX_DIMS {
+        bail!("Tensor has {} dimensions, at most {} allowed", tensor.shape.len(), MAX_DIMS);
+    }
+    let ordered;
+    let data = match options.layout {
+        Layout::RowMajor => &tensor.data,
+        Layout::ColumnMajor => {
+            ordered = reorder(&tensor.data, &tensor.shape, true);
+            &ordered
+        }
+    };
+    let raw: Vec<u8> = match options.dtype {
+        WireDType::F32 => data.iter().flat_map(|v| v.to_le_bytes()).collect(),
+        WireDType::F16 => data.iter().flat_map(|v| f16::from_f32(*v).to_le_bytes()).collect(),
+        WireDType::BF16 => data.iter().flat_map(|v| bf16::from_f32(*v).to_le_bytes()).collect(),
+        WireDType::I8 => {
+            if options.layout == Layout::ColumnMajor {
+                bail!("Int8 frames must be row-major");
+            }
+            quantize_i8(data, &tensor.shape)
+        }
+    };
+    let payload = match options.compression {
+        Compression::None => raw,
+        Compression::Zstd => zstd::bulk::compress(&raw, DEFAULT_ZSTD_LEVEL)?,
+    };
+
+    let mut frame = Vec::with_capacity(FRAME_PREFIX_LEN + tensor.shape.len() * 4 + 8 + payload.len());
+    frame.extend(FRAME_MAGIC);
+    frame.push(FRAME_VERSION);
+    frame.push(options.dtype.code());
+    frame.push(options.layout as u8);
+    frame.push(options.compression as u8);
+    frame.extend(request_id.to_le_bytes());
+    frame.extend(sequence.to_le_bytes());
+    frame.push(tensor.shape.len() as u8);
+    for d in &tensor.shape {
+        frame.extend(u32::try_from(*d)?.to_le_bytes());
+    }
+    frame.extend(u32::try_from(payload.len())?.to_le_bytes());
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(&frame);
+    crc.update(&payload);
+    frame.extend(crc.finalize().to_le_bytes());
+    frame.extend(payload);
+    if frame.len() > MAX_FRAME_BYTES {
+        bail!("Frame of {} bytes exceeds {} bytes", frame.len(), MAX_FRAME_BYTES);
+    }
+    Ok(frame)
+}
+
+/// Taille de l'en-tête complet, d'après ses `FRAME_PREFIX_LEN` premiers octets
+pub fn header_len(prefix: &[u8]) -> Result<usize> {
+    if prefix.len() < FRAME_PREFIX_LEN || &prefix[..4] != FRAME_MAGIC {
+        bail!("Not a tensor frame");
+    }
+    if prefix[4] != FRAME_VERSION {
+        bail!("Unsupported frame version {}", prefix[4]);
+    }
+    let ndim = prefix[FRAME_PREFIX_LEN - 1] as usize;
+    if ndim > MAX_DIMS {
+        bail!("Frame has {} dimensions, at most {} allowed", ndim, MAX_DIMS);
+    }
+    Ok(FRAME_PREFIX_LEN + ndim * 4 + 8)
+}
+
+/// Taille de la trame entière, d'après son en-tête complet
+pub fn frame_len(header: &[u8]) -> Result<usize> {
+    let len = header_len(header)?;
+    if header.len() < len {
+        bail!("Truncated frame header");
+    }
+    let payload = u32::from_le_bytes(header[len - 8..len - 4].try_into()?) as usize;
+    let total = len + payload;
+    if total > MAX_FRAME_BYTES {
+        bail!("Frame of {} bytes exceeds {} bytes", total, MAX_FRAME_BYTES);
+    }
+    Ok(total)
+}
+
+/// Décode une trame complète, après vérification de sa somme de contrôle
+pub fn decode_frame(frame: &[u8]) -> Result<(FrameHeader, Tensor)> {
+    let len = header_len(frame)?;
+    if frame_len(frame)? != frame.len() {
+        bail!("Frame length does not match its header");
+    }
+    let (header, payload) = frame.split_at(len);
+    let expected = u32::from_le_bytes(header[len - 4..].try_into()?);
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(&header[..len - 4]);
+    crc.update(payload);
+    if crc.finalize() != expected {
+        bail!("Frame checksum mismatch");
+    }
+
+    let dtype = WireDType::from_code(header[5])?;
+    let layout = match header[6] {
+        0 => Layout::RowMajor,
+        1 => Layout::ColumnMajor,
+        other => bail!("Unknown frame layout {}", other),
+    };
+    let compression = match header[7] {
+        0 => Compression::None,
+        1 => Compression::Zstd,
+        other => bail!("Unknown frame compression {}", other),
+    };
+    let request_id = u64::from_le_bytes(header[8..16].try_into()?);
+    let sequence = u64::from_le_bytes(header[16..24].try_into()?);
+    let shape: Vec<usize> = header[FRAME_PREFIX_LEN..len - 8]
+        .chunks_exact(4)
+        .map(|d| u32::from_le_bytes([d[0], d[1], d[2], d[3]]) as usize)
+        .collect();
+
+    // La forme vient de l'émetteur: elle borne la décompression, elle doit donc être bornée
+    let raw_len = dtype.payload_len(&shape).ok_or_else(|| anyhow!("Frame shape {:?} overflows", shape))?;
+    if raw_len > MAX_FRAME_BYTES {
+        bail!("Frame shape {:?} needs {} bytes, at most {} allowed", shape, raw_len, MAX_FRAME_BYTES);
+    }
+    let decompressed;
+    let raw = match compression {
+        Compression::None => payload,
+        Compression::Zstd => {
+            decompressed = zstd::bulk::decompress(payload, raw_len)?;
+            &decompressed[..]
+        }
+    };
+    if raw.len() != raw_len {
+        bail!("Frame payload has {} bytes, {} expected for {:?} {:?}", raw.len(), raw_len, dtype, shape);
+    }
+    let data: Vec<f32> = match dtype {
+        WireDType::F32 => raw.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect(),
+        WireDType::F16 => raw.chunks_exact(2).map(|b| f16::from_le_bytes([b[0], b[1]]).to_f32()).collect(),
+        WireDType::BF16 => raw.chunks_exact(2).map(|b| bf16::from_le_bytes([b[0], b[1]]).to_f32()).collect(),
+        WireDType::I8 => dequantize_i8(raw, &shape),
+    };
+    let data = match layout {
+        Layout::RowMajor => data,
+        Layout::ColumnMajor => reorder(&data, &shape, false),
+    };
+    let tensor = Tensor::new(shape.clone(), data)?;
+    Ok((FrameHeader { dtype, layout, compression, request_id, sequence, shape }, tensor))
+}
@@ -0,0 +1,257 @@
+// src/frame/stream.rs
+//! Protocole de flux dédié aux trames de tenseurs. La contre-pression est de bout en bout:
+//! le lecteur n'avance sur le flux que lorsque la file des trames reçues a de la place, et
+//! le contrôle de flux du multiplexeur bloque alors l'écriture de l'émetteur. Un seul flux
+//! par pair et par sens, partagé par toutes les requêtes: les trames reçues sont aiguillées
+//! par pair et par requête, chaque requête a sa propre file.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Result};
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use libp2p::{PeerId, Stream, StreamProtocol};
+use tokio::sync::mpsc;
+
+use super::{decode_frame, encode_frame, frame_len, header_len, FrameHeader, FrameOptions, FRAME_PREFIX_LEN};
+use crate::discovery::NodeHandle;
+use crate::executor::Tensor;
+use crate::policy::DataClass;
+use crate::topics::TopicNamespace;
+
+pub const TENSOR_PROTOCOL_VERSION: u32 = 1;
+/// Trames reçues en attente de traitement, par requête; au-delà, la lecture du flux s'arrête
+pub const FRAME_QUEUE: usize = 8;
+/// Attente de la trame d'une requête: entrée côté serveur, sortie une fois la réponse reçue
+pub const FRAME_TIMEOUT_SECS: u64 = 30;
+/// Requêtes dont des trames sont arrivées sans que personne ne les attende encore
+pub const MAX_UNCLAIMED_ROUTES: usize = 64;
+/// Durée de vie des trames d'une requête non réclamée
+pub const UNCLAIMED_ROUTE_SECS: u64 = 30;
+
+/// Nom du protocole, propre au mesh comme celui des requêtes directes
+pub fn protocol(namespace: &TopicNamespace) -> StreamProtocol {
+    let name = format!("/cortex/{}/tensor/{}", namespace.mesh(), TENSOR_PROTOCOL_VERSION);
+    StreamProtocol::try_from_owned(name).unwrap_or(StreamProtocol::new("/cortex/tensor/1"))
+}
+
+/// Trame reçue d'un pair
+#[derive(Debug, Clone, PartialEq)]
+pub struct InboundFrame {
+    pub peer: PeerId,
+    pub header: FrameHeader,
+    pub tensor: Tensor,
+}
+
+/// Lit la trame suivante dans un seul tampon; `None` si le flux s'est fermé proprement
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Option<Vec<u8>>> {
+    let mut frame = vec![0u8; FRAME_PREFIX_LEN];
+    let mut filled = 0;
+    while filled < FRAME_PREFIX_LEN {
+        let read = reader.read(&mut frame[filled..]).await?;
+        if read == 0 {
+            if filled == 0 {
+                return Ok(None);
+            }
+            bail!("Stream closed inside a frame header");
+        }
+        filled += read;
+    }
+    let header = header_len(&frame)?;
+    frame.resize(header, 0);
+    reader.read_exact(&mut frame[FRAME_PREFIX_LEN..]).await?;
+    let total = frame_len(&frame)?;
+    frame.resize(total, 0);
+    reader.read_exact(&mut frame[header..]).await?;
+    Ok(Some(frame))
+}
+
+pub async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, frame: &[u8]) -> Result<()> {
+    writer.write_all(frame).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+struct Route {
+    sender: mpsc::Sender<InboundFrame>,
+    /// File pas encore réclamée par un destinataire
+    receiver: Option<mpsc::Receiver<InboundFrame>>,
+    created: Instant,
+}
+
+impl Route {
+    fn new() -> Self {
+        let (sender, receiver) = mpsc::channel(FRAME_QUEUE);
+        Route { sender, receiver: Some(receiver), created: Instant::now() }
+    }
+}
+
+/// Aiguillage des trames reçues vers la file de leur requête, identifiée par le pair émetteur
+/// et l'identifiant de requête porté par chaque trame
+#[derive(Default)]
+pub struct FrameRouter {
+    routes: Mutex<HashMap<(PeerId, u64), Route>>,
+}
+
+impl FrameRouter {
+    /// Réclame la file des trames de la requête `request_id` venant de `peer`, y compris celles
+    /// déjà arrivées; une seule file par requête
+    pub fn subscribe(self: &Arc<Self>, peer: PeerId, request_id: u64) -> Result<FrameReceiver> {
+        let mut routes = self.routes.lock().map_err(|_| anyhow!("Frame routes poisoned"))?;
+        let route = routes.entry((peer, request_id)).or_insert_with(Route::new);
+        let receiver = route.receiver.take()
+            .ok_or_else(|| anyhow!("Frames of request {} from {} already claimed", request_id, peer))?;
+        Ok(FrameReceiver { router: Arc::clone(self), peer, request_id, receiver })
+    }
+
+    /// File d'une requête, créée à sa première trame; `None` si trop de requêtes attendent
+    /// déjà d'être réclamées
+    fn sender(&self, peer: PeerId, request_id: u64) -> Option<mpsc::Sender<InboundFrame>> {
+        let mut routes = self.routes.lock().ok()?;
+        if let Some(route) = routes.get(&(peer, request_id)) {
+            return Some(route.sender.clone());
+        }
+        let ttl = Duration::from_secs(UNCLAIMED_ROUTE_SECS);
+        routes.retain(|_, route| route.receiver.is_none() || route.created.elapsed() < ttl);
+        if routes.values().filter(|route| route.receiver.is_some()).count() >= MAX_UNCLAIMED_ROUTES {
+            return None;
+        }
+        let route = Route::new();
+        let sender = route.sender.clone();
+        routes.insert((peer, request_id), route);
+        Some(sender)
+    }
+
+    fn remove(&self, peer: PeerId, request_id: u64) {
+        if let Ok(mut routes) = self.routes.lock() {
+            routes.remove(&(peer, request_id));
+        }
+    }
+}
+
+/// File des trames d'une requête; la route est retirée quand la file est abandonnée
+pub struct FrameReceiver {
+    router: Arc<FrameRouter>,
+    peer: PeerId,
+    request_id: u64,
+    receiver: mpsc::Receiver<InboundFrame>,
+}
+
+impl FrameReceiver {
+    pub fn request_id(&self) -> u64 {
+        self.request_id
+    }
+
+    /// Prochaine trame de la requête
+    pub async fn recv(&mut self) -> Option<InboundFrame> {
+        self.receiver.recv().await
+    }
+
+    /// Prochaine trame de la requête, dans le délai imparti
+    pub async fn recv_timeout(&mut self, timeout: Duration) -> Result<Tensor> {
+        match tokio::time::timeout(timeout, self.receiver.recv()).await {
+            Ok(Some(frame)) => Ok(frame.tensor),
+            Ok(None) => bail!("Frames of request {} from {} dropped", self.request_id, self.peer),
+            Err(_) => bail!("No tensor received from {} for request {} within {:?}", self.peer, self.request_id, timeout),
+        }
+    }
+}
+
+impl Drop for FrameReceiver {
+    fn drop(&mut self) {
+        self.router.remove(self.peer, self.request_id);
+    }
+}
+
+/// Lit les trames d'un flux entrant et les place dans la file de leur requête, jusqu'à la fermeture
+pub(crate) async fn receive_frames(peer: PeerId, mut stream: Stream, router: Arc<FrameRouter>) {
+    loop {
+        let frame = match read_frame(&mut stream).await {
+            Ok(Some(frame)) => frame,
+            Ok(None) => break,
+            Err(e) => {
+                println!("⚠️ Flux de tenseurs de {} interrompu: {:#}", peer, e);
+                break;
+            }
+        };
+        let (header, tensor) = match decode_frame(&frame) {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                println!("⚠️ Trame invalide de {}: {:#}", peer, e);
+                break;
+            }
+        };
+        let Some(queue) = router.sender(peer, header.request_id) else {
+            println!("⚠️ Trame de {} ignorée: trop de requêtes en attente", peer);
+            continue;
+        };
+        if queue.send(InboundFrame { peer, header, tensor }).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Flux sortant vers un pair, partagé par les requêtes: les trames s'y succèdent entières.
+/// Un seul flux entrant par pair évite de saturer l'acceptation des flux, qui rejette ceux
+/// arrivés avant que le précédent ne soit pris en charge.
+pub(crate) type TensorLink = Arc<tokio::sync::Mutex<Option<Stream>>>;
+
+/// Trames de tenseurs d'une requête vers un pair, sur le flux partagé avec ce pair
+pub struct TensorStream {
+    handle: NodeHandle,
+    peer: PeerId,
+    link: TensorLink,
+    request_id: u64,
+    sequence: u64,
+    options: FrameOptions,
+}
+
+impl TensorStream {
+    pub(crate) fn new(handle: NodeHandle, peer: PeerId, link: TensorLink, request_id: u64) -> Self {
+        TensorStream { handle, peer, link, request_id, sequence: 0, options: FrameOptions::default() }
+    }
+
+    pub fn with_options(mut self, options: FrameOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn peer(&self) -> PeerId {
+        self.peer
+    }
+
+    pub fn request_id(&self) -> u64 {
+        self.request_id
+    }
+
+    /// Envoie un tenseur; attend si le pair ne consomme pas assez vite
+    pub async fn send(&mut self, tensor: &Tensor) -> Result<()> {
+        let frame = encode_frame(tensor, self.request_id, self.sequence, &self.options)?;
+        self.handle.authorize_egress(DataClass::Activations, &self.peer, false, &frame)?;
+        self.write(&frame).await
+    }
+
+    /// Envoie un tenseur dont la sortie vers ce pair a déjà été autorisée par l'appelant
+    pub(crate) async fn send_authorized(&mut self, tensor: &Tensor) -> Result<()> {
+        let frame = encode_frame(tensor, self.request_id, self.sequence, &self.options)?;
+        self.write(&frame).await
+    }
+
+    /// Écrit une trame sur le flux du pair; un flux rompu (connexion coupée, pair redémarré)
+    /// est rouvert une fois
+    async fn write(&mut self, frame: &[u8]) -> Result<()> {
+        let mut link = self.link.lock().await;
+        let written = match link.as_mut() {
+            Some(stream) => write_frame(stream, frame).await.is_ok(),
+            None => false,
+        };
+        if !written {
+            *link = None;
+            let mut stream = self.handle.connect_tensor_stream(self.peer).await?;
+            write_frame(&mut stream, frame).await?;
+            *link = Some(stream);
+        }
+        self.sequence += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,293 @@
+// src/harness/mod.rs
+//! Banc de test multi-nœuds: N nœuds du mesh dans un même processus,
+//! reliés par le transport mémoire de libp2p, pilotés par commandes.
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::{anyhow, Result};
+use libp2p::identity::Keypair;
+use libp2p::multiaddr::Protocol;
+use libp2p::{Multiaddr, PeerId};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration, Instant};
+
+use crate::config::CortexConfig;
+use crate::discovery::{create_memory_transport, BoxedTransport, MeshNode, NodeHandle, NodeOptions, NodeRole};
+use crate::registry::Registry;
+
+/// Ports `/memory/<n>` partagés par tous les bancs du processus (tests parallèles)
+static NEXT_MEMORY_PORT: AtomicU64 = AtomicU64::new(1);
+
+const POLL_INTERVAL_MS: u64 = 100;
+
+/// Un nœud lancé par le banc
+pub struct HarnessNode {
+    pub role: NodeRole,
+    /// Port mémoire du nœud (identifie le nœud dans le réseau simulé)
+    pub port: u64,
+    /// Adresse complète, `/memory/<n>/p2p/<PeerId>`
+    pub addr: Multiaddr,
+    pub handle: NodeHandle,
+    shutdown: Option<oneshot::Sender<()>>,
+    task: JoinHandle<Result<()>>,
+}
+
+impl HarnessNode {
+    pub fn peer_id(&self) -> PeerId {
+        self.handle.peer_id()
+    }
+
+    /// Copie du registre courant du nœud
+    pub fn registry(&self) -> Registry {
+        self.handle.registry().lock().map(|r| r.clone()).unwrap_or_default()
+    }
+
+    /// Le nœud connaît-il `peer` dans son registre ?
+    pub fn knows(&self, peer: &PeerId) -> bool {
+        self.registry().nodes.contains_key(&peer.to_string())
+    }
+}
+
+/// Fabrique de transport, pour remplacer le transport mémoire (ex: simulation de pannes)
+pub type TransportFactory = Box<dyn Fn(&Keypair, u64) -> Result<BoxedTransport> + Send + Sync>;
+
+/// Ensemble de nœuds en mémoire. Les indices restent stables: un nœud parti laisse un trou.
+pub struct MeshHarness {
+    config: CortexConfig,
+    nodes: Vec<Option<HarnessNode>>,
+    transport_factory: TransportFactory,
+}
+
+impl MeshHarness {
+    /// Banc vide, avec la configuration par défaut
+    pub fn new() -> Self {
+        Self::with_config(CortexConfig::default())
+    }
+
+    pub fn with_config(config: CortexConfig) -> Self {
+        MeshHarness {
+            config,
+            nodes: Vec::new(),
+            transport_factory: Box::new(|keypair, _port| create_memory_transport(keypair)),
+        }
+    }
+
+    /// Configuration des nœuds lancés ensuite (ex: un nœud qui ignore une règle du mesh)
+    pub fn config_mut(&mut self) -> &mut CortexConfig {
+        &mut self.config
+    }
+
+    /// Remplace la fabrique de transport (reçoit la clé du nœud et son port mémoire)
+    pub fn with_transport_factory(mut self, factory: TransportFactory) -> Self {
+        self.transport_factory = factory;
+        self
+    }
+
+    /// Lance un bootstrap puis `n - 1` nœuds légers qui s'y connectent
+    pub async fn start(n: usize) -> Result<Self> {
+        let mut harness = Self::new();
+        harness.spawn_many(n).await?;
+        Ok(harness)
+    }
+
+    /// Lance un bootstrap (s'il n'y en a pas encore) puis des nœuds légers jusqu'à `n` nouveaux nœuds
+    pub async fn spawn_many(&mut self, n: usize) -> Result<Vec<usize>> {
+        let mut spawned = Vec::with_capacity(n);
+        for _ in 0..n {
+            let role = if self.bootstrap_addr().is_none() {
+                NodeRole::Bootstrap
+            } else {
+                NodeRole::Light
+            };
+            spawned.push(self.spawn_node(role).await?);
+        }
+        Ok(spawned)
+    }
+
+    /// Lance un nœud, connecté au premier bootstrap actif. Renvoie son indice.
+    pub async fn spawn_node(&mut self, role: NodeRole) -> Result<usize> {
+        self.spawn_node_with_key(role, Keypair::generate_ed25519()).await
+    }
+
+    /// Lance un nœud avec une identité donnée (ex: redémarrage après un crash)
+    pub async fn spawn_node_with_key(&mut self, role: NodeRole, keypair: Keypair) -> Result<usize> {
+        self.spawn_node_with(role, keypair, |_| {}).await
+    }
+
+    /// Lance un nœud en ajustant ses options (ex: certificat d'organisation)
+    pub async fn spawn_node_with<F>(&mut self, role: NodeRole, keypair: Keypair, configure: F) -> Result<usize>
+    where
+        F: FnOnce(&mut NodeOptions),
+    {
+        let port = NEXT_MEMORY_PORT.fetch_add(1, Ordering::Relaxed);
+        let peer_id = PeerId::from(keypair.public());
+        let listen_addr = Multiaddr::empty().with(Protocol::Memory(port));
+        let bootstrap_peers = self.bootstrap_addr().into_iter().collect();
+
+        let transport = (self.transport_factory)(&keypair, port)?;
+        let mut options = NodeOptions::in_memory(role, listen_addr.clone(), bootstrap_peers);
+        configure(&mut options);
+        let (node, handle) = MeshNode::new(keypair, &self.config, options, transport).await?;
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let task = tokio::spawn(node.run(async move {
+            let _ = shutdown_rx.await;
+        }));
+
+        self.nodes.push(Some(HarnessNode {
+            role,
+            port,
+            addr: listen_addr.with(Protocol::P2p(peer_id)),
+            handle,
+            shutdown: Some(shutdown_tx),
+            task,
+        }));
+        Ok(self.nodes.len() - 1)
+    }
+
+    /// Adresse du premier bootstrap actif
+    pub fn bootstrap_addr(&self) -> Option<Multiaddr> {
+        self.running()
+            .find(|n| n.role == NodeRole::Bootstrap)
+            .map(|n| n.addr.clone())
+    }
+
+    pub fn node(&self, index: usize) -> Option<&HarnessNode> {
+        self.nodes.get(index).and_then(|n| n.as_ref())
+    }
+
+    /// Nœuds encore actifs
+    pub fn running(&self) -> impl Iterator<Item = &HarnessNode> {
+        self.nodes.iter().flatten()
+    }
+
+    /// Ports mémoire des nœuds actifs aux indices donnés
+    pub fn ports(&self, indices: &[usize]) -> Vec<u64> {
+        indices.iter().filter_map(|i| self.node(*i)).map(|n| n.port).collect()
+    }
+
+    pub fn peer_ids(&self) -> Vec<PeerId> {
+        self.running().map(|n| n.peer_id()).collect()
+    }
+
+    /// Départ propre: annonce de départ et drainage, comme sur SIGTERM
+    pub async fn leave(&mut self, index: usize) -> Result<()> {
+        let mut node = self.take(index)?;
+        if let Some(tx) = node.shutdown.take() {
+            let _ = tx.send(());
+        }
+        node.task.await??;
+        Ok(())
+    }
+
+    /// Arrêt brutal: la tâche est interrompue sans annonce de départ
+    pub fn crash(&mut self, index: usize) -> Result<PeerId> {
+        let node = self.take(index)?;
+        node.task.abort();
+        Ok(node.peer_id())
+    }
+
+    fn take(&mut self, index: usize) -> Result<HarnessNode> {
+        self.nodes
+            .get_mut(index)
+            .and_then(|n| n.take())
+            .ok_or_else(|| anyhow!("No running node at index {}", index))
+    }
+
+    /// Demande à chaque nœud actif de publier son annonce
+    pub async fn announce_all(&self) -> Result<()> {
+        for node in self.running() {
+            node.handle.announce().await?;
+        }
+        Ok(())
+    }
+
+    /// Attend que `condition` soit vraie, en la réévaluant périodiquement
+    pub async fn wait_until<F>(&self, timeout: Duration, mut condition: F) -> bool
+    where
+        F: FnMut(&Self) -> bool,
+    {
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            if condition(self) {
+                return true;
+            }
+            sleep(Duration::from_millis(POLL_INTERVAL_MS)).await;
+        }
+        condition(self)
+    }
+
+    /// Attend que chaque nœud actif, bootstrap compris, ait au moins une connexion
+    pub async fn wait_for_connections(&self, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let mut all_connected = true;
+            for node in self.running() {
+                if node.handle.connected_peers().await?.is_empty() {
+                    all_connected = false;
+                }
+            }
+            if all_connected {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(anyhow!("Nodes not connected after {:?}", timeout));
+            }
+            sleep(Duration::from_millis(POLL_INTERVAL_MS)).await;
+        }
+    }
+
+    /// Chaque registre actif contient exactement les autres nœuds actifs
+    pub fn registries_converged(&self) -> bool {
+        let peers: Vec<String> = self.peer_ids().iter().map(|p| p.to_string()).collect();
+        self.running().all(|node| {
+            let registry = node.registry();
+            let me = node.peer_id().to_string();
+            registry.nodes.len() == peers.len() - 1
+                && peers.iter().filter(|p| **p != me).all(|p| registry.nodes.contains_key(p))
+        })
+    }
+
+    /// Republie les annonces jusqu'à ce que `condition` soit vraie (les nœuds
+    /// actifs restent ainsi frais dans les registres pendant l'attente)
+    pub async fn announce_until<F>(&self, timeout: Duration, mut condition: F) -> Result<bool>
+    where
+        F: FnMut(&Self) -> bool,
+    {
+        let deadline = Instant::now() + timeout;
+        loop {
+            self.announce_all().await?;
+            if self.wait_until(Duration::from_millis(500), &mut condition).await {
+                return Ok(true);
+            }
+            if Instant::now() >= deadline {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Republie les annonces jusqu'à convergence de tous les registres
+    pub async fn converge(&self, timeout: Duration) -> Result<()> {
+        if self.announce_until(timeout, |h| h.registries_converged()).await? {
+            Ok(())
+        } else {
+            Err(anyhow!("Registries did not converge after {:?}", timeout))
+        }
+    }
+
+    /// Arrête proprement tous les nœuds restants
+    pub async fn shutdown(mut self) -> Result<()> {
+        for index in 0..self.nodes.len() {
+            if self.node(index).is_some() {
+                self.leave(index).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for MeshHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,293 @@
+// src/identity/keystore.rs
+use std::fs;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::XChaCha20Poly1305;
+use libp2p::identity::{ed25519, Keypair, PeerId};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// Phrase de passe fournie directement
+pub const ENV_PASSPHRASE: &str = "CORTEX_KEY_PASSPHRASE";
+/// Fichier contenant la phrase de passe (ex: secret Docker)
+pub const ENV_PASSPHRASE_FILE: &str = "CORTEX_KEY_PASSPHRASE_FILE";
+/// Force le chiffrement des nouvelles clés (la phrase de passe est alors demandée)
+pub const ENV_ENCRYPT: &str = "CORTEX_KEY_ENCRYPT";
+/// Autorise le chargement d'une clé en clair lisible par tous
+pub const ENV_ALLOW_INSECURE: &str = "CORTEX_ALLOW_INSECURE_KEY";
+
+const KEYSTORE_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+
+/// Paramètres Argon2id (OWASP: 19 Mio, 2 passes, 1 voie)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub algorithm: String,
+    pub m_cost_kib: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams {
+            algorithm: "argon2id".into(),
+            m_cost_kib: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+}
+
+/// Clé d'identité chiffrée (`identity.key.enc`): Argon2id + XChaCha20-Poly1305
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedKeyFile {
+    pub version: u32,
+    /// PeerId en clair, pour identifier la clé sans la déchiffrer
+    pub peer_id: String,
+    pub kdf: KdfParams,
+    pub cipher: String,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], kdf: &KdfParams) -> Result<[u8; 32]> {
+    if kdf.algorithm != "argon2id" {
+        bail!("Unsupported KDF: {}", kdf.algorithm);
+    }
+    let params = Params::new(kdf.m_cost_kib, kdf.t_cost, kdf.p_cost, Some(32))
+        .map_err(|e| anyhow!("Invalid Argon2 parameters: {}", e))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Chiffre une paire de clés avec une phrase de passe
+pub fn encrypt_keypair(keypair: &ed25519::Keypair, passphrase: &str) -> Result<EncryptedKeyFile> {
+    let kdf = KdfParams::default();
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt, &kdf)?;
+
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, keypair.to_bytes().as_ref())
+        .map_err(|_| anyhow!("Encryption failed"))?;
+
+    let peer_id = PeerId::from(Keypair::from(keypair.clone()).public());
+    Ok(EncryptedKeyFile {
+        version: KEYSTORE_VERSION,
+        peer_id: peer_id.to_string(),
+        kdf,
+        cipher: "xchacha20poly1305".into(),
+        salt: STANDARD.encode(salt),
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+    })
+}
+
+/// Déchiffre une clé; échoue si la phrase de passe est fausse ou le fichier altéré
+pub fn decrypt_keypair(file: &EncryptedKeyFile, passphrase: &str) -> Result<ed25519::Keypair> {
+    if file.version != KEYSTORE_VERSION {
+        bail!("Unsupported keystore version: {}", file.version);
+    }
+    if file.cipher != "xchacha20poly1305" {
+        bail!("Unsupported cipher: {}", file.cipher);
+    }
+    let salt = STANDARD.decode(&file.salt)?;
+    let nonce = STANDARD.decode(&file.nonce)?;
+    let ciphertext = STANDARD.decode(&file.ciphertext)?;
+    if nonce.len() != 24 {
+        bail!("Invalid nonce length");
+    }
+
+    let key = derive_key(passphrase, &salt, &file.kdf)?;
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    let mut plaintext = cipher
+        .decrypt(nonce.as_slice().into(), ciphertext.as_ref())
+        .map_err(|_| anyhow!("Wrong passphrase or corrupted keystore"))?;
+
+    let keypair = ed25519::Keypair::try_from_bytes(&mut plaintext)
+        .map_err(|e| anyhow!("Failed to decode keypair: {:?}", e))?;
+    let peer_id = PeerId::from(Keypair::from(keypair.clone()).public());
+    if peer_id.to_string() != file.peer_id {
+        bail!("Keystore PeerId mismatch: expected {}, got {}", file.peer_id, peer_id);
+    }
+    Ok(keypair)
+}
+
+/// Lit un keystore chiffré
+pub fn read_encrypted(path: &Path) -> Result<EncryptedKeyFile> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read keystore {:?}", path))?;
+    serde_json::from_str(&content).context("Failed to parse keystore")
+}
+
+/// Écrit un keystore chiffré (0600)
+pub fn write_encrypted(path: &Path, file: &EncryptedKeyFile) -> Result<()> {
+    let json = serde_json::to_string_pretty(file)?;
+    write_private_file(path, json.as_bytes())
+}
+
+/// Usage de la phrase de passe demandée
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassphrasePurpose {
+    /// Déverrouiller une clé existante
+    Unlock,
+    /// Chiffrer une nouvelle clé (confirmation demandée au prompt)
+    New,
+}
+
+/// Résout la phrase de passe: variable d'environnement, puis fichier, puis prompt interactif.
+/// Renvoie `None` si aucune source n'est disponible (ex: conteneur sans TTY).
+pub fn resolve_passphrase(purpose: PassphrasePurpose) -> Result<Option<String>> {
+    if let Ok(passphrase) = std::env::var(ENV_PASSPHRASE) {
+        return Ok(Some(passphrase));
+    }
+    if let Ok(file) = std::env::var(ENV_PASSPHRASE_FILE) {
+        let content = fs::read_to_string(&file)
+            .with_context(|| format!("Failed to read passphrase file {}", file))?;
+        return Ok(Some(content.trim_end_matches(['\n', '\r']).to_string()));
+    }
+    if !std::io::stdin().is_terminal() {
+        return Ok(None);
+    }
+
+    let passphrase = rpassword::prompt_password("🔐 Phrase de passe de l'identité: ")?;
+    if purpose == PassphrasePurpose::New {
+        let confirm = rpassword::prompt_password("🔐 Confirmation: ")?;
+        if confirm != passphrase {
+            bail!("Passphrases do not match");
+        }
+        if passphrase.is_empty() {
+            bail!("Empty passphrase");
+        }
+    }
+    Ok(Some(passphrase))
+}
+
+/// Les nouvelles clés doivent-elles être chiffrées ?
+pub fn wants_encryption() -> bool {
+    let forced = std::env::var(ENV_ENCRYPT)
+        .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    forced || std::env::var(ENV_PASSPHRASE).is_ok() || std::env::var(ENV_PASSPHRASE_FILE).is_ok()
+}
+
+fn insecure_override() -> bool {
+    std::env::var(ENV_ALLOW_INSECURE)
+        .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Écrit un fichier lisible par son seul propriétaire (0600), en remplaçant l'existant.
+///
+/// Le contenu est d'abord écrit dans un fichier temporaire 0600 du même répertoire,
+/// puis renommé sur la cible: un lecteur voit l'ancien fichier ou le nouveau, jamais un fichier tronqué.
+pub fn write_private_file(path: &Path, contents: &[u8]) -> Result<()> {
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty());
+    if let Some(parent) = parent {
+        create_private_dir(parent)?;
+    }
+    let name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid file path {:?}", path))?
+        .to_string_lossy();
+    let temp = parent
+        .unwrap_or_else(|| Path::new("."))
+        .join(format!(".{}.{}.tmp", name, std::process::id()));
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let written = options
+        .open(&temp)
+        .with_context(|| format!("Failed to open {:?}", temp))
+        .and_then(|mut file| {
+            // Un fichier temporaire laissé par une écriture interrompue garde ses anciens droits
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                file.set_permissions(fs::Permissions::from_mode(0o600))?;
+            }
+            file.write_all(contents)?;
+            file.sync_all()?;
+            Ok(())
+        })
+        .and_then(|()| fs::rename(&temp, path).with_context(|| format!("Failed to replace {:?}", path)));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written
+}
+
+/// Crée le répertoire s'il n'existe pas, réservé à son propriétaire (0700)
+pub fn create_private_dir(dir: &Path) -> Result<()> {
+    if dir.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(dir)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+/// Ramène les droits d'un fichier à 0600
+fn restrict_permissions(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Vérifie les droits d'une clé en clair avant chargement.
+///
+/// Une clé lisible par tous est refusée sauf si `CORTEX_ALLOW_INSECURE_KEY` est défini;
+/// dans tous les cas, des droits plus larges que 0600 sont ramenés à 0600.
+pub fn check_plaintext_permissions(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(path)?.permissions().mode() & 0o777;
+        if mode & 0o077 == 0 {
+            return Ok(());
+        }
+        if mode & 0o004 != 0 {
+            if !insecure_override() {
+                bail!(
+                    "Refusing to load world-readable key {:?} (mode {:o}). Run `chmod 600` on it, \
+                     or set {}=1 to override",
+                    path,
+                    mode,
+                    ENV_ALLOW_INSECURE
+                );
+            }
+            println!("⚠️ Clé lisible par tous ({:o}) chargée malgré tout ({}=1)", mode, ENV_ALLOW_INSECURE);
+        }
+        println!("🔒 Droits de {:?} ramenés de {:o} à 600", path, mode);
+        restrict_permissions(path)?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
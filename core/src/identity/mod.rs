@@ -6,95 +6,160 @@ use libp2p::identity::{ed25519, Keypair, PeerId};
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, Context, anyhow};
 
+pub mod format;
+pub mod keystore;
+pub mod paths;
+pub mod succession;
+
+pub use paths::CortexPaths;
+
+use keystore::PassphrasePurpose;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IdentityInfo {
     pub peer_id: String,
     pub public_key: String,
 }
 
-/// Déterminer le chemin du répertoire .cortex en fonction de l'environnement
-pub fn get_cortex_dir() -> PathBuf {
-    if let Ok(home) = std::env::var("HOME") {
-        PathBuf::from(home).join(".cortex")
-    } else if let Some(home) = dirs::home_dir() {
-        home.join(".cortex")
-    } else {
-        // Fallback pour Docker
-        PathBuf::from("/home/cortexuser/.cortex")
+impl IdentityInfo {
+    pub fn from_keypair(keypair: &ed25519::Keypair) -> Self {
+        let public_key = STANDARD.encode(keypair.public().to_bytes());
+        let peer_id = PeerId::from(Keypair::from(keypair.clone()).public());
+        IdentityInfo {
+            peer_id: peer_id.to_string(),
+            public_key,
+        }
     }
 }
 
-/// Obtenir le chemin du fichier clé
-pub fn get_key_path() -> PathBuf {
-    get_cortex_dir().join("identity.key")
+/// Chemins du profil courant (`CORTEX_HOME`, `CORTEX_PROFILE`).
+/// Un nom de profil invalide est une erreur, remontée à l'appelant.
+pub fn cortex_paths() -> Result<CortexPaths> {
+    CortexPaths::from_env()
 }
 
-/// Obtenir le chemin du fichier info
-pub fn get_info_path() -> PathBuf {
-    get_cortex_dir().join("identity.json")
+/// Une identité (chiffrée ou en clair) existe-t-elle déjà ?
+pub fn identity_exists(paths: &CortexPaths) -> bool {
+    paths.encrypted_key().exists() || paths.key().exists()
 }
 
-/// Génère ou charge un ed25519::Keypair brut
-pub fn load_or_generate_identity() -> Result<ed25519::Keypair> {
-    let path = get_key_path();
-    println!("Chemin de la clé: {:?}", path);
+/// L'identité existante est-elle chiffrée ?
+pub fn identity_is_encrypted(paths: &CortexPaths) -> bool {
+    paths.encrypted_key().exists()
+}
 
-    if path.exists() {
+/// Copie les fichiers de clé existants vers `<fichier>.<horodatage>.bak` (0600).
+/// Renvoie les chemins des sauvegardes.
+pub fn backup_identity(paths: &CortexPaths) -> Result<Vec<PathBuf>> {
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ");
+    let mut backups = Vec::new();
+    for path in [paths.key(), paths.encrypted_key(), paths.info()] {
+        if !path.exists() {
+            continue;
+        }
+        // Ne jamais écraser une sauvegarde existante (rotations rapprochées)
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let mut backup = path.with_file_name(format!("{}.{}.bak", file_name, stamp));
+        let mut n = 1;
+        while backup.exists() {
+            backup = path.with_file_name(format!("{}.{}-{}.bak", file_name, stamp, n));
+            n += 1;
+        }
+        keystore::write_private_file(&backup, &fs::read(&path)?)?;
+        backups.push(backup);
+    }
+    Ok(backups)
+}
+
+/// Génère ou charge un ed25519::Keypair brut.
+/// Le keystore chiffré est prioritaire sur la clé en clair s'ils existent tous les deux.
+pub fn load_or_generate_identity(paths: &CortexPaths) -> Result<ed25519::Keypair> {
+    if identity_exists(paths) {
         println!("Chargement de l'identité existante...");
-        let content = fs::read_to_string(&path)?;
-        let bytes = STANDARD.decode(content.trim())?;
-        return ed25519::Keypair::try_from_bytes(&mut bytes.clone())
-            .map_err(|e| anyhow!("Failed to decode keypair: {:?}", e));
+        return load_keypair(paths);
     }
 
     println!("Génération d'une nouvelle identité...");
     let ed25519_keypair = ed25519::Keypair::generate();
-    let encoded = STANDARD.encode(ed25519_keypair.to_bytes());
-    
-    // Créer le répertoire parent s'il n'existe pas
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-    
-    fs::write(&path, encoded)?;
+    let passphrase = if keystore::wants_encryption() {
+        let passphrase = keystore::resolve_passphrase(PassphrasePurpose::New)?
+            .ok_or_else(|| anyhow!("Encryption requested but no passphrase available"))?;
+        Some(passphrase)
+    } else {
+        None
+    };
+    let path = save_keypair(paths, &ed25519_keypair, passphrase.as_deref())?;
     println!("Nouvelle identité sauvegardée dans {:?}", path);
 
     Ok(ed25519_keypair)
 }
 
+/// Charge la clé existante (chiffrée ou en clair)
+pub fn load_keypair(paths: &CortexPaths) -> Result<ed25519::Keypair> {
+    let encrypted_path = paths.encrypted_key();
+    if encrypted_path.exists() {
+        println!("Chemin de la clé: {:?} (chiffrée)", encrypted_path);
+        let file = keystore::read_encrypted(&encrypted_path)?;
+        let passphrase = keystore::resolve_passphrase(PassphrasePurpose::Unlock)?
+            .ok_or_else(|| anyhow!(
+                "Identity is encrypted: set {} or {}, or run interactively",
+                keystore::ENV_PASSPHRASE,
+                keystore::ENV_PASSPHRASE_FILE
+            ))?;
+        return keystore::decrypt_keypair(&file, &passphrase);
+    }
+
+    let path = paths.key();
+    println!("Chemin de la clé: {:?}", path);
+    keystore::check_plaintext_permissions(&path)?;
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read key {:?}", path))?;
+    let mut bytes = STANDARD.decode(content.trim())?;
+    ed25519::Keypair::try_from_bytes(&mut bytes)
+        .map_err(|e| anyhow!("Failed to decode keypair: {:?}", e))
+}
+
+/// Sauvegarde la clé, chiffrée si une phrase de passe est fournie, en clair (0600) sinon.
+/// L'autre format est supprimé pour qu'une seule clé fasse foi. Renvoie le chemin écrit.
+pub fn save_keypair(paths: &CortexPaths, keypair: &ed25519::Keypair, passphrase: Option<&str>) -> Result<PathBuf> {
+    let (path, stale) = match passphrase {
+        Some(passphrase) => {
+            let path = paths.encrypted_key();
+            keystore::write_encrypted(&path, &keystore::encrypt_keypair(keypair, passphrase)?)?;
+            (path, paths.key())
+        }
+        None => {
+            let path = paths.key();
+            keystore::write_private_file(&path, STANDARD.encode(keypair.to_bytes()).as_bytes())?;
+            (path, paths.encrypted_key())
+        }
+    };
+    if stale.exists() {
+        fs::remove_file(&stale).with_context(|| format!("Failed to remove {:?}", stale))?;
+    }
+    Ok(path)
+}
+
 /// Retourne un Keypair utilisable par libp2p
-pub fn load_identity_file() -> Result<Keypair> {
-    let path = get_key_path();
-    let content = fs::read_to_string(path)?;
-    let bytes = STANDARD.decode(content.trim())?;
-    let ed25519 = ed25519::Keypair::try_from_bytes(&mut bytes.clone())?;
-    Ok(Keypair::from(ed25519))
+pub fn load_identity_file(paths: &CortexPaths) -> Result<Keypair> {
+    Ok(Keypair::from(load_keypair(paths)?))
 }
 
 /// Génère ou charge l'identité, et renvoie l'info utilisateur-friendly
-pub fn generate_identity() -> Result<IdentityInfo> {
-    let ed25519_keypair = load_or_generate_identity()?;
-    let public_key_bytes = ed25519_keypair.public().to_bytes();
-    let public_key = STANDARD.encode(public_key_bytes);
-
-    let keypair = Keypair::from(ed25519_keypair);
-    let peer_id = PeerId::from(keypair.public());
-
-    Ok(IdentityInfo {
-        peer_id: peer_id.to_string(),
-        public_key,
-    })
+pub fn generate_identity(paths: &CortexPaths) -> Result<IdentityInfo> {
+    let ed25519_keypair = load_or_generate_identity(paths)?;
+    Ok(IdentityInfo::from_keypair(&ed25519_keypair))
 }
 
 /// Sauvegarde une version lisible de l'identité
-pub fn save_identity_file(identity: &IdentityInfo) -> Result<()> {
-    let path = get_info_path();
+pub fn save_identity_file(paths: &CortexPaths, identity: &IdentityInfo) -> Result<()> {
+    let path = paths.info();
     let json = serde_json::to_string_pretty(identity)
         .context("Failed to serialize identity info")?;
     
     // Créer le répertoire parent s'il n'existe pas
     if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
+        keystore::create_private_dir(parent)?;
     }
     
     fs::write(&path, json).context("Failed to write identity file")?;
@@ -0,0 +1,159 @@
+// src/identity/paths.rs
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+
+/// Répertoire racine Cortex (remplace `$HOME/.cortex`)
+pub const ENV_HOME: &str = "CORTEX_HOME";
+/// Profil de nœud actif (`<home>/profiles/<nom>/`)
+pub const ENV_PROFILE: &str = "CORTEX_PROFILE";
+
+/// Emplacement de tous les fichiers d'un nœud: identité, configuration, registre, cache de shards.
+///
+/// Sans profil, les fichiers sont à la racine (`~/.cortex/identity.key`, comme avant);
+/// avec un profil, dans `~/.cortex/profiles/<nom>/`, ce qui permet plusieurs nœuds par machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CortexPaths {
+    home: PathBuf,
+    profile: Option<String>,
+}
+
+impl CortexPaths {
+    pub fn new(home: impl Into<PathBuf>, profile: Option<String>) -> Result<Self> {
+        if let Some(name) = &profile {
+            validate_profile_name(name)?;
+        }
+        Ok(CortexPaths {
+            home: home.into(),
+            profile,
+        })
+    }
+
+    /// Chemins courants: `CORTEX_HOME` et `CORTEX_PROFILE`, sinon `$HOME/.cortex` sans profil
+    pub fn from_env() -> Result<Self> {
+        Self::resolve(None, None)
+    }
+
+    /// Chemins explicites (ex: `--home`, `--profile`), complétés par l'environnement
+    pub fn resolve(home: Option<PathBuf>, profile: Option<String>) -> Result<Self> {
+        let home = home
+            .or_else(|| std::env::var_os(ENV_HOME).filter(|h| !h.is_empty()).map(PathBuf::from))
+            .unwrap_or_else(Self::default_home);
+        let profile = profile.or_else(|| std::env::var(ENV_PROFILE).ok().filter(|p| !p.is_empty()));
+        Self::new(home, profile)
+    }
+
+    /// Répertoire racine par défaut, en fonction de l'environnement
+    pub fn default_home() -> PathBuf {
+        if let Ok(home) = std::env::var("HOME") {
+            PathBuf::from(home).join(".cortex")
+        } else if let Some(home) = dirs::home_dir() {
+            home.join(".cortex")
+        } else {
+            // Fallback pour Docker
+            PathBuf::from("/home/cortexuser/.cortex")
+        }
+    }
+
+    /// Même racine, autre profil
+    pub fn with_profile(&self, profile: Option<String>) -> Result<Self> {
+        Self::new(self.home.clone(), profile)
+    }
+
+    pub fn home(&self) -> &Path {
+        &self.home
+    }
+
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Répertoire du profil actif (la racine sans profil)
+    pub fn dir(&self) -> PathBuf {
+        match &self.profile {
+            Some(name) => self.home.join("profiles").join(name),
+            None => self.home.clone(),
+        }
+    }
+
+    pub fn key(&self) -> PathBuf {
+        self.dir().join("identity.key")
+    }
+
+    pub fn encrypted_key(&self) -> PathBuf {
+        self.dir().join("identity.key.enc")
+    }
+
+    pub fn info(&self) -> PathBuf {
+        self.dir().join("identity.json")
+    }
+
+    pub fn config(&self) -> PathBuf {
+        self.dir().join("config.yaml")
+    }
+
+    pub fn registry(&self) -> PathBuf {
+        self.dir().join("registry.json")
+    }
+
+    /// Listes d'autorisation et de blocage modifiées à chaud (API, CLI)
+    pub fn access(&self) -> PathBuf {
+        self.dir().join("access.json")
+    }
+
+    /// Preuve de travail d'admission, calculée une fois par identité et par mesh
+    pub fn admission(&self) -> PathBuf {
+        self.dir().join("admission.json")
+    }
+
+    /// Certificat de nœud signé par l'organisation
+    pub fn certificate(&self) -> PathBuf {
+        self.dir().join("node-cert.json")
+    }
+
+    /// Certificats de succession émis lors des rotations de clé
+    pub fn successions(&self) -> PathBuf {
+        self.dir().join("successions.json")
+    }
+
+    /// Réputation observée des pairs, conservée entre deux démarrages
+    pub fn reputation(&self) -> PathBuf {
+        self.dir().join("reputation.json")
+    }
+
+    /// Journal d'audit chaîné et signé
+    pub fn audit_dir(&self) -> PathBuf {
+        self.dir().join("audit")
+    }
+
+    pub fn shard_cache(&self) -> PathBuf {
+        self.dir().join("shards")
+    }
+
+    /// Profils existants sous `<home>/profiles/`
+    pub fn list_profiles(&self) -> Result<Vec<String>> {
+        let dir = self.home.join("profiles");
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut profiles: Vec<String> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+        profiles.sort();
+        Ok(profiles)
+    }
+}
+
+/// Un nom de profil devient un nom de répertoire: pas de séparateur ni de `..`
+fn validate_profile_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        bail!("Invalid profile name: {:?} (letters, digits, '-', '_' and '.' only)", name);
+    }
+    Ok(())
+}
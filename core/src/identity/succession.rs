@@ -0,0 +1,113 @@
+// src/identity/succession.rs
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use libp2p::identity::{ed25519, PeerId, PublicKey};
+use serde::{Deserialize, Serialize};
+
+use crate::signing::{unix_now, verify_signed_by};
+
+const SIGNING_DOMAIN: &str = "cortex-key-succession/v1";
+
+/// Certificat de succession de clé: l'ancienne identité désigne la nouvelle.
+///
+/// Signé par l'ancienne clé (autorité sur l'historique) et par la nouvelle
+/// (preuve de possession, pour qu'on ne puisse pas désigner le PeerId d'un tiers).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeySuccession {
+    pub old_peer_id: String,
+    /// Clé publique ed25519 de l'ancienne identité (base64, 32 octets)
+    pub old_public_key: String,
+    pub new_peer_id: String,
+    pub new_public_key: String,
+    /// Date d'émission (secondes UNIX)
+    pub issued_at: u64,
+    /// Motif libre, ex. "rotation" ou "compromission"
+    #[serde(default)]
+    pub reason: Option<String>,
+    pub old_signature: String,
+    pub new_signature: String,
+}
+
+impl KeySuccession {
+    /// Émet le certificat de passage de `old` à `new`
+    pub fn sign(old: &ed25519::Keypair, new: &ed25519::Keypair, reason: Option<String>) -> Self {
+        let mut succession = KeySuccession {
+            old_peer_id: peer_id_of(&PublicKey::from(old.public())).to_string(),
+            old_public_key: STANDARD.encode(old.public().to_bytes()),
+            new_peer_id: peer_id_of(&PublicKey::from(new.public())).to_string(),
+            new_public_key: STANDARD.encode(new.public().to_bytes()),
+            issued_at: unix_now(),
+            reason,
+            old_signature: String::new(),
+            new_signature: String::new(),
+        };
+        let payload = succession.signing_bytes();
+        succession.old_signature = STANDARD.encode(old.sign(&payload));
+        succession.new_signature = STANDARD.encode(new.sign(&payload));
+        succession
+    }
+
+    /// Vérifie les deux signatures et la correspondance clés publiques / PeerId
+    pub fn verify(&self) -> Result<()> {
+        if self.old_peer_id == self.new_peer_id {
+            bail!("Succession to the same identity");
+        }
+        let payload = self.signing_bytes();
+        verify_signed_by(&self.old_peer_id, &self.old_public_key, &payload, &self.old_signature)
+            .context("Old key")?;
+        verify_signed_by(&self.new_peer_id, &self.new_public_key, &payload, &self.new_signature)
+            .context("New key")?;
+        Ok(())
+    }
+
+    pub fn old_peer(&self) -> Result<PeerId> {
+        self.old_peer_id.parse().map_err(|e| anyhow!("Invalid PeerId: {:?}", e))
+    }
+
+    pub fn new_peer(&self) -> Result<PeerId> {
+        self.new_peer_id.parse().map_err(|e| anyhow!("Invalid PeerId: {:?}", e))
+    }
+
+    /// Âge du certificat en secondes
+    pub fn age_secs(&self) -> u64 {
+        unix_now().saturating_sub(self.issued_at)
+    }
+
+    fn signing_bytes(&self) -> Vec<u8> {
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            SIGNING_DOMAIN,
+            self.old_peer_id,
+            self.old_public_key,
+            self.new_peer_id,
+            self.new_public_key,
+            self.issued_at,
+            self.reason.as_deref().unwrap_or("")
+        )
+        .into_bytes()
+    }
+}
+
+/// Charge les certificats émis par ce nœud (fichier absent: aucun)
+pub fn load_successions(path: &Path) -> Result<Vec<KeySuccession>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    serde_json::from_str(&content).context("Failed to parse successions file")
+}
+
+/// Ajoute un certificat au fichier des successions
+pub fn append_succession(path: &Path, succession: &KeySuccession) -> Result<()> {
+    let mut successions = load_successions(path)?;
+    successions.push(succession.clone());
+    let json = serde_json::to_string_pretty(&successions)?;
+    super::keystore::write_private_file(path, json.as_bytes())
+}
+
+fn peer_id_of(public: &PublicKey) -> PeerId {
+    PeerId::from(public.clone())
+}
@@ -2,4 +2,28 @@ pub mod discovery;
 pub mod identity;
 pub mod registry;
 pub mod communicator;
-pub mod api_interface;
\ No newline at end of file
+pub mod api_interface;
+pub mod config;
+pub mod topics;
+pub mod shutdown;
+pub mod metrics;
+#[cfg(any(test, feature = "harness"))]
+pub mod harness;
+#[cfg(any(test, feature = "harness"))]
+pub mod simulation;
+pub mod trust;
+pub mod access;
+pub mod admission;
+pub mod envelope;
+pub mod policy;
+pub mod audit;
+pub mod executor;
+pub mod weights;
+pub mod rpc;
+pub mod pipeline;
+pub mod moe;
+pub mod frame;
+pub mod scheduler;
+pub mod quorum;
+pub mod reputation;
+pub mod signing;
@@ -1,8 +1,24 @@
+use cortex_id::access::{AccessLists, AccessUpdate};
+use cortex_id::audit::{self, AUDIT_FILE};
+use cortex_id::config::CortexConfig;
 use cortex_id::discovery::{run_bootstrap_node, run_light_node};
-use cortex_id::identity::load_or_generate_identity;
-use clap::Parser;
+use cortex_id::identity::format::{decode_keypair, encode_keypair, KeyFormat};
+use cortex_id::identity::keystore::{self, PassphrasePurpose};
+use cortex_id::identity::succession::{append_succession, load_successions, KeySuccession};
+use cortex_id::trust::{encode_public_key, load_json, save_json, NodeCertificate, RevocationList};
+use libp2p::PeerId;
+use std::time::Duration;
+use cortex_id::identity::{
+    backup_identity, identity_exists, identity_is_encrypted, load_keypair, load_or_generate_identity, save_identity_file,
+    save_keypair, CortexPaths, IdentityInfo,
+};
+use clap::{Parser, Subcommand};
+use libp2p::identity::ed25519;
 use std::env;
-use anyhow::Result;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use anyhow::{anyhow, bail, Context, Result};
 
 /// Cortex Node CLI
 #[derive(Parser, Debug)]
@@ -16,6 +32,169 @@ struct Cli {
     /// Adresse bootstrap à utiliser (format multiaddr)
     #[arg(long)]
     bootstrap_peer: Option<String>,
+
+    /// Répertoire racine Cortex (défaut: ~/.cortex, ou CORTEX_HOME)
+    #[arg(long, global = true)]
+    home: Option<PathBuf>,
+
+    /// Profil de nœud, stocké dans <home>/profiles/<nom> (ou CORTEX_PROFILE)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Gestion de l'identité du nœud, sans lancer le nœud
+    Identity {
+        #[command(subcommand)]
+        action: IdentityAction,
+    },
+    /// Liste les profils existants
+    Profiles,
+    /// Outils d'organisation: clé racine, certificats de nœud, révocations
+    Trust {
+        #[command(subcommand)]
+        action: TrustAction,
+    },
+    /// Listes d'autorisation et de blocage (rechargées à chaud par le nœud en cours)
+    Access {
+        #[command(subcommand)]
+        action: AccessAction,
+    },
+    /// Journal d'audit des sorties de données et des exécutions
+    Audit {
+        #[command(subcommand)]
+        action: AuditAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum AuditAction {
+    /// Vérifie le chaînage et les signatures de tout le journal
+    Verify {
+        /// PeerId propriétaire du journal (défaut: l'identité du profil)
+        #[arg(long)]
+        identity: Option<String>,
+    },
+    /// Exporte les entrées d'une période (JSON lines)
+    Export {
+        /// Début (secondes UNIX ou RFC 3339, ex: 2026-01-31T00:00:00Z)
+        #[arg(long)]
+        since: Option<String>,
+        /// Fin, incluse
+        #[arg(long)]
+        until: Option<String>,
+        /// Fichier de sortie (défaut: sortie standard)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum AccessAction {
+    /// Affiche les listes courantes
+    List,
+    /// Ajoute un pair à la liste d'autorisation
+    Allow { peer: String },
+    /// Retire un pair de la liste d'autorisation
+    Unallow { peer: String },
+    /// Bloque un pair
+    Deny { peer: String },
+    /// Débloque un pair
+    Undeny { peer: String },
+}
+
+#[derive(Subcommand, Debug)]
+enum TrustAction {
+    /// Génère une clé d'organisation et affiche sa clé publique (à mettre dans trust.roots)
+    Keygen {
+        #[arg(long, short)]
+        output: PathBuf,
+    },
+    /// Signe le certificat d'un nœud
+    Issue {
+        /// Clé de l'organisation (fichier produit par `trust keygen`)
+        #[arg(long)]
+        org_key: PathBuf,
+        #[arg(long)]
+        domain: String,
+        /// PeerId du nœud certifié
+        #[arg(long)]
+        peer: String,
+        /// Rôle autorisé (répétable), ex: shard_executor
+        #[arg(long = "role")]
+        roles: Vec<String>,
+        /// Durée de validité en jours
+        #[arg(long, default_value_t = 365)]
+        days: u64,
+        #[arg(long, short)]
+        output: PathBuf,
+    },
+    /// Produit une nouvelle liste de révocation (reprend la précédente si --base est donné)
+    Revoke {
+        #[arg(long)]
+        org_key: PathBuf,
+        #[arg(long)]
+        domain: String,
+        /// PeerId à révoquer (répétable)
+        #[arg(long = "peer")]
+        peers: Vec<String>,
+        /// Numéro de série de certificat à révoquer (répétable)
+        #[arg(long = "serial")]
+        serials: Vec<u64>,
+        /// Liste précédente à compléter
+        #[arg(long)]
+        base: Option<PathBuf>,
+        #[arg(long, short)]
+        output: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum IdentityAction {
+    /// Affiche le PeerId et la clé publique, et met à jour identity.json
+    Show,
+    /// Crée l'identité si elle n'existe pas encore
+    Init {
+        /// Chiffre la clé avec une phrase de passe
+        #[arg(long)]
+        encrypt: bool,
+    },
+    /// Exporte la clé privée
+    Export {
+        /// raw, base64, pem ou protobuf
+        #[arg(long, default_value = "pem")]
+        format: KeyFormat,
+        /// Fichier de sortie (obligatoire pour les formats binaires)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Importe une clé ed25519 existante (raw, base64, PEM ou protobuf libp2p)
+    Import {
+        /// Fichier contenant la clé
+        path: PathBuf,
+        /// Format de la clé (deviné si absent)
+        #[arg(long)]
+        format: Option<KeyFormat>,
+        /// Chiffre la clé importée avec une phrase de passe
+        #[arg(long)]
+        encrypt: bool,
+        /// Remplace l'identité existante (une sauvegarde est conservée)
+        #[arg(long)]
+        force: bool,
+    },
+    /// Génère une nouvelle identité en sauvegardant l'ancienne
+    Rotate {
+        /// Chiffre la nouvelle clé (par défaut: même format que l'ancienne)
+        #[arg(long)]
+        encrypt: bool,
+        /// Motif inscrit dans le certificat de succession (ex: compromission)
+        #[arg(long, default_value = "rotation")]
+        reason: String,
+    },
 }
 
 #[tokio::main]
@@ -27,6 +206,19 @@ async fn main() -> Result<()> {
         .init();
     
     let cli = Cli::parse();
+
+    // Les options de la ligne de commande priment sur CORTEX_HOME et CORTEX_PROFILE
+    let paths = CortexPaths::resolve(cli.home.clone(), cli.profile.clone())?;
+
+    match cli.command {
+        Some(Command::Identity { action }) => return run_identity_command(action, &paths),
+        Some(Command::Profiles) => return list_profiles(&paths),
+        Some(Command::Trust { action }) => return run_trust_command(action),
+        Some(Command::Access { action }) => return run_access_command(action, &paths),
+        Some(Command::Audit { action }) => return run_audit_command(action, &paths),
+        None => {}
+    }
+    println!("📁 Répertoire du nœud: {:?}", paths.dir());
     
     // Priorité: argument CLI, puis variable d'environnement, puis défaut
     let mode = cli.mode.clone();
@@ -38,17 +230,267 @@ async fn main() -> Result<()> {
     
     // Chargement ou génération de l'identité
     println!("🔑 Chargement/génération de l'identité...");
-    let keypair = load_or_generate_identity()?.into();
+    let keypair = load_or_generate_identity(&paths)?.into();
+    let config = CortexConfig::load(&paths)?;
     
     println!("🚀 Démarrage du nœud en mode: {}", mode);
     
     // Choix du mode de fonctionnement
     match mode.as_str() {
-        "bootstrap" => run_bootstrap_node(keypair).await,
-        "light" => run_light_node(keypair).await,
+        "bootstrap" => run_bootstrap_node(keypair, &config, &paths).await,
+        "light" => run_light_node(keypair, &config, &paths).await,
         _ => {
             println!("Mode inconnu: {}, utilisation du mode light par défaut", mode);
-            run_light_node(keypair).await
+            run_light_node(keypair, &config, &paths).await
+        }
+    }
+}
+
+/// Exécute une sous-commande `identity`
+fn run_identity_command(action: IdentityAction, paths: &CortexPaths) -> Result<()> {
+    match action {
+        IdentityAction::Show => {
+            if !identity_exists(paths) {
+                bail!("No identity found in {:?}, run `cortex-id identity init`", paths.key());
+            }
+            let keypair = load_keypair(paths)?;
+            print_identity(paths, &keypair)
+        }
+        IdentityAction::Init { encrypt } => {
+            if identity_exists(paths) {
+                println!("ℹ️ Identité déjà présente, rien à générer");
+                return print_identity(paths, &load_keypair(paths)?);
+            }
+            let keypair = ed25519::Keypair::generate();
+            store_keypair(paths, &keypair, encrypt)?;
+            print_identity(paths, &keypair)
+        }
+        IdentityAction::Export { format, output } => {
+            let keypair = load_keypair(paths)?;
+            let data = encode_keypair(&keypair, format)?;
+            match output {
+                Some(path) => {
+                    keystore::write_private_file(&path, &data)?;
+                    println!("📤 Clé exportée dans {:?}", path);
+                }
+                None if format.is_text() => std::io::stdout().write_all(&data)?,
+                None => bail!("Binary format {:?} requires --output", format),
+            }
+            Ok(())
+        }
+        IdentityAction::Import { path, format, encrypt, force } => {
+            let data = fs::read(&path).with_context(|| format!("Failed to read {:?}", path))?;
+            let keypair = decode_keypair(&data, format)?;
+            if identity_exists(paths) {
+                if !force {
+                    bail!("An identity already exists, use --force to replace it");
+                }
+                for backup in backup_identity(paths)? {
+                    println!("💾 Ancienne identité sauvegardée dans {:?}", backup);
+                }
+            }
+            store_keypair(paths, &keypair, encrypt)?;
+            println!("📥 Identité importée depuis {:?}", path);
+            print_identity(paths, &keypair)
+        }
+        IdentityAction::Rotate { encrypt, reason } => {
+            if !identity_exists(paths) {
+                bail!("No identity to rotate, run `cortex-id identity init`");
+            }
+            let was_encrypted = identity_is_encrypted(paths);
+            let old_keypair = load_keypair(paths)?;
+            for backup in backup_identity(paths)? {
+                println!("💾 Ancienne identité sauvegardée dans {:?}", backup);
+            }
+            let keypair = ed25519::Keypair::generate();
+
+            // L'ancienne clé désigne la nouvelle: les pairs migreront l'historique du nœud
+            let succession = KeySuccession::sign(&old_keypair, &keypair, Some(reason));
+            store_keypair(paths, &keypair, encrypt || was_encrypted)?;
+            append_succession(&paths.successions(), &succession)?;
+            println!("🔄 Rotation: {} -> {}", succession.old_peer_id, succession.new_peer_id);
+            println!("📜 Certificat de succession ajouté à {:?} (diffusé au prochain démarrage)", paths.successions());
+            print_identity(paths, &keypair)
+        }
+    }
+}
+
+/// Enregistre la clé (chiffrée si demandé) et met à jour identity.json
+fn store_keypair(paths: &CortexPaths, keypair: &ed25519::Keypair, encrypt: bool) -> Result<()> {
+    let passphrase = if encrypt || keystore::wants_encryption() {
+        let passphrase = keystore::resolve_passphrase(PassphrasePurpose::New)?
+            .ok_or_else(|| anyhow!(
+                "Encryption requested but no passphrase available: set {} or {}",
+                keystore::ENV_PASSPHRASE,
+                keystore::ENV_PASSPHRASE_FILE
+            ))?;
+        Some(passphrase)
+    } else {
+        None
+    };
+    let path = save_keypair(paths, keypair, passphrase.as_deref())?;
+    println!("🔑 Identité sauvegardée dans {:?}", path);
+    Ok(())
+}
+
+/// Affiche l'identité et met à jour identity.json
+fn print_identity(paths: &CortexPaths, keypair: &ed25519::Keypair) -> Result<()> {
+    let info = IdentityInfo::from_keypair(keypair);
+    save_identity_file(paths, &info)?;
+    let storage = if identity_is_encrypted(paths) {
+        format!("{:?} (chiffrée)", paths.encrypted_key())
+    } else {
+        format!("{:?}", paths.key())
+    };
+    println!("Profil:       {}", paths.profile().unwrap_or("(défaut)"));
+    println!("PeerId:       {}", info.peer_id);
+    println!("Clé publique: {}", info.public_key);
+    println!("Stockage:     {}", storage);
+    println!("Info:         {:?}", paths.info());
+    Ok(())
+}
+
+fn list_profiles(paths: &CortexPaths) -> Result<()> {
+    let profiles = paths.list_profiles()?;
+    if profiles.is_empty() {
+        println!("Aucun profil dans {:?}", paths.home().join("profiles"));
+    }
+    for profile in profiles {
+        let marker = if paths.profile() == Some(profile.as_str()) { "*" } else { " " };
+        println!("{} {}", marker, profile);
+    }
+    Ok(())
+}
+
+/// Exécute une sous-commande `access` sur `access.json` (créé depuis la configuration au besoin)
+fn run_access_command(action: AccessAction, paths: &CortexPaths) -> Result<()> {
+    let path = paths.access();
+    let mut lists = match AccessLists::load(&path)? {
+        Some(lists) => lists,
+        None => CortexConfig::load(paths)?.access,
+    };
+    let update = match action {
+        AccessAction::List => {
+            println!("Mode liste d'autorisation: {}", if lists.allowlist_only { "oui" } else { "non" });
+            println!("Autorisés ({}):", lists.allow.len());
+            for peer in &lists.allow {
+                println!("  {}", peer);
+            }
+            println!("Bloqués ({}):", lists.deny.len());
+            for peer in &lists.deny {
+                println!("  {}", peer);
+            }
+            return Ok(());
         }
+        AccessAction::Allow { peer } => AccessUpdate::Allow(peer),
+        AccessAction::Unallow { peer } => AccessUpdate::Unallow(peer),
+        AccessAction::Deny { peer } => AccessUpdate::Deny(peer),
+        AccessAction::Undeny { peer } => AccessUpdate::Undeny(peer),
+    };
+    if lists.apply(&update)? {
+        lists.save(&path)?;
+        println!("✅ Listes d'accès mises à jour: {:?}", path);
+    } else {
+        println!("ℹ️ Aucun changement");
     }
-}
\ No newline at end of file
+    Ok(())
+}
+
+/// Exécute une sous-commande `audit` sur le journal du profil
+fn run_audit_command(action: AuditAction, paths: &CortexPaths) -> Result<()> {
+    let path = paths.audit_dir().join(AUDIT_FILE);
+    match action {
+        AuditAction::Verify { identity } => {
+            let identity = match identity {
+                Some(identity) => identity,
+                None => IdentityInfo::from_keypair(&load_keypair(paths)?).peer_id,
+            };
+            let successions = load_successions(&paths.successions())?;
+            let report = audit::verify_chain(&path, &identity, &successions).context("Audit log verification failed")?;
+            println!("✅ Journal intègre: {} entrées ({:?})", report.entries, path);
+            println!("Dernier hachage: {}", report.head_hash);
+            for signer in report.signers {
+                println!("Signataire: {}", signer);
+            }
+        }
+        AuditAction::Export { since, until, output } => {
+            let since = since.as_deref().map(parse_time).transpose()?;
+            let until = until.as_deref().map(parse_time).transpose()?;
+            let mut lines = String::new();
+            for entry in audit::export_range(&path, since, until)? {
+                lines.push_str(&serde_json::to_string(&entry)?);
+                lines.push('\n');
+            }
+            match output {
+                Some(output) => {
+                    keystore::write_private_file(&output, lines.as_bytes())?;
+                    println!("✅ Entrées exportées vers {:?}", output);
+                }
+                None => print!("{}", lines),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Date en secondes UNIX ou au format RFC 3339
+fn parse_time(value: &str) -> Result<u64> {
+    if let Ok(secs) = value.parse::<u64>() {
+        return Ok(secs);
+    }
+    let date = chrono::DateTime::parse_from_rfc3339(value)
+        .with_context(|| format!("Invalid date {:?} (UNIX seconds or RFC 3339)", value))?;
+    u64::try_from(date.timestamp()).map_err(|_| anyhow!("Date before 1970: {}", value))
+}
+
+/// Exécute une sous-commande `trust` (outillage de l'organisation, hors nœud)
+fn run_trust_command(action: TrustAction) -> Result<()> {
+    match action {
+        TrustAction::Keygen { output } => {
+            if output.exists() {
+                bail!("{:?} already exists", output);
+            }
+            let org = ed25519::Keypair::generate();
+            keystore::write_private_file(&output, &encode_keypair(&org, KeyFormat::Base64)?)?;
+            println!("🏛️ Clé d'organisation écrite dans {:?}", output);
+            println!("Clé publique: {}", encode_public_key(&org.public()));
+            Ok(())
+        }
+        TrustAction::Issue { org_key, domain, peer, roles, days, output } => {
+            let org = load_org_key(&org_key)?;
+            let peer_id: PeerId = peer.parse().map_err(|e| anyhow!("Invalid PeerId {}: {:?}", peer, e))?;
+            let cert = NodeCertificate::issue(&org, &domain, &peer_id, roles, Duration::from_secs(days * 24 * 3600));
+            save_json(&output, &cert)?;
+            println!("📜 Certificat {} (série {}) pour {} écrit dans {:?}", domain, cert.serial, peer_id, output);
+            Ok(())
+        }
+        TrustAction::Revoke { org_key, domain, mut peers, mut serials, base, output } => {
+            let org = load_org_key(&org_key)?;
+            if let Some(base) = base {
+                let previous: RevocationList = load_json(&base)?;
+                if previous.trust_domain != domain {
+                    bail!("Base list belongs to domain {}", previous.trust_domain);
+                }
+                peers.extend(previous.revoked_peers);
+                serials.extend(previous.revoked_serials);
+            }
+            peers.sort();
+            peers.dedup();
+            serials.sort_unstable();
+            serials.dedup();
+            let list = RevocationList::sign(&org, &domain, peers, serials);
+            save_json(&output, &list)?;
+            println!(
+                "⛔ Liste de révocation {} écrite dans {:?} ({} pairs, {} séries)",
+                domain, output, list.revoked_peers.len(), list.revoked_serials.len()
+            );
+            Ok(())
+        }
+    }
+}
+
+fn load_org_key(path: &PathBuf) -> Result<ed25519::Keypair> {
+    keystore::check_plaintext_permissions(path)?;
+    let data = fs::read(path).with_context(|| format!("Failed to read {:?}", path))?;
+    decode_keypair(&data, None)
+}
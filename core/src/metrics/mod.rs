@@ -0,0 +1,121 @@
+// src/metrics/mod.rs
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use libp2p::allow_block_list::{Blocked, NotAllowed};
+use libp2p::connection_limits::Exceeded;
+use libp2p::memory_connection_limits::MemoryUsageLimitExceeded;
+use libp2p::swarm::ConnectionDenied;
+use serde::Serialize;
+
+/// Raison d'un refus de connexion par les limites du swarm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DenialReason {
+    /// Limite de connexions (entrantes, sortantes, par pair...) atteinte
+    ConnectionLimit,
+    /// Mémoire du processus au-dessus du seuil configuré
+    MemoryLimit,
+    /// Pair bloqué ou absent de la liste d'autorisation
+    AccessList,
+    /// Autre comportement ayant refusé la connexion
+    Other,
+}
+
+impl DenialReason {
+    pub fn from_cause(cause: &ConnectionDenied) -> Self {
+        if cause.downcast_ref::<Exceeded>().is_some() {
+            DenialReason::ConnectionLimit
+        } else if cause.downcast_ref::<MemoryUsageLimitExceeded>().is_some() {
+            DenialReason::MemoryLimit
+        } else if cause.downcast_ref::<Blocked>().is_some() || cause.downcast_ref::<NotAllowed>().is_some() {
+            DenialReason::AccessList
+        } else {
+            DenialReason::Other
+        }
+    }
+}
+
+/// Compteurs du nœud, partagés entre la boucle du swarm et l'API
+#[derive(Debug, Default)]
+pub struct NodeMetrics {
+    pub connections_established: AtomicU64,
+    pub connections_closed: AtomicU64,
+    pub inbound_denied_connection_limit: AtomicU64,
+    pub inbound_denied_memory_limit: AtomicU64,
+    pub inbound_denied_access_list: AtomicU64,
+    pub inbound_denied_other: AtomicU64,
+    pub outbound_denied_connection_limit: AtomicU64,
+    pub outbound_denied_memory_limit: AtomicU64,
+    pub outbound_denied_access_list: AtomicU64,
+    pub outbound_denied_other: AtomicU64,
+}
+
+/// Vue figée des compteurs, sérialisable en JSON
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsSnapshot {
+    pub connections_established: u64,
+    pub connections_closed: u64,
+    pub connections_open: u64,
+    pub inbound_denied_connection_limit: u64,
+    pub inbound_denied_memory_limit: u64,
+    pub inbound_denied_access_list: u64,
+    pub inbound_denied_other: u64,
+    pub outbound_denied_connection_limit: u64,
+    pub outbound_denied_memory_limit: u64,
+    pub outbound_denied_access_list: u64,
+    pub outbound_denied_other: u64,
+}
+
+impl NodeMetrics {
+    pub fn record_established(&self) {
+        self.connections_established.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_closed(&self) {
+        self.connections_closed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Comptabilise une connexion entrante refusée
+    pub fn record_inbound_denied(&self, reason: DenialReason) {
+        let counter = match reason {
+            DenialReason::ConnectionLimit => &self.inbound_denied_connection_limit,
+            DenialReason::MemoryLimit => &self.inbound_denied_memory_limit,
+            DenialReason::AccessList => &self.inbound_denied_access_list,
+            DenialReason::Other => &self.inbound_denied_other,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Comptabilise une connexion sortante refusée
+    pub fn record_outbound_denied(&self, reason: DenialReason) {
+        let counter = match reason {
+            DenialReason::ConnectionLimit => &self.outbound_denied_connection_limit,
+            DenialReason::MemoryLimit => &self.outbound_denied_memory_limit,
+            DenialReason::AccessList => &self.outbound_denied_access_list,
+            DenialReason::Other => &self.outbound_denied_other,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let established = self.connections_established.load(Ordering::Relaxed);
+        let closed = self.connections_closed.load(Ordering::Relaxed);
+        MetricsSnapshot {
+            connections_established: established,
+            connections_closed: closed,
+            connections_open: established.saturating_sub(closed),
+            inbound_denied_connection_limit: self.inbound_denied_connection_limit.load(Ordering::Relaxed),
+            inbound_denied_memory_limit: self.inbound_denied_memory_limit.load(Ordering::Relaxed),
+            inbound_denied_access_list: self.inbound_denied_access_list.load(Ordering::Relaxed),
+            inbound_denied_other: self.inbound_denied_other.load(Ordering::Relaxed),
+            outbound_denied_connection_limit: self.outbound_denied_connection_limit.load(Ordering::Relaxed),
+            outbound_denied_memory_limit: self.outbound_denied_memory_limit.load(Ordering::Relaxed),
+            outbound_denied_access_list: self.outbound_denied_access_list.load(Ordering::Relaxed),
+            outbound_denied_other: self.outbound_denied_other.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Export JSON lisible, au même format que le snapshot du registre
+    pub fn snapshot_json(&self) -> String {
+        serde_json::to_string_pretty(&self.snapshot()).unwrap_or_else(|_| "{}".into())
+    }
+}
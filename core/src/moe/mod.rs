@@ -0,0 +1,330 @@
+// src/moe/mod.rs
+//! Couche Mixture-of-Experts répartie: le routeur tourne sur le nœud d'origine, choisit les
+//! top-k experts de chaque token, regroupe les tokens par expert, les envoie aux nœuds qui
+//! hébergent ces experts, puis combine les sorties pondérées.
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::{anyhow, bail, Result};
+use futures::future::join_all;
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+use tokio::time::{timeout, Duration};
+
+use crate::discovery::NodeHandle;
+use crate::executor::Tensor;
+use crate::pipeline::PipelinePlan;
+use crate::policy::DataClass;
+use crate::rpc::{RpcRequest, RpcResponse};
+
+pub const DEFAULT_TOP_K: usize = 2;
+pub const DEFAULT_EXPERT_TIMEOUT_MS: u64 = 10_000;
+
+/// Expert d'un shard nommé `<modèle>/layer-<couche>/expert-<n>`
+pub fn parse_expert(shard_id: &str) -> Option<(&str, usize, usize)> {
+    let (rest, expert) = shard_id.rsplit_once('/')?;
+    let (model, layer) = rest.rsplit_once('/')?;
+    let layer = layer.strip_prefix("layer-")?.parse().ok()?;
+    let expert = expert.strip_prefix("expert-")?.parse().ok()?;
+    Some((model, layer, expert))
+}
+
+pub fn expert_shard_id(model: &str, layer: usize, expert: usize) -> String {
+    format!("{}/layer-{}/expert-{}", model, layer, expert)
+}
+
+/// Que faire d'un expert absent du registre, ou dont tous les hôtes sont en erreur ou trop lents ?
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackPolicy {
+    /// Le remplacer par l'expert suivant dans le classement du routeur
+    #[default]
+    NextBest,
+    /// L'ignorer: le token est combiné avec les experts restants
+    Drop,
+    /// Échouer toute la couche
+    Fail,
+}
+
+/// Section `moe:` de la configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MoeConfig {
+    /// Experts activés par token
+    pub top_k: usize,
+    pub fallback: FallbackPolicy,
+    /// Délai au-delà duquel un expert distant est considéré comme en échec
+    pub expert_timeout_ms: u64,
+}
+
+impl Default for MoeConfig {
+    fn default() -> Self {
+        MoeConfig {
+            top_k: DEFAULT_TOP_K,
+            fallback: FallbackPolicy::default(),
+            expert_timeout_ms: DEFAULT_EXPERT_TIMEOUT_MS,
+        }
+    }
+}
+
+impl MoeConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.top_k == 0 {
+            bail!("moe.top_k must be at least 1");
+        }
+        if self.expert_timeout_ms == 0 {
+            bail!("moe.expert_timeout_ms must be positive");
+        }
+        Ok(())
+    }
+}
+
+/// Routeur (gating) linéaire: poids `[num_experts, hidden]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Router {
+    gate: Tensor,
+}
+
+impl Router {
+    pub fn new(gate: Tensor) -> Result<Self> {
+        if gate.shape.len() != 2 || gate.shape.contains(&0) {
+            bail!("Router weights must be [num_experts, hidden], got {:?}", gate.shape);
+        }
+        Ok(Router { gate })
+    }
+
+    pub fn num_experts(&self) -> usize {
+        self.gate.shape[0]
+    }
+
+    pub fn hidden_size(&self) -> usize {
+        self.gate.shape[1]
+    }
+
+    /// Logits du routeur pour chaque token de `hidden` (`[seq, hidden]`)
+    pub fn logits(&self, hidden: &Tensor) -> Result<Vec<Vec<f32>>> {
+        let dim = self.hidden_size();
+        if hidden.shape.len() != 2 || hidden.shape[1] != dim {
+            bail!("Expected hidden states [seq, {}], got {:?}", dim, hidden.shape);
+        }
+        Ok(hidden.data.chunks(dim)
+            .map(|x| self.gate.data.chunks(dim).map(|w| w.iter().zip(x).map(|(a, b)| a * b).sum()).collect())
+            .collect())
+    }
+}
+
+/// Experts du meilleur au moins bon (à égalité, le plus petit indice d'abord)
+pub fn rank_experts(logits: &[f32]) -> Vec<usize> {
+    let mut experts: Vec<usize> = (0..logits.len()).collect();
+    experts.sort_by(|&a, &b| logits[b].total_cmp(&logits[a]).then(a.cmp(&b)));
+    experts
+}
+
+/// Poids de combinaison: softmax des logits des seuls experts retenus
+pub fn gate_weights(logits: &[f32], experts: &[usize]) -> Vec<f32> {
+    let max = experts.iter().map(|&e| logits[e]).fold(f32::NEG_INFINITY, f32::max);
+    let exps: Vec<f32> = experts.iter().map(|&e| (logits[e] - max).exp()).collect();
+    let sum: f32 = exps.iter().sum();
+    exps.iter().map(|v| v / sum).collect()
+}
+
+/// Tokens à faire traiter par un expert
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpertRequest {
+    pub shard_id: String,
+    /// États cachés `[tokens, hidden]` (en trame, hors du JSON)
+    #[serde(skip)]
+    pub input: Tensor,
+}
+
+/// Exécute un expert de ce nœud pour `from`, depuis un état vide: les lots sont indépendants
+/// et ne touchent pas à l'état de séquence du shard
+pub async fn serve_expert(handle: NodeHandle, from: PeerId, request: ExpertRequest) -> Result<Tensor> {
+    let ExpertRequest { shard_id, input } = request;
+    if parse_expert(&shard_id).is_none() || !handle.shards().is_loaded(&shard_id) {
+        bail!("Expert {} not loaded on {}", shard_id, handle.peer_id());
+    }
+    if from != handle.peer_id() {
+        if let Some(audit) = handle.audit() {
+            audit.record_execution(&from, &shard_id, &serde_json::to_vec(&input)?)?;
+        }
+    }
+    handle.execute_isolated(&shard_id, input).await
+}
+
+/// Couche MoE d'un modèle, pilotée depuis ce nœud
+pub struct MoeLayer {
+    handle: NodeHandle,
+    model: String,
+    layer: usize,
+    router: Router,
+    config: MoeConfig,
+}
+
+impl MoeLayer {
+    /// Couche utilisant la section `moe:` de la configuration du nœud
+    pub fn new(handle: NodeHandle, model: &str, layer: usize, router: Router) -> Self {
+        let config = handle.moe_config();
+        MoeLayer { handle, model: model.to_string(), layer, router, config }
+    }
+
+    pub fn with_config(mut self, config: MoeConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Hôtes de chaque expert par ordre de préférence: ce nœud s'il l'a chargé, puis les
+    /// PeerId du registre du plus petit au plus grand
+    pub fn hosts(&self) -> Result<BTreeMap<usize, Vec<PeerId>>> {
+        let mut candidates = {
+            let registry = self.handle.registry();
+            let registry = registry.lock().map_err(|_| anyhow!("Registry lock poisoned"))?;
+            PipelinePlan::candidates(&registry)
+        };
+        let local = self.handle.peer_id().to_string();
+        candidates.extend(self.handle.shards().loaded().into_iter().map(|s| (local.clone(), s.id)));
+        candidates.sort_by(|a, b| (a.0 != local).cmp(&(b.0 != local)).then_with(|| a.0.cmp(&b.0)));
+
+        let mut hosts: BTreeMap<usize, Vec<PeerId>> = BTreeMap::new();
+        for (peer, shard_id) in candidates {
+            let Some((model, layer, expert)) = parse_expert(&shard_id) else {
+                continue;
+            };
+            if model != self.model || layer != self.layer || expert >= self.router.num_experts() {
+                continue;
+            }
+            if let Ok(peer) = peer.parse::<PeerId>() {
+                let peers = hosts.entry(expert).or_default();
+                if !peers.contains(&peer) {
+                    peers.push(peer);
+                }
+            }
+        }
+        Ok(hosts)
+    }
+
+    /// Applique la couche à `hidden` (`[seq, hidden]`): routage, envoi par expert, combinaison
+    pub async fn forward(&self, hidden: &Tensor) -> Result<Tensor> {
+        let logits = self.router.logits(hidden)?;
+        let dim = self.router.hidden_size();
+        let hosts = self.hosts()?;
+        let top_k = self.config.top_k.min(self.router.num_experts());
+        // Nombre d'experts que chaque token peut essayer
+        let reach = match self.config.fallback {
+            FallbackPolicy::NextBest => self.router.num_experts(),
+            FallbackPolicy::Drop | FallbackPolicy::Fail => top_k,
+        };
+
+        let ranked: Vec<Vec<usize>> = logits.iter().map(|l| rank_experts(l)).collect();
+        let mut cursor = vec![0; ranked.len()];
+        let mut used: Vec<Vec<(usize, Vec<f32>)>> = vec![Vec::new(); ranked.len()];
+        let mut failed: HashSet<usize> = HashSet::new();
+
+        loop {
+            // Prochains experts à essayer, regroupés par expert
+            let mut batches: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+            for (token, experts) in ranked.iter().enumerate() {
+                let mut wanted = top_k - used[token].len();
+                while wanted > 0 && cursor[token] < reach {
+                    let expert = experts[cursor[token]];
+                    cursor[token] += 1;
+                    if !hosts.contains_key(&expert) {
+                        if self.config.fallback == FallbackPolicy::Fail {
+                            bail!("No node hosts {}", expert_shard_id(&self.model, self.layer, expert));
+                        }
+                        continue;
+                    }
+                    if failed.contains(&expert) {
+                        continue;
+                    }
+                    batches.entry(expert).or_default().push(token);
+                    wanted -= 1;
+                }
+            }
+            if batches.is_empty() {
+                break;
+            }
+
+            let runs = batches.iter().map(|(&expert, tokens)| {
+                let rows = tokens.iter().flat_map(|&t| hidden.data[t * dim..(t + 1) * dim].iter().copied()).collect();
+                let input = Tensor::new(vec![tokens.len(), dim], rows);
+                let peers = &hosts[&expert];
+                async move { self.run_expert(expert, peers, input?).await }
+            });
+            let results = join_all(runs).await;
+            for ((expert, tokens), result) in batches.into_iter().zip(results) {
+                match result {
+                    Ok(output) => {
+                        for (row, token) in output.data.chunks(dim).zip(tokens) {
+                            used[token].push((expert, row.to_vec()));
+                        }
+                    },
+                    Err(e) => {
+                        if self.config.fallback == FallbackPolicy::Fail {
+                            return Err(e);
+                        }
+                        println!("⚠️ Expert {} écarté: {:#}", expert, e);
+                        failed.insert(expert);
+                    },
+                }
+            }
+        }
+
+        let mut data = Vec::with_capacity(hidden.data.len());
+        for (token, outputs) in used.iter().enumerate() {
+            if outputs.is_empty() {
+                bail!("No expert available for token {} of layer {}", token, self.layer);
+            }
+            let experts: Vec<usize> = outputs.iter().map(|(e, _)| *e).collect();
+            let weights = gate_weights(&logits[token], &experts);
+            let mut combined = vec![0.0; dim];
+            for (weight, (_, row)) in weights.iter().zip(outputs) {
+                for (acc, v) in combined.iter_mut().zip(row) {
+                    *acc += weight * v;
+                }
+            }
+            data.extend(combined);
+        }
+        Tensor::new(hidden.shape.clone(), data)
+    }
+
+    /// Exécute un lot sur le premier hôte de l'expert qui répond: un hôte en échec, trop lent
+    /// ou injoignable est remplacé par le suivant avant d'appliquer la politique de repli
+    async fn run_expert(&self, expert: usize, peers: &[PeerId], input: Tensor) -> Result<Tensor> {
+        let mut last_error = None;
+        for &peer in peers {
+            match self.run_on(expert, peer, input.clone()).await {
+                Ok(output) => return Ok(output),
+                Err(e) => {
+                    if peers.len() > 1 {
+                        println!("⚠️ Expert {} sur {} en échec, hôte suivant: {:#}", expert, peer, e);
+                    }
+                    last_error = Some(e);
+                },
+            }
+        }
+        Err(last_error.unwrap_or_else(|| anyhow!("No node hosts {}", expert_shard_id(&self.model, self.layer, expert))))
+    }
+
+    /// Exécute un lot sur un hôte de l'expert, dans le délai configuré
+    async fn run_on(&self, expert: usize, peer: PeerId, input: Tensor) -> Result<Tensor> {
+        let shard_id = expert_shard_id(&self.model, self.layer, expert);
+        let shape = input.shape.clone();
+        let run = async {
+            if peer == self.handle.peer_id() {
+                return self.handle.execute_isolated(&shard_id, input).await;
+            }
+            self.handle.authorize_egress(DataClass::Activations, &peer, false, &serde_json::to_vec(&input)?)?;
+            let request = RpcRequest::Expert(ExpertRequest { shard_id: shard_id.clone(), input });
+            match self.handle.rpc(peer, request).await? {
+                RpcResponse::Tensor(output) => Ok(output),
+                RpcResponse::Error { message, .. } => bail!("Expert {} on {} failed: {}", shard_id, peer, message),
+            }
+        };
+        let output = timeout(Duration::from_millis(self.config.expert_timeout_ms), run).await
+            .map_err(|_| anyhow!("Expert {} on {} timed out", shard_id, peer))??;
+        if output.shape != shape {
+            bail!("Expert {} returned {:?} for input {:?}", shard_id, output.shape, shape);
+        }
+        Ok(output)
+    }
+}
@@ -0,0 +1,699 @@
+// src/pipeline/mod.rs
+//! Inférence en pipeline: chaque nœud porte une plage de couches consécutives (shard nommé
+//! `<modèle>/layers-<début>-<fin>`). Le nœud d'origine envoie les tokens à la première étape;
+//! chaque étape exécute son shard puis transmet les états cachés à la suivante en RPC direct,
+//! et les logits remontent la chaîne jusqu'à l'origine. Les micro-lots du prompt partent sans
+//! attendre: une étape traite le micro-lot `k + 1` pendant que la suivante traite le `k`.
+//! Si une étape disparaît en cours de génération, l'origine la réaffecte à un autre
+//! fournisseur du même shard: l'étape précédente, qui conserve ses sorties, les rejoue au
+//! remplaçant pour qu'il reconstruise son cache KV, et la génération reprend au dernier token.
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::sync::Mutex;
+
+use anyhow::{anyhow, bail, Result};
+use futures::future::{try_join_all, BoxFuture, FutureExt};
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use tokio::time::{timeout, Duration};
+
+use crate::discovery::NodeHandle;
+use crate::executor::Tensor;
+use crate::policy::DataClass;
+use crate::registry::Registry;
+use crate::rpc::{RpcRequest, RpcResponse};
+
+/// Taille par défaut des micro-lots du prompt, en tokens
+pub const DEFAULT_MICRO_BATCH: usize = 16;
+/// Attente maximale du micro-lot précédent d'une session sur une étape
+const STAGE_ORDER_TIMEOUT_SECS: u64 = 60;
+/// Réaffectations d'étapes tolérées par génération avant d'abandonner
+pub const DEFAULT_MAX_REASSIGNMENTS: usize = 3;
+
+/// Plage de couches d'un shard nommé `<modèle>/layers-<début>-<fin>` (bornes incluses)
+pub fn parse_layer_range(shard_id: &str) -> Option<(&str, usize, usize)> {
+    let (model, range) = shard_id.rsplit_once('/')?;
+    let (start, end) = range.strip_prefix("layers-")?.split_once('-')?;
+    let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+    (start <= end).then_some((model, start, end))
+}
+
+/// Étape d'un pipeline: un nœud et le shard qu'il exécute
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipelineStage {
+    pub peer: String,
+    pub shard_id: String,
+    pub first_layer: usize,
+    pub last_layer: usize,
+}
+
+/// Chaîne d'étapes couvrant toutes les couches d'un modèle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipelinePlan {
+    pub model: String,
+    pub stages: Vec<PipelineStage>,
+}
+
+impl PipelinePlan {
+    /// Choisit un nœud par plage de couches parmi les candidats `(PeerId, shard)`, de la
+    /// couche 0 à `num_layers - 1`: le moins d'étapes possible, puis les plages les plus
+    /// longues et les plus petits PeerId
+    pub fn build<I>(candidates: I, model: &str, num_layers: usize) -> Result<Self>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        // Candidats par première couche, des plages les plus longues aux plus courtes
+        let mut by_start: BTreeMap<usize, Vec<PipelineStage>> = BTreeMap::new();
+        for (peer, shard_id) in candidates {
+            let Some((shard_model, first_layer, last_layer)) = parse_layer_range(&shard_id) else {
+                continue;
+            };
+            if shard_model != model || last_layer >= num_layers {
+                continue;
+            }
+            let stage = PipelineStage { peer, shard_id: shard_id.clone(), first_layer, last_layer };
+            by_start.entry(first_layer).or_default().push(stage);
+        }
+        for stages in by_start.values_mut() {
+            stages.sort_by(|a, b| b.last_layer.cmp(&a.last_layer).then_with(|| a.peer.cmp(&b.peer)));
+        }
+
+        // Parcours en largeur sur les positions: la première chaîne trouvée est la plus courte
+        let mut reached: HashMap<usize, (usize, PipelineStage)> = HashMap::new();
+        let mut frontier = vec![0];
+        while !frontier.is_empty() && !reached.contains_key(&num_layers) {
+            let mut next = Vec::new();
+            for position in frontier {
+                for stage in by_start.get(&position).into_iter().flatten() {
+                    let after = stage.last_layer + 1;
+                    if let Entry::Vacant(slot) = reached.entry(after) {
+                        slot.insert((position, stage.clone()));
+                        next.push(after);
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        let mut stages = Vec::new();
+        let mut position = num_layers;
+        while position > 0 {
+            let (previous, stage) = reached.get(&position).ok_or_else(|| {
+                anyhow!("No chain of nodes covers layers 0-{} of {}", num_layers.saturating_sub(1), model)
+            })?;
+            stages.push(stage.clone());
+            position = *previous;
+        }
+        if stages.is_empty() {
+            bail!("Model {} has no layers", model);
+        }
+        stages.reverse();
+        Ok(PipelinePlan { model: model.to_string(), stages })
+    }
+
+    /// Candidats d'un registre: nœuds admis et leurs shards disponibles
+    pub fn candidates(registry: &Registry) -> Vec<(String, String)> {
+        registry.eligible_nodes()
+            .into_iter()
+            .filter_map(|id| registry.nodes.get(&id).map(|entry| (id, entry)))
+            .flat_map(|(id, entry)| {
+                entry.shards.iter()
+                    .filter(|shard| shard.available)
+                    .map(move |shard| (id.clone(), shard.shard_id.clone()))
+            })
+            .collect()
+    }
+}
+
+/// Étape suivante d'une chaîne
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageHop {
+    pub peer: String,
+    pub shard_id: String,
+}
+
+/// Étape injoignable: connexion fermée, délai dépassé ou nœud expiré du registre.
+/// Remonte la chaîne jusqu'à l'origine, qui peut confier le shard à un autre nœud.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageUnavailable {
+    pub peer: String,
+    pub shard_id: String,
+    pub reason: String,
+}
+
+impl fmt::Display for StageUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Stage {} on {} unavailable: {}", self.shard_id, self.peer, self.reason)
+    }
+}
+
+impl std::error::Error for StageUnavailable {}
+
+/// Micro-lot à exécuter par une étape
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageRequest {
+    /// Génération en cours; une nouvelle session réinitialise le cache KV du shard
+    pub session: u64,
+    /// Position du premier token du micro-lot dans la séquence
+    pub offset: usize,
+    pub shard_id: String,
+    /// Tokens `[seq]` pour la première étape, états cachés `[seq, hidden]` ensuite (en trame, hors du JSON)
+    #[serde(skip)]
+    pub input: Tensor,
+    /// Étapes restantes après celle-ci
+    #[serde(default)]
+    pub next: Vec<StageHop>,
+    /// Reconstruction du cache KV d'une étape remplaçante: positions déjà traitées par la chaîne,
+    /// exécutées sans être transmises à l'étape suivante
+    #[serde(default)]
+    pub replay: bool,
+}
+
+#[derive(Debug, Default)]
+struct StageState {
+    session: Option<u64>,
+    next_offset: usize,
+    failed: bool,
+    /// Sorties des positions traitées, une ligne par position: entrées de l'étape suivante,
+    /// rejouées à son remplaçant ou renvoyées pour un micro-lot déjà traité. La dernière étape
+    /// ne garde que la ligne de la dernière position (les logits attendus par l'origine).
+    outputs: Vec<f32>,
+    /// Forme d'une ligne de sortie
+    row_shape: Vec<usize>,
+    last_stage: bool,
+    /// Nœud de l'étape suivante, qui a reçu ces sorties
+    downstream: Option<String>,
+}
+
+impl StageState {
+    fn record(&mut self, output: &Tensor, tokens: usize) {
+        self.row_shape = output.shape[1..].to_vec();
+        if self.last_stage {
+            let width = self.row_shape.iter().product::<usize>();
+            if tokens > 0 {
+                self.outputs = output.data[output.data.len() - width..].to_vec();
+            }
+        } else {
+            self.outputs.extend_from_slice(&output.data);
+        }
+        self.next_offset += tokens;
+    }
+
+    /// Sorties conservées des positions `offset..end`
+    fn rows(&self, offset: usize, end: usize) -> Result<Tensor> {
+        if end > self.next_offset {
+            bail!("Positions {}..{} overlap unprocessed ones (next: {})", offset, end, self.next_offset);
+        }
+        let width = self.row_shape.iter().product::<usize>();
+        let (rows, data) = if !self.last_stage {
+            (end - offset, self.outputs[offset * width..end * width].to_vec())
+        } else if end == self.next_offset && end > offset {
+            (1, self.outputs.clone())
+        } else {
+            // Logits de positions antérieures: l'origine n'utilise que ceux du dernier micro-lot
+            (0, Vec::new())
+        };
+        Tensor::new([vec![rows], self.row_shape.clone()].concat(), data)
+    }
+}
+
+/// Tour d'un micro-lot sur une étape
+enum Turn {
+    /// À exécuter à partir de la ligne `skip` de l'entrée (les premières lignes d'une
+    /// reconstruction peuvent déjà avoir été traitées); `fresh`: nouvelle session, cache KV à vider
+    Run { fresh: bool, skip: usize },
+    /// Micro-lot déjà traité (rejoué après la panne d'une étape suivante): sa sortie conservée
+    Done(Tensor),
+}
+
+/// Ordre d'exécution des micro-lots sur les shards de ce nœud: les requêtes RPC peuvent
+/// arriver dans le désordre, mais le cache KV exige les positions dans l'ordre.
+/// Conserve aussi les sorties de la session en cours, pour reprendre sans recalcul.
+#[derive(Debug, Default)]
+pub struct StageSessions {
+    states: Mutex<HashMap<String, StageState>>,
+    notify: Notify,
+}
+
+impl StageSessions {
+    /// Attend le tour du micro-lot `offset..offset + tokens`, transmis ensuite à `downstream`.
+    /// Une nouvelle session remplace la précédente: un shard ne sert qu'une génération à la fois.
+    async fn wait_turn(
+        &self,
+        shard_id: &str,
+        session: u64,
+        offset: usize,
+        tokens: usize,
+        replay: bool,
+        downstream: Option<&str>,
+    ) -> Result<Turn> {
+        let wait = async {
+            loop {
+                let notified = self.notify.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+                {
+                    let mut states = self.states.lock().map_err(|_| anyhow!("Stage sessions lock poisoned"))?;
+                    let state = states.entry(shard_id.to_string()).or_default();
+                    if state.session == Some(session) {
+                        if state.failed {
+                            bail!("Session {} failed on shard {}", session, shard_id);
+                        }
+                        if state.next_offset == offset {
+                            return Ok(Turn::Run { fresh: false, skip: 0 });
+                        }
+                        if offset < state.next_offset {
+                            let end = offset + tokens;
+                            if replay && end > state.next_offset {
+                                return Ok(Turn::Run { fresh: false, skip: state.next_offset - offset });
+                            }
+                            return state.rows(offset, end).map(Turn::Done);
+                        }
+                    } else if offset == 0 {
+                        *state = StageState {
+                            session: Some(session),
+                            last_stage: downstream.is_none(),
+                            downstream: downstream.map(str::to_string),
+                            ..StageState::default()
+                        };
+                        return Ok(Turn::Run { fresh: true, skip: 0 });
+                    }
+                }
+                notified.await;
+            }
+        };
+        timeout(Duration::from_secs(STAGE_ORDER_TIMEOUT_SECS), wait).await
+            .map_err(|_| anyhow!("Timed out waiting for position {} of session {} on {}", offset, session, shard_id))?
+    }
+
+    /// Fin d'un micro-lot: conserve sa sortie et débloque le suivant, ou toute la session en cas d'échec
+    fn finish(&self, shard_id: &str, session: u64, tokens: usize, output: Option<&Tensor>) {
+        if let Ok(mut states) = self.states.lock() {
+            if let Some(state) = states.get_mut(shard_id).filter(|s| s.session == Some(session)) {
+                match output {
+                    Some(output) => state.record(output, tokens),
+                    None => state.failed = true,
+                }
+            }
+        }
+        self.notify.notify_waiters();
+    }
+
+    /// Session servie en dernier par un shard de ce nœud et nombre de positions traitées
+    pub fn position(&self, shard_id: &str) -> Option<(u64, usize)> {
+        let states = self.states.lock().ok()?;
+        let state = states.get(shard_id)?;
+        Some((state.session?, state.next_offset))
+    }
+
+    /// Transmission d'un micro-lot à `peer`: si l'étape suivante a changé de nœud, renvoie les
+    /// sorties des positions précédentes, que le remplaçant doit traiter d'abord
+    fn route(&self, shard_id: &str, session: u64, peer: &str, offset: usize) -> Result<Option<Tensor>> {
+        let mut states = self.states.lock().map_err(|_| anyhow!("Stage sessions lock poisoned"))?;
+        let Some(state) = states.get_mut(shard_id).filter(|s| s.session == Some(session)) else {
+            return Ok(None);
+        };
+        if state.downstream.as_deref() == Some(peer) {
+            return Ok(None);
+        }
+        state.downstream = Some(peer.to_string());
+        if offset == 0 {
+            return Ok(None);
+        }
+        state.rows(0, offset).map(Some)
+    }
+}
+
+/// Exécute une étape sur ce nœud pour `from`, puis transmet la sortie à l'étape suivante
+pub fn serve_stage(handle: NodeHandle, from: PeerId, request: StageRequest) -> BoxFuture<'static, Result<Tensor>> {
+    async move {
+        let StageRequest { session, offset, shard_id, input, next, replay } = request;
+        if !handle.shards().is_loaded(&shard_id) {
+            bail!("Shard {} not loaded on {}", shard_id, handle.peer_id());
+        }
+        let tokens = input.shape.first().copied().unwrap_or(0);
+        let sessions = handle.stage_sessions();
+        let downstream = next.first().map(|hop| hop.peer.as_str());
+        let output = match sessions.wait_turn(&shard_id, session, offset, tokens, replay, downstream).await? {
+            Turn::Done(output) => output,
+            Turn::Run { fresh, skip } => {
+                let result = async {
+                    if fresh {
+                        handle.shards().reset(&shard_id)?;
+                    }
+                    let input = if skip > 0 {
+                        let width = input.len() / tokens;
+                        let shape = [vec![tokens - skip], input.shape[1..].to_vec()].concat();
+                        Tensor::new(shape, input.data[skip * width..].to_vec())?
+                    } else {
+                        input
+                    };
+                    if from != handle.peer_id() {
+                        if let Some(audit) = handle.audit() {
+                            audit.record_execution(&from, &shard_id, &serde_json::to_vec(&input)?)?;
+                        }
+                    }
+                    let output = handle.execute(&shard_id, input).await?;
+                    if output.shape.first() != Some(&(tokens - skip)) {
+                        bail!("Shard {} returned {:?} for {} positions", shard_id, output.shape, tokens - skip);
+                    }
+                    Ok(output)
+                }
+                .await;
+                sessions.finish(&shard_id, session, tokens - skip, result.as_ref().ok());
+                result?
+            },
+        };
+        // Une reconstruction s'arrête à cette étape: les suivantes ont déjà ces positions
+        if replay {
+            return Ok(Tensor::zeros(vec![0]));
+        }
+
+        let Some((hop, rest)) = next.split_first() else {
+            return Ok(output);
+        };
+        let peer: PeerId = hop.peer.parse().map_err(|_| anyhow!("Invalid stage PeerId {}", hop.peer))?;
+        // Étape suivante réaffectée: le remplaçant reconstruit son cache KV avec les sorties
+        // conservées ici, sans que les étapes amont ne recalculent quoi que ce soit
+        if let Some(history) = sessions.route(&shard_id, session, &hop.peer, offset)? {
+            let request = StageRequest {
+                session,
+                offset: 0,
+                shard_id: hop.shard_id.clone(),
+                input: history,
+                next: rest.to_vec(),
+                replay: true,
+            };
+            dispatch(handle.clone(), peer, DataClass::Activations, true, request).await?;
+        }
+        let request = StageRequest {
+            session,
+            offset,
+            shard_id: hop.shard_id.clone(),
+            input: output,
+            next: rest.to_vec(),
+            replay: false,
+        };
+        dispatch(handle, peer, DataClass::Activations, true, request).await
+    }
+    .boxed()
+}
+
+/// Envoie un micro-lot à une étape: exécution locale si elle est sur ce nœud, sinon RPC
+/// direct, après accord de la politique et consignation dans le journal d'audit
+fn dispatch(
+    handle: NodeHandle,
+    peer: PeerId,
+    data: DataClass,
+    split: bool,
+    request: StageRequest,
+) -> BoxFuture<'static, Result<Tensor>> {
+    async move {
+        if peer == handle.peer_id() {
+            let local = handle.peer_id();
+            return serve_stage(handle, local, request).await;
+        }
+        let shard_id = request.shard_id.clone();
+        let unavailable = move |reason: String| StageUnavailable { peer: peer.to_string(), shard_id, reason };
+        let known = handle.registry().lock().map(|r| r.nodes.contains_key(&peer.to_string())).unwrap_or(false);
+        if !known {
+            return Err(unavailable("expired from registry".into()).into());
+        }
+        handle.authorize_egress(data, &peer, split, &serde_json::to_vec(&request.input)?)?;
+        // Une erreur de transport désigne ce pair; une erreur applicative reste fatale
+        match handle.rpc(peer, RpcRequest::Stage(request)).await {
+            Ok(RpcResponse::Tensor(output)) => Ok(output),
+            Ok(RpcResponse::Error { unavailable: Some(downstream), .. }) => Err(downstream.into()),
+            Ok(RpcResponse::Error { message, .. }) => bail!("Stage on {} failed: {}", peer, message),
+            Err(e) => Err(unavailable(format!("{:#}", e)).into()),
+        }
+    }
+    .boxed()
+}
+
+/// Résultat d'une génération
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Generation {
+    pub tokens: Vec<u32>,
+    /// Logits ayant produit le dernier token
+    pub logits: Vec<f32>,
+}
+
+/// Session de génération vue de l'origine
+struct Session {
+    id: u64,
+    /// Tokens de la séquence déjà traversés par la chaîne dans cette session
+    processed: usize,
+    reassignments: usize,
+    /// Nœuds défaillants, écartés jusqu'à la fin de la génération
+    excluded: HashSet<String>,
+    /// Nœud de la première étape, dont le cache KV porte les tokens traités
+    entry: Option<String>,
+}
+
+impl Session {
+    fn new() -> Self {
+        Session { id: rand::random(), processed: 0, reassignments: 0, excluded: HashSet::new(), entry: None }
+    }
+}
+
+/// Pilote une génération depuis le nœud d'origine
+pub struct PipelineCoordinator {
+    handle: NodeHandle,
+    plan: Mutex<PipelinePlan>,
+    micro_batch: usize,
+    max_reassignments: usize,
+}
+
+impl PipelineCoordinator {
+    pub fn new(handle: NodeHandle, plan: PipelinePlan) -> Self {
+        PipelineCoordinator {
+            handle,
+            plan: Mutex::new(plan),
+            micro_batch: DEFAULT_MICRO_BATCH,
+            max_reassignments: DEFAULT_MAX_REASSIGNMENTS,
+        }
+    }
+
+    /// Planifie d'après le registre du nœud et ses propres shards
+    pub fn from_registry(handle: NodeHandle, model: &str, num_layers: usize) -> Result<Self> {
+        let plan = PipelinePlan::build(local_candidates(&handle)?, model, num_layers)?;
+        Ok(Self::new(handle, plan))
+    }
+
+    /// Suit le plan signé en vigueur pour `model` (publié par ce nœud ou reçu d'un routeur
+    /// certifié), avec les successeurs des identités retirées; à défaut, planifie d'après le registre
+    pub fn for_model(handle: NodeHandle, model: &str, num_layers: usize) -> Result<Self> {
+        let Some(signed) = handle.plan(model).filter(|signed| !signed.plan.stages.is_empty()) else {
+            return Self::from_registry(handle, model, num_layers);
+        };
+        let mut plan = signed.plan.pipeline();
+        let covered = plan.stages.last().map_or(0, |s| s.last_layer + 1);
+        if covered != num_layers {
+            bail!("Plan for {} covers {} layers, {} requested", model, covered, num_layers);
+        }
+        {
+            let registry = handle.registry();
+            let registry = registry.lock().map_err(|_| anyhow!("Registry lock poisoned"))?;
+            for stage in &mut plan.stages {
+                stage.peer = registry.resolve(&stage.peer).to_string();
+            }
+        }
+        Ok(Self::new(handle, plan))
+    }
+
+    /// Taille des micro-lots du prompt, en tokens
+    pub fn with_micro_batch(mut self, tokens: usize) -> Self {
+        self.micro_batch = tokens.max(1);
+        self
+    }
+
+    /// Réaffectations d'étapes tolérées par génération (0: la première panne est fatale)
+    pub fn with_max_reassignments(mut self, max: usize) -> Self {
+        self.max_reassignments = max;
+        self
+    }
+
+    /// Plan courant, mis à jour après chaque réaffectation
+    pub fn plan(&self) -> PipelinePlan {
+        self.plan.lock().map(|plan| plan.clone()).unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    /// Décodage glouton: le prompt en micro-lots, puis un token par passage
+    pub async fn generate(&self, prompt: &[u32], max_new_tokens: usize) -> Result<Generation> {
+        if prompt.is_empty() {
+            bail!("Empty prompt");
+        }
+        let mut session = Session::new();
+        let mut sequence = prompt.to_vec();
+        let mut logits = self.advance(&mut session, &sequence).await?;
+        let mut tokens = Vec::with_capacity(max_new_tokens);
+        while tokens.len() < max_new_tokens {
+            let token = greedy(&logits)?;
+            tokens.push(token);
+            if tokens.len() == max_new_tokens {
+                break;
+            }
+            sequence.push(token);
+            logits = self.advance(&mut session, &sequence).await?;
+        }
+        Ok(Generation { tokens, logits })
+    }
+
+    /// Fait traverser la chaîne aux tokens de `sequence` pas encore traités. Une étape injoignable
+    /// est confiée à un autre fournisseur du même shard, dans la même session: les étapes amont
+    /// renvoient les sorties qu'elles ont conservées au lieu de recalculer, et seul le remplaçant
+    /// reconstruit son cache KV à partir d'elles. Sans autre fournisseur, la chaîne est
+    /// replanifiée et la séquence rejouée depuis la position 0 dans une nouvelle session.
+    async fn advance(&self, session: &mut Session, sequence: &[u32]) -> Result<Vec<f32>> {
+        loop {
+            let plan = self.plan();
+            let result = match self.replay_entry(&plan, session, &sequence[..session.processed]).await {
+                Ok(()) => self.forward(&plan, session.id, session.processed, &sequence[session.processed..]).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(logits) => {
+                    session.processed = sequence.len();
+                    return Ok(logits);
+                },
+                Err(e) => {
+                    let Some(failed) = e.downcast_ref::<StageUnavailable>() else {
+                        return Err(e);
+                    };
+                    if session.reassignments >= self.max_reassignments {
+                        return Err(e.context(format!("Gave up after {} stage reassignments", session.reassignments)));
+                    }
+                    println!("⚠️ {}: réaffectation de l'étape", failed);
+                    session.excluded.insert(failed.peer.clone());
+                    if !self.reassign(&plan, &failed.shard_id, &session.excluded)? {
+                        *session = Session {
+                            reassignments: session.reassignments,
+                            excluded: std::mem::take(&mut session.excluded),
+                            ..Session::new()
+                        };
+                    }
+                    session.reassignments += 1;
+                },
+            }
+        }
+    }
+
+    /// Première étape confiée à un nouveau nœud: lui fait reconstruire son cache KV avec les
+    /// tokens déjà traités par la session
+    async fn replay_entry(&self, plan: &PipelinePlan, session: &mut Session, history: &[u32]) -> Result<()> {
+        let (first, peer, next) = entry(plan)?;
+        if session.entry.as_deref() == Some(first.peer.as_str()) {
+            return Ok(());
+        }
+        if !history.is_empty() {
+            let split = !next.is_empty();
+            let request = StageRequest {
+                session: session.id,
+                offset: 0,
+                shard_id: first.shard_id.clone(),
+                input: token_tensor(history)?,
+                next,
+                replay: true,
+            };
+            dispatch(self.handle.clone(), peer, DataClass::Prompt, split, request).await?;
+        }
+        session.entry = Some(first.peer.clone());
+        Ok(())
+    }
+
+    /// Confie le shard défaillant à un autre fournisseur (le plus petit PeerId) et renvoie `true`;
+    /// à défaut, replanifie toute la chaîne sans les nœuds écartés et renvoie `false`
+    fn reassign(&self, plan: &PipelinePlan, shard_id: &str, excluded: &HashSet<String>) -> Result<bool> {
+        let candidates: Vec<(String, String)> = local_candidates(&self.handle)?
+            .into_iter()
+            .filter(|(peer, _)| !excluded.contains(peer))
+            .collect();
+        let replacement = candidates.iter()
+            .filter(|(_, shard)| shard == shard_id)
+            .map(|(peer, _)| peer)
+            .min();
+        let (updated, resumed) = match (replacement, plan.stages.iter().position(|s| s.shard_id == shard_id)) {
+            (Some(peer), Some(index)) => {
+                let mut updated = plan.clone();
+                updated.stages[index].peer = peer.clone();
+                (updated, true)
+            },
+            _ => {
+                let num_layers = plan.stages.last().map_or(0, |s| s.last_layer + 1);
+                (PipelinePlan::build(candidates, &plan.model, num_layers)?, false)
+            },
+        };
+        *self.plan.lock().map_err(|_| anyhow!("Pipeline plan lock poisoned"))? = updated;
+        Ok(resumed)
+    }
+
+    /// Passe avant de `tokens` à partir de `offset`; renvoie les logits du dernier token
+    async fn forward(&self, plan: &PipelinePlan, session: u64, offset: usize, tokens: &[u32]) -> Result<Vec<f32>> {
+        let (first, peer, next) = entry(plan)?;
+        // Le prompt est réparti entre plusieurs nœuds dès que la chaîne a plus d'une étape
+        let split = !next.is_empty();
+
+        let mut batches = Vec::new();
+        for (i, chunk) in tokens.chunks(self.micro_batch).enumerate() {
+            let request = StageRequest {
+                session,
+                offset: offset + i * self.micro_batch,
+                shard_id: first.shard_id.clone(),
+                input: token_tensor(chunk)?,
+                next: next.clone(),
+                replay: false,
+            };
+            batches.push(dispatch(self.handle.clone(), peer, DataClass::Prompt, split, request));
+        }
+        let outputs = try_join_all(batches).await?;
+
+        let last = outputs.last().ok_or_else(|| anyhow!("No tokens to process"))?;
+        let [rows, vocab] = last.shape[..] else {
+            bail!("Last stage returned shape {:?} instead of logits", last.shape);
+        };
+        if rows == 0 {
+            bail!("Last stage returned no logits");
+        }
+        Ok(last.data[last.data.len() - vocab..].to_vec())
+    }
+}
+
+/// Première étape du plan, son nœud et les étapes suivantes
+fn entry(plan: &PipelinePlan) -> Result<(&PipelineStage, PeerId, Vec<StageHop>)> {
+    let (first, rest) = plan.stages.split_first().ok_or_else(|| anyhow!("Empty pipeline plan"))?;
+    let peer: PeerId = first.peer.parse().map_err(|_| anyhow!("Invalid stage PeerId {}", first.peer))?;
+    let next = rest.iter()
+        .map(|s| StageHop { peer: s.peer.clone(), shard_id: s.shard_id.clone() })
+        .collect();
+    Ok((first, peer, next))
+}
+
+fn token_tensor(tokens: &[u32]) -> Result<Tensor> {
+    Tensor::new(vec![tokens.len()], tokens.iter().map(|&t| t as f32).collect())
+}
+
+/// Candidats du registre du nœud, plus ses propres shards chargés
+fn local_candidates(handle: &NodeHandle) -> Result<Vec<(String, String)>> {
+    let mut candidates = {
+        let registry = handle.registry();
+        let registry = registry.lock().map_err(|_| anyhow!("Registry lock poisoned"))?;
+        PipelinePlan::candidates(&registry)
+    };
+    let local = handle.peer_id().to_string();
+    candidates.extend(handle.shards().loaded().into_iter().map(|s| (local.clone(), s.id)));
+    Ok(candidates)
+}
+
+/// Token le plus probable (le premier en cas d'égalité)
+fn greedy(logits: &[f32]) -> Result<u32> {
+    let mut best: Option<(usize, f32)> = None;
+    for (i, &value) in logits.iter().enumerate() {
+        if best.map_or(true, |(_, b)| value > b) {
+            best = Some((i, value));
+        }
+    }
+    best.map(|(i, _)| i as u32).ok_or_else(|| anyhow!("Empty logits"))
+}
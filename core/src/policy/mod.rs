@@ -0,0 +1,193 @@
+// src/policy/mod.rs
+//! Consentement et localité des données: toute sortie d'un prompt, d'un cache KV,
+//! d'activations ou d'une réponse est soumise à la politique avant de quitter le nœud.
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::audit::{AuditEvent, AuditLog};
+use crate::signing::unix_now;
+
+/// Nombre de décisions récentes conservées en mémoire (consultables via l'API)
+const DECISION_HISTORY: usize = 1024;
+
+/// Catégorie de données susceptible de quitter le nœud
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataClass {
+    Prompt,
+    KvCache,
+    Activations,
+    Output,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyAction {
+    #[default]
+    Allow,
+    Deny,
+}
+
+/// Règle de la section `policy.rules`; la première règle qui correspond s'applique
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PolicyRule {
+    /// Catégories concernées (toutes si vide)
+    pub data: Vec<DataClass>,
+    /// PeerId destinataires concernés
+    pub peers: Vec<String>,
+    /// Domaines de confiance concernés (certificat valide du destinataire)
+    pub trust_domains: Vec<String>,
+    pub action: PolicyAction,
+}
+
+impl PolicyRule {
+    fn matches(&self, request: &EgressRequest) -> bool {
+        let data = self.data.is_empty() || self.data.contains(&request.data);
+        let any_peer = self.peers.is_empty() && self.trust_domains.is_empty();
+        let peer = self.peers.contains(&request.peer);
+        let domain = request.trust_domain.as_ref()
+            .is_some_and(|d| self.trust_domains.contains(d));
+        data && (any_peer || peer || domain)
+    }
+}
+
+/// Section `policy:` de la configuration
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PolicyConfig {
+    /// Action si aucune règle ne correspond
+    pub default: PolicyAction,
+    pub rules: Vec<PolicyRule>,
+    /// Un prompt peut-il être découpé entre plusieurs nœuds (pipeline) ?
+    pub allow_prompt_split: bool,
+    /// Les journaux peuvent-ils contenir le contenu des messages ?
+    pub log_content: bool,
+}
+
+impl PolicyConfig {
+    /// Vérifie que les PeerId des règles sont valides
+    pub fn validate(&self) -> Result<()> {
+        for (i, rule) in self.rules.iter().enumerate() {
+            for peer in &rule.peers {
+                if peer.parse::<libp2p::PeerId>().is_err() {
+                    bail!("policy.rules[{}]: invalid PeerId {:?}", i, peer);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Donnée sur le point de quitter le nœud
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EgressRequest {
+    pub data: DataClass,
+    /// PeerId du destinataire
+    pub peer: String,
+    /// Domaine de confiance du destinataire, s'il présente un certificat valide
+    #[serde(default)]
+    pub trust_domain: Option<String>,
+    /// Fragment d'un prompt réparti sur plusieurs nœuds
+    #[serde(default)]
+    pub split: bool,
+}
+
+/// Décision rendue pour une sortie de données
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyDecision {
+    pub timestamp: u64,
+    pub request: EgressRequest,
+    pub allowed: bool,
+    /// Indice de la règle appliquée (`None`: action par défaut ou découpage)
+    pub rule: Option<usize>,
+    pub reason: String,
+}
+
+/// Moteur de politique partagé entre la boucle du nœud et l'API
+#[derive(Debug, Default)]
+pub struct PolicyEngine {
+    config: PolicyConfig,
+    decisions: Mutex<VecDeque<PolicyDecision>>,
+    audit: Option<Arc<AuditLog>>,
+}
+
+impl PolicyEngine {
+    pub fn new(config: PolicyConfig) -> Self {
+        PolicyEngine {
+            config,
+            decisions: Mutex::new(VecDeque::new()),
+            audit: None,
+        }
+    }
+
+    /// Consigne aussi chaque décision dans le journal d'audit
+    pub fn with_audit(mut self, audit: Arc<AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    pub fn config(&self) -> &PolicyConfig {
+        &self.config
+    }
+
+    /// Évalue une sortie sans l'enregistrer (requêtes de l'API)
+    pub fn evaluate(&self, request: &EgressRequest) -> PolicyDecision {
+        let (allowed, rule, reason) = if request.split && request.data == DataClass::Prompt && !self.config.allow_prompt_split {
+            (false, None, "prompt splitting not allowed".to_string())
+        } else if let Some((i, rule)) = self.config.rules.iter().enumerate().find(|(_, r)| r.matches(request)) {
+            (rule.action == PolicyAction::Allow, Some(i), format!("rule {}", i))
+        } else {
+            (self.config.default == PolicyAction::Allow, None, "default".to_string())
+        };
+        PolicyDecision {
+            timestamp: unix_now(),
+            request: request.clone(),
+            allowed,
+            rule,
+            reason,
+        }
+    }
+
+    /// Point de contrôle avant toute sortie: évalue, journalise, et refuse si nécessaire
+    pub fn check(&self, request: EgressRequest) -> Result<PolicyDecision> {
+        let decision = self.evaluate(&request);
+        if decision.allowed {
+            println!("🛂 Sortie autorisée: {:?} → {} ({})", request.data, request.peer, decision.reason);
+        } else {
+            println!("🛑 Sortie refusée: {:?} → {} ({})", request.data, request.peer, decision.reason);
+        }
+        if let Ok(mut decisions) = self.decisions.lock() {
+            if decisions.len() == DECISION_HISTORY {
+                decisions.pop_front();
+            }
+            decisions.push_back(decision.clone());
+        }
+        if let Some(audit) = &self.audit {
+            if let Err(e) = audit.record(AuditEvent::Policy(decision.clone())) {
+                println!("⚠️ Décision non consignée dans le journal d'audit: {:?}", e);
+            }
+        }
+        if !decision.allowed {
+            bail!("Policy denies sending {:?} to {} ({})", request.data, request.peer, decision.reason);
+        }
+        Ok(decision)
+    }
+
+    /// Décisions récentes, de la plus ancienne à la plus récente
+    pub fn decisions(&self) -> Vec<PolicyDecision> {
+        self.decisions.lock().map(|d| d.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Contenu tel qu'il peut apparaître dans les journaux
+    pub fn loggable(&self, content: &str) -> String {
+        if self.config.log_content {
+            content.to_string()
+        } else {
+            format!("<{} octets masqués>", content.len())
+        }
+    }
+}
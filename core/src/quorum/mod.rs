@@ -0,0 +1,289 @@
+// src/quorum/mod.rs
+//! Validation croisée par quorum: un même calcul de shard est confié à plusieurs fournisseurs
+//! indépendants, leurs sorties sont comparées et le résultat n'est retenu que si une majorité
+//! concorde. Les fournisseurs minoritaires sont signalés au système de réputation.
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use futures::future::join_all;
+use libp2p::PeerId;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::time::{timeout, Duration};
+
+use crate::discovery::NodeHandle;
+use crate::executor::Tensor;
+use crate::pipeline::PipelinePlan;
+use crate::policy::DataClass;
+use crate::rpc::{RpcRequest, RpcResponse};
+
+pub const DEFAULT_REPLICAS: usize = 3;
+pub const DEFAULT_REPLICA_TIMEOUT_MS: u64 = 30_000;
+/// Poids minimal d'un fournisseur au tirage: une réputation nulle le rend rare, pas exclu
+const MIN_PROVIDER_WEIGHT: f64 = 0.01;
+
+/// Comparaison de deux sorties d'un même calcul
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tolerance {
+    /// Égalité bit à bit, pour les backends déterministes (`cpu`, `identity`)
+    Exact,
+    /// Écart admis par valeur: `|a - b| <= absolute + relative * |b|`
+    Approx { absolute: f32, relative: f32 },
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance::Approx { absolute: 1e-4, relative: 1e-3 }
+    }
+}
+
+impl Tolerance {
+    /// Les deux tenseurs sont-ils la même sortie ? (une valeur NaN ne concorde jamais)
+    pub fn matches(&self, a: &Tensor, b: &Tensor) -> bool {
+        if a.shape != b.shape || a.data.len() != b.data.len() {
+            return false;
+        }
+        match *self {
+            Tolerance::Exact => a.data.iter().zip(&b.data).all(|(x, y)| x.to_bits() == y.to_bits()),
+            Tolerance::Approx { absolute, relative } => a.data.iter()
+                .zip(&b.data)
+                .all(|(x, y)| (x - y).abs() <= absolute + relative * y.abs()),
+        }
+    }
+}
+
+/// Section `quorum:` de la configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuorumConfig {
+    /// Fournisseurs sollicités pour chaque calcul
+    pub replicas: usize,
+    /// Sorties concordantes exigées (défaut: majorité stricte des répliques)
+    pub quorum: Option<usize>,
+    pub tolerance: Tolerance,
+    /// Délai au-delà duquel une réplique est considérée comme en échec
+    pub replica_timeout_ms: u64,
+}
+
+impl Default for QuorumConfig {
+    fn default() -> Self {
+        QuorumConfig {
+            replicas: DEFAULT_REPLICAS,
+            quorum: None,
+            tolerance: Tolerance::default(),
+            replica_timeout_ms: DEFAULT_REPLICA_TIMEOUT_MS,
+        }
+    }
+}
+
+impl QuorumConfig {
+    /// Sorties concordantes exigées
+    pub fn threshold(&self) -> usize {
+        self.quorum.unwrap_or(self.replicas / 2 + 1)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.replicas == 0 {
+            bail!("quorum.replicas must be at least 1");
+        }
+        // Une majorité stricte garantit qu'au plus un groupe de sorties atteint le quorum
+        let threshold = self.threshold();
+        if threshold * 2 <= self.replicas || threshold > self.replicas {
+            bail!("quorum.quorum must be a strict majority of {} replicas, got {}", self.replicas, threshold);
+        }
+        if let Tolerance::Approx { absolute, relative } = self.tolerance {
+            if !(absolute >= 0.0 && relative >= 0.0 && absolute.is_finite() && relative.is_finite()) {
+                bail!("quorum.tolerance must be finite and non-negative");
+            }
+        }
+        if self.replica_timeout_ms == 0 {
+            bail!("quorum.replica_timeout_ms must be positive");
+        }
+        Ok(())
+    }
+}
+
+/// Calcul isolé d'un shard, à comparer avec ceux des autres répliques
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplicaRequest {
+    pub shard_id: String,
+    /// Transmis en trame sur le flux de tenseurs de la requête, hors du JSON
+    #[serde(skip)]
+    pub input: Tensor,
+}
+
+/// Exécute une réplique sur ce nœud pour `from`, depuis un état de séquence vide: toutes
+/// les répliques partent du même état, sans quoi leurs sorties divergeraient légitimement.
+/// L'exécution part d'un cache KV vierge, propre à l'appel, pour ne pas effacer celui
+/// d'une génération en pipeline servie au même moment.
+pub async fn serve_replica(handle: NodeHandle, from: PeerId, request: ReplicaRequest) -> Result<Tensor> {
+    let ReplicaRequest { shard_id, input } = request;
+    if !handle.shards().is_loaded(&shard_id) {
+        bail!("Shard {} not loaded on {}", shard_id, handle.peer_id());
+    }
+    if from != handle.peer_id() {
+        if let Some(audit) = handle.audit() {
+            audit.record_execution(&from, &shard_id, &serde_json::to_vec(&input)?)?;
+        }
+    }
+    handle.execute_isolated(&shard_id, input).await
+}
+
+/// Destinataire des désaccords constatés (système de réputation)
+pub trait DissentReporter: Send + Sync {
+    /// `peer` a rendu pour `shard_id` une sortie contredite par le quorum
+    fn report_dissent(&self, peer: &PeerId, shard_id: &str);
+
+    /// `peer` a rendu pour `shard_id` la sortie retenue par le quorum
+    fn report_agreement(&self, _peer: &PeerId, _shard_id: &str) {}
+}
+
+/// Résultat accepté par le quorum
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuorumOutcome {
+    pub output: Tensor,
+    /// Fournisseurs dont la sortie a formé le quorum
+    pub agreeing: Vec<PeerId>,
+    /// Fournisseurs dont la sortie a été rejetée
+    pub dissenting: Vec<PeerId>,
+    /// Fournisseurs en erreur, trop lents ou injoignables
+    pub failed: Vec<PeerId>,
+}
+
+/// Exécution répliquée d'un shard, pilotée depuis ce nœud
+pub struct QuorumExecutor {
+    handle: NodeHandle,
+    config: QuorumConfig,
+    reporter: Option<Arc<dyn DissentReporter>>,
+}
+
+impl QuorumExecutor {
+    /// Exécuteur utilisant la section `quorum:` de la configuration du nœud; les accords et
+    /// désaccords sont reportés à la réputation du nœud
+    pub fn new(handle: NodeHandle) -> Self {
+        let config = handle.quorum_config();
+        let reporter: Arc<dyn DissentReporter> = handle.reputation();
+        QuorumExecutor { handle, config, reporter: Some(reporter) }
+    }
+
+    pub fn with_config(mut self, config: QuorumConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_reporter(mut self, reporter: Arc<dyn DissentReporter>) -> Self {
+        self.reporter = Some(reporter);
+        self
+    }
+
+    /// Fournisseurs de `shard_id` sollicités, dans la limite des répliques configurées: ce nœud
+    /// s'il l'a chargé, puis des pairs tirés au hasard en proportion de leur réputation. Un domaine
+    /// de confiance connu ne fournit qu'une réplique: une organisation ne forme pas seule le quorum.
+    pub fn providers(&self, shard_id: &str) -> Result<Vec<PeerId>> {
+        let local = self.handle.peer_id();
+        let mut providers = Vec::new();
+        let mut domains = HashSet::new();
+        if self.handle.shards().is_loaded(shard_id) {
+            providers.push(local);
+            domains.extend(self.handle.trust_domain().map(str::to_string));
+        }
+
+        let reputation = self.handle.reputation();
+        let mut rng = rand::thread_rng();
+        let mut candidates: Vec<(f64, PeerId, Option<String>)> = {
+            let registry = self.handle.registry();
+            let registry = registry.lock().map_err(|_| anyhow!("Registry lock poisoned"))?;
+            PipelinePlan::candidates(&registry)
+                .into_iter()
+                .filter(|(peer, shard)| shard == shard_id && *peer != local.to_string())
+                .filter_map(|(peer, _)| {
+                    let domain = registry.nodes.get(&peer)
+                        .and_then(|entry| entry.trust.as_ref())
+                        .filter(|trust| trust.is_valid())
+                        .map(|trust| trust.domain.clone());
+                    // Tirage pondéré sans remise: clé u^(1/poids), les plus grandes clés passent en tête
+                    let weight = f64::from(reputation.score(&peer)).max(MIN_PROVIDER_WEIGHT);
+                    let key = rng.gen::<f64>().powf(1.0 / weight);
+                    Some((key, peer.parse().ok()?, domain))
+                })
+                .collect()
+        };
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        for (_, peer, domain) in candidates {
+            if providers.len() >= self.config.replicas {
+                break;
+            }
+            if providers.contains(&peer) || domain.is_some_and(|domain| !domains.insert(domain)) {
+                continue;
+            }
+            providers.push(peer);
+        }
+        Ok(providers)
+    }
+
+    /// Exécute `shard_id` sur chaque fournisseur et retient la sortie du quorum
+    pub async fn execute(&self, shard_id: &str, input: &Tensor) -> Result<QuorumOutcome> {
+        let threshold = self.config.threshold();
+        let providers = self.providers(shard_id)?;
+        if providers.len() < threshold {
+            bail!("Only {} provider(s) of {} for a quorum of {}", providers.len(), shard_id, threshold);
+        }
+
+        let runs = providers.iter().map(|&peer| self.run_replica(peer, shard_id, input.clone()));
+        let results = join_all(runs).await;
+
+        // Regroupe les sorties concordantes, dans l'ordre des fournisseurs
+        let mut groups: Vec<(Tensor, Vec<PeerId>)> = Vec::new();
+        let mut failed = Vec::new();
+        for (peer, result) in providers.into_iter().zip(results) {
+            match result {
+                Ok(output) => match groups.iter_mut().find(|(first, _)| self.config.tolerance.matches(&output, first)) {
+                    Some((_, peers)) => peers.push(peer),
+                    None => groups.push((output, vec![peer])),
+                },
+                Err(e) => {
+                    println!("⚠️ Réplique de {} sur {} en échec: {:#}", shard_id, peer, e);
+                    failed.push(peer);
+                },
+            }
+        }
+        let Some(winner) = groups.iter().position(|(_, peers)| peers.len() >= threshold) else {
+            let sizes: Vec<usize> = groups.iter().map(|(_, peers)| peers.len()).collect();
+            bail!("No quorum of {} for {}: agreeing groups {:?}, {} failed", threshold, shard_id, sizes, failed.len());
+        };
+        let (output, agreeing) = groups.swap_remove(winner);
+        let dissenting: Vec<PeerId> = groups.into_iter().flat_map(|(_, peers)| peers).collect();
+        if let Some(reporter) = &self.reporter {
+            for peer in &agreeing {
+                reporter.report_agreement(peer, shard_id);
+            }
+        }
+        for peer in &dissenting {
+            println!("⚠️ Sortie de {} pour {} rejetée par le quorum", peer, shard_id);
+            if let Some(reporter) = &self.reporter {
+                reporter.report_dissent(peer, shard_id);
+            }
+        }
+        Ok(QuorumOutcome { output, agreeing, dissenting, failed })
+    }
+
+    /// Exécute une réplique: localement si ce nœud est fournisseur, sinon en RPC direct
+    async fn run_replica(&self, peer: PeerId, shard_id: &str, input: Tensor) -> Result<Tensor> {
+        let request = ReplicaRequest { shard_id: shard_id.to_string(), input };
+        let run = async {
+            if peer == self.handle.peer_id() {
+                return serve_replica(self.handle.clone(), peer, request).await;
+            }
+            self.handle.authorize_egress(DataClass::Activations, &peer, false, &serde_json::to_vec(&request.input)?)?;
+            match self.handle.rpc(peer, RpcRequest::Replica(request)).await? {
+                RpcResponse::Tensor(output) => Ok(output),
+                RpcResponse::Error { message, .. } => bail!("Replica of {} on {} failed: {}", shard_id, peer, message),
+            }
+        };
+        timeout(Duration::from_millis(self.config.replica_timeout_ms), run).await
+            .map_err(|_| anyhow!("Replica of {} on {} timed out", shard_id, peer))?
+    }
+}
@@ -1,9 +1,22 @@
 // src/registry/mod.rs
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 use std::time::{Duration, Instant};
 
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
+use crate::admission::AdmissionProof;
+use crate::config::PROTOCOL_VERSION;
+use crate::signing::unix_now;
+use crate::trust::{NodeCertificate, RevocationList, TrustInfo};
+
+/// Durée par défaut au-delà de laquelle un nœud silencieux est retiré du registre
+pub const NODE_TTL_SECS: u64 = 120;
+/// Poids d'une nouvelle mesure dans la moyenne glissante de latence
+const LATENCY_SMOOTHING: f32 = 0.2;
+
 /// Informations sur un shard disponible sur un nœud
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ShardInfo {
@@ -18,6 +31,17 @@ pub struct NodeEntry {
     pub last_seen: Instant,
     pub shards: Vec<ShardInfo>,
     pub vram_free_mb: u32,
+    pub ram_free_mb: u32,
+    /// Exécutions de shards en cours sur le nœud
+    pub load: u32,
+    /// Aller-retour moyen mesuré depuis ce nœud (absent si jamais connecté directement)
+    pub latency_ms: Option<f32>,
+    /// Certificat d'organisation validé (absent si non présenté ou invalide)
+    pub trust: Option<TrustInfo>,
+    /// Preuve d'admission acceptée; sinon le nœud est exclu du quorum et de l'ordonnancement
+    pub admitted: bool,
+    /// Adresses annoncées, pour joindre le nœud en RPC direct
+    pub addrs: Vec<String>,
 }
 
 /// Message de simulation ou de réception PubSub
@@ -27,72 +51,333 @@ pub struct AnnounceMsg {
     pub shards: Vec<String>,
     pub version: String,
     pub vram_free_mb: u32,
+    #[serde(default)]
+    pub ram_free_mb: u32,
+    #[serde(default)]
+    pub load: u32,
+    /// Version du protocole mesh de l'émetteur (absente chez les nœuds v1)
+    #[serde(default = "default_protocol_version")]
+    pub protocol_version: u32,
+    /// Annonce de départ: le nœud quitte le mesh et doit être retiré immédiatement
+    #[serde(default)]
+    pub leaving: bool,
+    /// Certificat signé par l'organisation du nœud, s'il en a un
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<NodeCertificate>,
+    /// Preuve de travail liée au PeerId, exigée par les mesh à coût d'admission
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admission: Option<AdmissionProof>,
+    /// Adresses d'écoute de l'émetteur, pour le RPC direct entre nœuds non connectés
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub addrs: Vec<String>,
+}
+
+fn default_protocol_version() -> u32 {
+    1
+}
+
+impl AnnounceMsg {
+    /// Annonce minimale d'un nœud: protocole et version courants, aucune ressource ni shard.
+    /// Les champs utiles se renseignent ensuite par mise à jour de structure.
+    pub fn new(node_id: impl Into<String>) -> Self {
+        AnnounceMsg {
+            node_id: node_id.into(),
+            shards: Vec::new(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            vram_free_mb: 0,
+            ram_free_mb: 0,
+            load: 0,
+            protocol_version: PROTOCOL_VERSION,
+            leaving: false,
+            certificate: None,
+            admission: None,
+            addrs: Vec::new(),
+        }
+    }
 }
 
 /// Registry local contenant les métadonnées du mesh
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct Registry {
     pub nodes: HashMap<String, NodeEntry>, // node_id → info
+    /// Identités retirées par une succession de clé: ancien node_id → nouveau
+    pub successors: HashMap<String, String>,
+    ttl: Duration,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Registry {
+            nodes: HashMap::new(),
+            successors: HashMap::new(),
+            ttl: Duration::from_secs(NODE_TTL_SECS),
+        }
+    }
 }
 
 impl Registry {
-    /// Met à jour le registre depuis un message d’annonce (ex: PubSub)
+    /// Registre vide dont les nœuds expirent après `ttl` sans annonce
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Registry {
+            nodes: HashMap::new(),
+            successors: HashMap::new(),
+            ttl,
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Met à jour le registre depuis un message d’annonce (ex: PubSub).
+    /// Les annonces d'une identité retirée sont ignorées.
     pub fn update_from_announce(&mut self, msg: AnnounceMsg) {
+        if self.successors.contains_key(&msg.node_id) {
+            return;
+        }
         let shards = msg.shards.into_iter().map(|s| ShardInfo {
             shard_id: s,
             version: msg.version.clone(),
             available: true,
         }).collect();
 
+        let latency_ms = self.nodes.get(&msg.node_id).and_then(|entry| entry.latency_ms);
         let entry = NodeEntry {
             last_seen: Instant::now(),
             shards,
             vram_free_mb: msg.vram_free_mb,
+            ram_free_mb: msg.ram_free_mb,
+            load: msg.load,
+            latency_ms,
+            trust: None,
+            admitted: true,
+            addrs: msg.addrs,
         };
 
         self.nodes.insert(msg.node_id, entry);
     }
 
-    /// Supprime les nœuds inactifs depuis plus de `120` secondes
-    pub fn prune(&mut self) {
-        self.nodes.retain(|_peer_id, info| info.last_seen.elapsed().as_secs() <= 120);
+    /// Transfère l'entrée de `old` à `new` et retire définitivement `old`.
+    /// Si `new` s'est déjà annoncé, son entrée (plus fraîche) est conservée.
+    pub fn migrate(&mut self, old: &str, new: &str) -> bool {
+        let moved = match self.nodes.remove(old) {
+            Some(mut entry) => {
+                // Le certificat désignait l'ancien PeerId: celui du successeur sera vérifié à son annonce
+                entry.trust = None;
+                self.nodes.entry(new.to_string()).or_insert(entry);
+                true
+            }
+            None => false,
+        };
+        self.successors.insert(old.to_string(), new.to_string());
+        moved
     }
 
-    /// Export JSON lisible pour debug ou snapshot
-    pub fn snapshot_json(&self) -> String {
-        #[derive(Serialize)]
-        struct Snapshot<'a> {
-            timestamp: u64,
-            nodes: &'a HashMap<String, NodeEntryJson>,
+    /// Identité courante d'un nœud, en suivant les successions éventuelles
+    pub fn resolve<'a>(&'a self, node_id: &'a str) -> &'a str {
+        let mut current = node_id;
+        // Borne de sécurité contre un cycle de successions
+        for _ in 0..self.successors.len() {
+            match self.successors.get(current) {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        current
+    }
+
+    /// Intègre une mesure d'aller-retour vers un nœud à sa latence moyenne
+    pub fn record_latency(&mut self, node_id: &str, rtt: Duration) {
+        if let Some(entry) = self.nodes.get_mut(node_id) {
+            let ms = rtt.as_secs_f32() * 1000.0;
+            entry.latency_ms = Some(match entry.latency_ms {
+                Some(avg) => avg + LATENCY_SMOOTHING * (ms - avg),
+                None => ms,
+            });
+        }
+    }
+
+    /// Enregistre le résultat de la validation du certificat d'un nœud
+    pub fn set_trust(&mut self, node_id: &str, trust: Option<TrustInfo>) {
+        if let Some(entry) = self.nodes.get_mut(node_id) {
+            entry.trust = trust;
+        }
+    }
+
+    /// Enregistre le résultat de la vérification de la preuve d'admission d'un nœud
+    pub fn set_admission(&mut self, node_id: &str, admitted: bool) {
+        if let Some(entry) = self.nodes.get_mut(node_id) {
+            entry.admitted = admitted;
+        }
+    }
+
+    /// Nœuds admis, seuls candidats au quorum et à l'ordonnancement
+    pub fn eligible_nodes(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.nodes.iter()
+            .filter(|(_, entry)| entry.admitted)
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Le nœud présente-t-il un certificat valide (tous domaines confondus) pour ce rôle ?
+    pub fn holds_role(&self, node_id: &str, role: &str) -> bool {
+        self.nodes.get(node_id)
+            .and_then(|entry| entry.trust.as_ref())
+            .is_some_and(|trust| trust.is_valid() && trust.roles.iter().any(|r| r == role))
+    }
+
+    /// Nœuds certifiés (et non expirés) par l'organisation `domain`, éventuellement pour un rôle
+    pub fn in_trust_domain(&self, domain: &str, role: Option<&str>) -> Vec<String> {
+        let mut ids: Vec<String> = self.nodes.iter()
+            .filter(|(_, entry)| match &entry.trust {
+                Some(trust) => {
+                    trust.domain == domain
+                        && trust.is_valid()
+                        && role.map_or(true, |r| trust.roles.iter().any(|x| x == r))
+                }
+                None => false,
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Retire la confiance accordée aux nœuds révoqués; renvoie leurs identifiants
+    pub fn apply_revocation(&mut self, list: &RevocationList) -> Vec<String> {
+        let mut revoked = Vec::new();
+        for (id, entry) in self.nodes.iter_mut() {
+            let hit = entry.trust.as_ref()
+                .is_some_and(|t| t.domain == list.trust_domain && list.revokes(id, t.serial));
+            if hit {
+                entry.trust = None;
+                revoked.push(id.clone());
+            }
         }
+        revoked
+    }
+
+    /// Retire immédiatement un nœud (ex: annonce de départ)
+    pub fn remove_node(&mut self, node_id: &str) -> bool {
+        self.nodes.remove(node_id).is_some()
+    }
 
-        #[derive(Serialize)]
-        struct NodeEntryJson {
-            shards: Vec<ShardInfo>,
-            vram_free_mb: u32,
-            last_seen_secs_ago: u64,
+    /// Supprime les nœuds inactifs depuis plus que le TTL, et renvoie leurs identifiants
+    pub fn prune(&mut self) -> Vec<String> {
+        let ttl = self.ttl;
+        let expired: Vec<String> = self.nodes.iter()
+            .filter(|(_, info)| info.last_seen.elapsed() > ttl)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in &expired {
+            self.nodes.remove(id);
         }
+        expired
+    }
 
+    fn to_snapshot(&self) -> Snapshot {
         let now = Instant::now();
-        let mapped: HashMap<_, _> = self.nodes.iter().map(|(k, v)| {
+        let nodes = self.nodes.iter().map(|(k, v)| {
             let age = now.duration_since(v.last_seen).as_secs();
             let json = NodeEntryJson {
                 shards: v.shards.clone(),
                 vram_free_mb: v.vram_free_mb,
+                ram_free_mb: v.ram_free_mb,
+                load: v.load,
                 last_seen_secs_ago: age,
+                trust: v.trust.clone(),
+                admitted: v.admitted,
+                addrs: v.addrs.clone(),
             };
             (k.clone(), json)
         }).collect();
 
-        let snap = Snapshot {
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or(Duration::from_secs(0))
-                .as_secs(),
-            nodes: &mapped,
-        };
-        
+        Snapshot {
+            timestamp: unix_now(),
+            nodes,
+            successors: self.successors.clone(),
+        }
+    }
+
+    /// Export JSON lisible pour debug ou snapshot
+    pub fn snapshot_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_snapshot()).unwrap_or_else(|_| "{}".into())
+    }
+
+    /// Sauvegarde le registre sur disque (même format que `snapshot_json`)
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.snapshot_json()).context("Failed to write registry file")?;
+        Ok(())
+    }
 
-        serde_json::to_string_pretty(&snap).unwrap_or_else(|_| "{}".into())
+    /// Recharge un registre sauvegardé, en ignorant les nœuds déjà expirés après `ttl`.
+    /// Certificat et admission ne sont pas repris du fichier: chaque nœud reste hors du quorum
+    /// et de l'ordonnancement jusqu'à sa prochaine annonce vérifiée.
+    pub fn load_from(path: &Path, ttl: Duration) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let snap: Snapshot = serde_json::from_str(&content)
+            .context("Failed to parse registry file")?;
+
+        let elapsed_since_save = unix_now().saturating_sub(snap.timestamp);
+        let now = Instant::now();
+        let mut registry = Registry {
+            successors: snap.successors,
+            ..Registry::with_ttl(ttl)
+        };
+        for (node_id, node) in snap.nodes {
+            let age = node.last_seen_secs_ago.saturating_add(elapsed_since_save);
+            if age > ttl.as_secs() {
+                continue;
+            }
+            let last_seen = now.checked_sub(Duration::from_secs(age)).unwrap_or(now);
+            registry.nodes.insert(node_id, NodeEntry {
+                last_seen,
+                shards: node.shards,
+                vram_free_mb: node.vram_free_mb,
+                ram_free_mb: node.ram_free_mb,
+                load: node.load,
+                latency_ms: None,
+                trust: None,
+                admitted: false,
+                addrs: node.addrs,
+            });
+        }
+        Ok(registry)
     }
 }
+
+/// Format JSON du snapshot (debug, API et persistance)
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    timestamp: u64,
+    nodes: HashMap<String, NodeEntryJson>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    successors: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct NodeEntryJson {
+    shards: Vec<ShardInfo>,
+    vram_free_mb: u32,
+    #[serde(default)]
+    ram_free_mb: u32,
+    #[serde(default)]
+    load: u32,
+    last_seen_secs_ago: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    trust: Option<TrustInfo>,
+    /// Absent des snapshots antérieurs au coût d'admission
+    #[serde(default = "default_admitted")]
+    admitted: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    addrs: Vec<String>,
+}
+
+fn default_admitted() -> bool {
+    true
+}
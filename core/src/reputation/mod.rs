@@ -0,0 +1,421 @@
+// src/reputation/mod.rs
+//! Réputation des nœuds: disponibilité, succès des RPC, percentiles de latence, désaccords de
+//! quorum et messages invalides, observés localement pour chaque PeerId. Les compteurs
+//! décroissent avec une demi-vie: un nœud fautif se rachète, un bon historique s'use.
+//! Le score qui en résulte pondère l'ordonnanceur, alimente le score applicatif de gossipsub
+//! et l'API; il est persisté localement et peut être partagé en attestation signée.
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use libp2p::identity::{ed25519, PeerId, PublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::quorum::DissentReporter;
+use crate::signing::{unix_now, verify_signed_by};
+
+pub const DEFAULT_HALF_LIFE_SECS: u64 = 6 * 3600;
+pub const DEFAULT_MIN_SCORE: f32 = 0.2;
+/// Latences conservées par pair pour les percentiles
+const LATENCY_WINDOW: usize = 128;
+/// Messages invalides qui divisent le score par deux
+const INVALID_HALVING: f64 = 4.0;
+/// Un pair absent depuis ce nombre de demi-vies est oublié
+const FORGET_AFTER_HALF_LIVES: u64 = 8;
+/// Amplitude du score applicatif gossipsub: un score nul atteint le seuil de mise à l'écart
+pub const GOSSIP_SCORE_SCALE: f64 = 200.0;
+const SIGNING_DOMAIN: &str = "cortex-reputation/v1";
+
+/// Section `reputation:` de la configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReputationConfig {
+    /// Demi-vie des observations
+    pub half_life_secs: u64,
+    /// Score en deçà duquel l'ordonnanceur écarte un nœud
+    pub min_score: f32,
+    /// Reporter les scores dans le score applicatif de gossipsub
+    pub gossip_scoring: bool,
+    /// Publier avec les annonces une attestation signée des scores de ce nœud
+    pub share_attestations: bool,
+}
+
+impl Default for ReputationConfig {
+    fn default() -> Self {
+        ReputationConfig {
+            half_life_secs: DEFAULT_HALF_LIFE_SECS,
+            min_score: DEFAULT_MIN_SCORE,
+            gossip_scoring: true,
+            share_attestations: false,
+        }
+    }
+}
+
+impl ReputationConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.half_life_secs == 0 {
+            bail!("reputation.half_life_secs must be positive");
+        }
+        if !(0.0..=1.0).contains(&self.min_score) {
+            bail!("reputation.min_score must be in [0, 1], got {}", self.min_score);
+        }
+        Ok(())
+    }
+}
+
+/// Fait observé sur un pair
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Observation {
+    /// Relevé périodique: le pair est-il au registre ?
+    Presence(bool),
+    RpcSuccess { latency: Duration },
+    RpcFailure,
+    QuorumAgreement,
+    QuorumDissent,
+    /// Message mal formé, signature invalide ou preuve refusée
+    InvalidMessage,
+}
+
+/// Succès et total, décroissants
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Ratio {
+    pub good: f64,
+    pub total: f64,
+}
+
+impl Ratio {
+    fn record(&mut self, ok: bool) {
+        self.total += 1.0;
+        if ok {
+            self.good += 1.0;
+        }
+    }
+
+    fn decay(&mut self, factor: f64) {
+        self.good *= factor;
+        self.total *= factor;
+    }
+
+    /// Estimation lissée: 1/2 sans observation, puis tend vers la proportion de succès
+    pub fn estimate(&self) -> f64 {
+        (self.good + 1.0) / (self.total + 2.0)
+    }
+}
+
+/// Observations cumulées sur un pair
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeerRecord {
+    /// Première observation (secondes UNIX)
+    #[serde(default)]
+    pub first_seen: u64,
+    /// Dernière décroissance appliquée (secondes UNIX)
+    pub updated_at: u64,
+    /// Dernier relevé où le pair était présent (secondes UNIX)
+    #[serde(default)]
+    pub last_present: u64,
+    pub uptime: Ratio,
+    pub rpc: Ratio,
+    pub quorum: Ratio,
+    pub invalid_messages: f64,
+    /// Latences RPC récentes (ms), de la plus ancienne à la plus récente
+    pub latencies_ms: VecDeque<f32>,
+}
+
+impl PeerRecord {
+    fn new(now: u64) -> Self {
+        PeerRecord { first_seen: now, updated_at: now, ..Self::default() }
+    }
+
+    /// Applique la décroissance écoulée jusqu'à `now`
+    fn decay_to(&mut self, now: u64, half_life_secs: u64) {
+        let elapsed = now.saturating_sub(self.updated_at);
+        if elapsed > 0 {
+            let factor = 0.5f64.powf(elapsed as f64 / half_life_secs as f64);
+            self.uptime.decay(factor);
+            self.rpc.decay(factor);
+            self.quorum.decay(factor);
+            self.invalid_messages *= factor;
+        }
+        self.updated_at = self.updated_at.max(now);
+    }
+
+    fn apply(&mut self, observation: Observation, now: u64) {
+        match observation {
+            Observation::Presence(present) => {
+                self.uptime.record(present);
+                if present {
+                    self.last_present = now;
+                }
+            },
+            Observation::RpcSuccess { latency } => {
+                self.rpc.record(true);
+                if self.latencies_ms.len() == LATENCY_WINDOW {
+                    self.latencies_ms.pop_front();
+                }
+                self.latencies_ms.push_back(latency.as_secs_f32() * 1000.0);
+            },
+            Observation::RpcFailure => self.rpc.record(false),
+            Observation::QuorumAgreement => self.quorum.record(true),
+            Observation::QuorumDissent => self.quorum.record(false),
+            Observation::InvalidMessage => self.invalid_messages += 1.0,
+        }
+    }
+
+    /// Score dans [0, 1]: fiabilité (disponibilité, RPC, quorum), divisée par deux tous les
+    /// `INVALID_HALVING` messages invalides. Un pair inconnu vaut 1/2.
+    pub fn score(&self) -> f32 {
+        let reliability = 0.3 * self.uptime.estimate() + 0.3 * self.rpc.estimate() + 0.4 * self.quorum.estimate();
+        (reliability * 0.5f64.powf(self.invalid_messages / INVALID_HALVING)) as f32
+    }
+
+    /// Percentile `p` (0-100) des latences récentes
+    pub fn latency_percentile(&self, p: f32) -> Option<f32> {
+        if self.latencies_ms.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<f32> = self.latencies_ms.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+        let rank = ((p.clamp(0.0, 100.0) / 100.0) * (sorted.len() - 1) as f32).round() as usize;
+        Some(sorted[rank])
+    }
+
+    fn view(&self, peer: &str) -> PeerScore {
+        PeerScore {
+            peer: peer.to_string(),
+            score: self.score(),
+            uptime: self.uptime.estimate() as f32,
+            rpc_success: self.rpc.estimate() as f32,
+            quorum_agreement: self.quorum.estimate() as f32,
+            invalid_messages: self.invalid_messages as f32,
+            latency_p50_ms: self.latency_percentile(50.0),
+            latency_p95_ms: self.latency_percentile(95.0),
+            latency_p99_ms: self.latency_percentile(99.0),
+        }
+    }
+}
+
+/// Score d'un pair et ses composantes (API, attestations)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerScore {
+    pub peer: String,
+    pub score: f32,
+    pub uptime: f32,
+    pub rpc_success: f32,
+    pub quorum_agreement: f32,
+    pub invalid_messages: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_p50_ms: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_p95_ms: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_p99_ms: Option<f32>,
+}
+
+/// Format JSON de la persistance
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    peers: HashMap<String, PeerRecord>,
+}
+
+/// Réputation des pairs vue par ce nœud, partagée entre la boucle du swarm, l'API et les
+/// coordinateurs d'inférence
+#[derive(Debug, Default)]
+pub struct Reputation {
+    config: ReputationConfig,
+    peers: Mutex<HashMap<String, PeerRecord>>,
+    /// Dernière attestation reçue de chaque émetteur
+    attestations: Mutex<HashMap<String, ReputationAttestation>>,
+}
+
+impl Reputation {
+    pub fn new(config: ReputationConfig) -> Self {
+        Reputation { config, ..Self::default() }
+    }
+
+    pub fn config(&self) -> &ReputationConfig {
+        &self.config
+    }
+
+    pub fn observe(&self, peer: &str, observation: Observation) {
+        self.observe_at(peer, observation, unix_now());
+    }
+
+    /// Enregistre une observation datée de `now` (secondes UNIX)
+    pub fn observe_at(&self, peer: &str, observation: Observation, now: u64) {
+        if let Ok(mut peers) = self.peers.lock() {
+            let record = peers.entry(peer.to_string()).or_insert_with(|| PeerRecord::new(now));
+            record.decay_to(now, self.config.half_life_secs);
+            record.apply(observation, now);
+        }
+    }
+
+    /// Relevé de disponibilité: présents ceux de `present`, absents les autres pairs connus.
+    /// Les pairs absents depuis trop longtemps sont oubliés.
+    pub fn record_presence(&self, present: &[String]) {
+        self.record_presence_at(present, unix_now());
+    }
+
+    pub fn record_presence_at(&self, present: &[String], now: u64) {
+        let Ok(mut peers) = self.peers.lock() else {
+            return;
+        };
+        for peer in present {
+            peers.entry(peer.clone()).or_insert_with(|| PeerRecord::new(now));
+        }
+        let horizon = FORGET_AFTER_HALF_LIVES * self.config.half_life_secs;
+        peers.retain(|peer, record| {
+            let here = present.contains(peer);
+            if !here && now.saturating_sub(record.last_present.max(record.first_seen)) > horizon {
+                return false;
+            }
+            record.decay_to(now, self.config.half_life_secs);
+            record.apply(Observation::Presence(here), now);
+            true
+        });
+    }
+
+    /// Score courant d'un pair (1/2 s'il est inconnu)
+    pub fn score(&self, peer: &str) -> f32 {
+        self.record(peer).map_or(PeerRecord::default().score(), |r| r.score())
+    }
+
+    /// Observations d'un pair, décrues jusqu'à maintenant
+    pub fn record(&self, peer: &str) -> Option<PeerRecord> {
+        let mut record = self.peers.lock().ok()?.get(peer).cloned()?;
+        record.decay_to(unix_now(), self.config.half_life_secs);
+        Some(record)
+    }
+
+    /// Scores de tous les pairs connus, du meilleur au moins bon (puis par PeerId)
+    pub fn scores(&self) -> Vec<PeerScore> {
+        self.scores_at(unix_now())
+    }
+
+    pub fn scores_at(&self, now: u64) -> Vec<PeerScore> {
+        let Ok(peers) = self.peers.lock() else {
+            return Vec::new();
+        };
+        let mut scores: Vec<PeerScore> = peers.iter()
+            .map(|(peer, record)| {
+                let mut record = record.clone();
+                record.decay_to(now, self.config.half_life_secs);
+                record.view(peer)
+            })
+            .collect();
+        scores.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.peer.cmp(&b.peer)));
+        scores
+    }
+
+    /// Score applicatif gossipsub: 0 pour un pair inconnu, `-GOSSIP_SCORE_SCALE / 2` au pire
+    pub fn gossip_score(&self, peer: &str) -> f64 {
+        (self.score(peer) as f64 - 0.5) * GOSSIP_SCORE_SCALE
+    }
+
+    /// Transfère l'historique d'une identité remplacée par une succession de clé
+    pub fn migrate(&self, old: &str, new: &str) -> bool {
+        let Ok(mut peers) = self.peers.lock() else {
+            return false;
+        };
+        match peers.remove(old) {
+            Some(record) => {
+                peers.entry(new.to_string()).or_insert(record);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Retient l'attestation d'un émetteur si elle est plus récente que la précédente
+    pub fn store_attestation(&self, attestation: ReputationAttestation) -> bool {
+        let Ok(mut attestations) = self.attestations.lock() else {
+            return false;
+        };
+        let newer = attestations.get(&attestation.issuer)
+            .map_or(true, |current| attestation.issued_at > current.issued_at);
+        if newer {
+            attestations.insert(attestation.issuer.clone(), attestation);
+        }
+        newer
+    }
+
+    /// Attestations reçues, par émetteur
+    pub fn attestations(&self) -> Vec<ReputationAttestation> {
+        let mut list: Vec<ReputationAttestation> = self.attestations.lock()
+            .map(|a| a.values().cloned().collect())
+            .unwrap_or_default();
+        list.sort_by(|a, b| a.issuer.cmp(&b.issuer));
+        list
+    }
+
+    /// Sauvegarde les observations (les attestations reçues ne sont pas conservées)
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        let peers = self.peers.lock().map_err(|_| anyhow!("Reputation lock poisoned"))?.clone();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(&Snapshot { peers })?;
+        fs::write(path, json).context("Failed to write reputation file")?;
+        Ok(())
+    }
+
+    /// Recharge des observations sauvegardées; la décroissance reprend depuis leur date
+    pub fn load_from(path: &Path, config: ReputationConfig) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let snapshot: Snapshot = serde_json::from_str(&content).context("Failed to parse reputation file")?;
+        Ok(Reputation { config, peers: Mutex::new(snapshot.peers), ..Self::default() })
+    }
+}
+
+impl DissentReporter for Reputation {
+    fn report_dissent(&self, peer: &PeerId, _shard_id: &str) {
+        self.observe(&peer.to_string(), Observation::QuorumDissent);
+    }
+
+    fn report_agreement(&self, peer: &PeerId, _shard_id: &str) {
+        self.observe(&peer.to_string(), Observation::QuorumAgreement);
+    }
+}
+
+/// Scores publiés et signés par un nœud, pour informer les autres de ce qu'il a observé
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReputationAttestation {
+    pub issuer: String,
+    /// Clé publique ed25519 de l'émetteur (base64)
+    pub public_key: String,
+    /// Date d'émission (secondes UNIX)
+    pub issued_at: u64,
+    pub scores: Vec<PeerScore>,
+    pub signature: String,
+}
+
+impl ReputationAttestation {
+    pub fn sign(identity: &ed25519::Keypair, scores: Vec<PeerScore>) -> Self {
+        let public = PublicKey::from(identity.public());
+        let mut attestation = ReputationAttestation {
+            issuer: PeerId::from_public_key(&public).to_string(),
+            public_key: STANDARD.encode(identity.public().to_bytes()),
+            issued_at: unix_now(),
+            scores,
+            signature: String::new(),
+        };
+        attestation.signature = STANDARD.encode(identity.sign(&attestation.signing_bytes()));
+        attestation
+    }
+
+    /// Vérifie la clé de l'émetteur et la signature des scores
+    pub fn verify(&self) -> Result<()> {
+        verify_signed_by(&self.issuer, &self.public_key, &self.signing_bytes(), &self.signature)
+            .context("Invalid reputation attestation")
+    }
+
+    fn signing_bytes(&self) -> Vec<u8> {
+        let scores = serde_json::to_vec(&self.scores).unwrap_or_default();
+        let digest: String = Sha256::digest(scores).iter().map(|b| format!("{:02x}", b)).collect();
+        format!("{}\n{}\n{}\n{}", SIGNING_DOMAIN, self.issuer, self.issued_at, digest).into_bytes()
+    }
+}
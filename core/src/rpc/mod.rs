@@ -0,0 +1,101 @@
+// src/rpc/mod.rs
+//! RPC direct entre deux nœuds (requête/réponse libp2p, encodage JSON), pour les échanges
+//! point à point trop volumineux ou trop fréquents pour le gossip: activations des pipelines
+//! et des experts MoE, répliques de la validation croisée. Le JSON ne porte que le contrôle:
+//! les activations voyagent en trames binaires sur le flux de tenseurs de la requête.
+use libp2p::request_response::{self, ProtocolSupport};
+use libp2p::StreamProtocol;
+use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+
+use crate::executor::Tensor;
+use crate::moe::ExpertRequest;
+use crate::pipeline::{StageRequest, StageUnavailable};
+use crate::quorum::ReplicaRequest;
+use crate::topics::TopicNamespace;
+
+/// Version du protocole RPC, indépendante de celle des topics
+pub const RPC_VERSION: u32 = 2;
+/// Délai de réponse: couvre l'exécution du shard et celle des étapes suivantes du pipeline
+pub const RPC_TIMEOUT_SECS: u64 = 120;
+
+/// Requête adressée à un pair
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RpcRequest {
+    /// Étape de pipeline: exécuter un shard puis transmettre à l'étape suivante
+    Stage(StageRequest),
+    /// Expert d'une couche MoE: exécuter un lot de tokens
+    Expert(ExpertRequest),
+    /// Réplique d'une validation croisée: exécuter un shard depuis un état vide
+    Replica(ReplicaRequest),
+}
+
+impl RpcRequest {
+    /// Activations, transmises en trame à part de la requête de contrôle
+    pub fn input_mut(&mut self) -> &mut Tensor {
+        match self {
+            RpcRequest::Stage(stage) => &mut stage.input,
+            RpcRequest::Expert(expert) => &mut expert.input,
+            RpcRequest::Replica(replica) => &mut replica.input,
+        }
+    }
+}
+
+/// Requête de contrôle sur le fil; ses activations suivent sur le flux de tenseurs `request_id`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcCall {
+    pub request_id: u64,
+    pub request: RpcRequest,
+}
+
+/// Réponse de contrôle sur le fil; le tenseur de sortie suit sur le flux de tenseurs de la requête
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RpcOutcome {
+    Tensor,
+    Error {
+        message: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unavailable: Option<StageUnavailable>,
+    },
+}
+
+/// Réponse d'un pair
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RpcResponse {
+    /// Sortie de la dernière étape (logits ou états cachés)
+    Tensor(Tensor),
+    Error {
+        message: String,
+        /// Étape injoignable plus loin dans la chaîne, que l'origine peut remplacer
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unavailable: Option<StageUnavailable>,
+    },
+}
+
+impl RpcOutcome {
+    /// Réponse d'échec, qui conserve l'étape injoignable à l'origine de l'erreur
+    pub fn error(error: &anyhow::Error) -> Self {
+        RpcOutcome::Error {
+            message: format!("{:#}", error),
+            unavailable: error.downcast_ref::<StageUnavailable>().cloned(),
+        }
+    }
+}
+
+pub type RpcBehaviour = request_response::json::Behaviour<RpcCall, RpcOutcome>;
+pub type RpcEvent = request_response::Event<RpcCall, RpcOutcome>;
+
+/// Nom du protocole, propre au mesh: deux mesh distincts ne s'échangent pas de requêtes
+pub fn protocol(namespace: &TopicNamespace) -> StreamProtocol {
+    let name = format!("/cortex/{}/rpc/{}", namespace.mesh(), RPC_VERSION);
+    StreamProtocol::try_from_owned(name).unwrap_or(StreamProtocol::new("/cortex/rpc/2"))
+}
+
+pub fn build_rpc_behaviour(namespace: &TopicNamespace) -> RpcBehaviour {
+    let config = request_response::Config::default()
+        .with_request_timeout(Duration::from_secs(RPC_TIMEOUT_SECS));
+    RpcBehaviour::new([(protocol(namespace), ProtocolSupport::Full)], config)
+}
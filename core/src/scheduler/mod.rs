@@ -0,0 +1,343 @@
+// src/scheduler/mod.rs
+//! Ordonnanceur: répartit les couches et les experts d'un modèle sur les nœuds du registre,
+//! dans la limite de leur mémoire, en minimisant la latence attendue. Le calcul ne dépend que
+//! du manifeste et des ressources des nœuds (aucun aléa, ordres de tri totaux): deux routeurs
+//! qui voient les mêmes entrées produisent le même plan, qu'ils publient signé.
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use libp2p::identity::{ed25519, PeerId, PublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::moe::expert_shard_id;
+use crate::pipeline::{PipelinePlan, PipelineStage};
+use crate::registry::Registry;
+use crate::reputation::Reputation;
+use crate::signing::{unix_now, verify_signed_by};
+
+/// Latence supposée d'un nœud jamais mesuré
+pub const DEFAULT_LATENCY_MS: f32 = 50.0;
+const SIGNING_DOMAIN: &str = "cortex-schedule-plan/v1";
+/// Rôle de certificat requis pour qu'un plan reçu soit retenu
+pub const ROUTER_ROLE: &str = "router";
+/// Avance maximale tolérée sur la date d'émission d'un plan reçu (horloges décalées)
+pub const MAX_PLAN_CLOCK_SKEW_SECS: u64 = 30;
+
+/// Description d'un modèle à répartir
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelManifest {
+    pub model: String,
+    pub num_layers: usize,
+    /// Mémoire d'une couche (Mo), experts exclus
+    pub layer_memory_mb: u64,
+    /// Experts par couche (0 pour un modèle dense)
+    #[serde(default)]
+    pub experts_per_layer: usize,
+    #[serde(default)]
+    pub expert_memory_mb: u64,
+    /// Organisation dont les nœuds doivent être certifiés (aucune: tout nœud admis)
+    #[serde(default)]
+    pub trust_domain: Option<String>,
+}
+
+impl ModelManifest {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+        let manifest: ModelManifest = serde_json::from_str(&content).context("Failed to parse model manifest")?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.model.is_empty() || self.num_layers == 0 || self.layer_memory_mb == 0 {
+            bail!("Model manifest needs a name, layers and a layer size");
+        }
+        if self.experts_per_layer > 0 && self.expert_memory_mb == 0 {
+            bail!("Model manifest needs an expert size");
+        }
+        Ok(())
+    }
+}
+
+/// Ressources d'un nœud candidat, tirées du registre
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeCapacity {
+    pub peer: String,
+    /// Mémoire libre annoncée, RAM et VRAM (Mo)
+    pub memory_mb: u64,
+    pub latency_ms: f32,
+    pub load: u32,
+    /// Score de réputation du nœud (1 si l'ordonnancement n'en tient pas compte)
+    #[serde(default = "full_reliability")]
+    pub reliability: f32,
+}
+
+fn full_reliability() -> f32 {
+    1.0
+}
+
+impl NodeCapacity {
+    /// Coût attendu d'un passage par ce nœud: sa latence, majorée par sa charge et divisée
+    /// par sa fiabilité
+    pub fn cost_ms(&self) -> f32 {
+        self.latency_ms * (1 + self.load) as f32 / self.reliability.max(f32::EPSILON)
+    }
+}
+
+/// Nœuds admis (et certifiés par `trust_domain` s'il est donné), triés par PeerId
+pub fn capacities(registry: &Registry, trust_domain: Option<&str>) -> Vec<NodeCapacity> {
+    let certified = trust_domain.map(|domain| registry.in_trust_domain(domain, None));
+    registry.eligible_nodes()
+        .into_iter()
+        .filter(|id| certified.as_ref().map_or(true, |ids| ids.contains(id)))
+        .filter_map(|id| {
+            let entry = registry.nodes.get(&id)?;
+            Some(NodeCapacity {
+                memory_mb: entry.ram_free_mb as u64 + entry.vram_free_mb as u64,
+                latency_ms: entry.latency_ms.unwrap_or(DEFAULT_LATENCY_MS),
+                load: entry.load,
+                reliability: full_reliability(),
+                peer: id,
+            })
+        })
+        .collect()
+}
+
+/// Pondère les candidats par leur réputation vue de ce nœud et écarte ceux dont le score est
+/// sous `min_score`. Le plan dépend alors des observations locales: deux routeurs n'aboutissent
+/// au même plan que s'ils partagent les mêmes scores.
+pub fn weigh_by_reputation(nodes: Vec<NodeCapacity>, reputation: &Reputation) -> Vec<NodeCapacity> {
+    let min_score = reputation.config().min_score;
+    nodes.into_iter()
+        .filter_map(|node| {
+            let score = reputation.score(&node.peer);
+            (score >= min_score).then_some(NodeCapacity { reliability: score, ..node })
+        })
+        .collect()
+}
+
+/// Expert confié à un nœud
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpertPlacement {
+    pub peer: String,
+    pub shard_id: String,
+    pub layer: usize,
+    pub expert: usize,
+}
+
+/// Affectation des couches (étapes de pipeline) et des experts d'un modèle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchedulePlan {
+    pub model: String,
+    pub stages: Vec<PipelineStage>,
+    #[serde(default)]
+    pub experts: Vec<ExpertPlacement>,
+    /// Somme des coûts des étapes, plus l'expert le plus lent de chaque couche
+    pub expected_latency_ms: f32,
+}
+
+impl SchedulePlan {
+    pub fn pipeline(&self) -> PipelinePlan {
+        PipelinePlan { model: self.model.clone(), stages: self.stages.clone() }
+    }
+
+    /// Shards qu'un nœud doit charger pour ce plan
+    pub fn shards_for(&self, peer: &str) -> Vec<String> {
+        self.stages.iter().filter(|s| s.peer == peer).map(|s| s.shard_id.clone())
+            .chain(self.experts.iter().filter(|e| e.peer == peer).map(|e| e.shard_id.clone()))
+            .collect()
+    }
+
+    /// Le plan confie-t-il au moins un shard à `peer` ?
+    pub fn involves(&self, peer: &str) -> bool {
+        self.stages.iter().any(|s| s.peer == peer) || self.experts.iter().any(|e| e.peer == peer)
+    }
+
+    /// Confie à `new` les shards de `old` (succession de clé); `true` si le plan a changé
+    pub fn replace_peer(&mut self, old: &str, new: &str) -> bool {
+        let peers = self.stages.iter_mut().map(|s| &mut s.peer).chain(self.experts.iter_mut().map(|e| &mut e.peer));
+        let mut changed = false;
+        for peer in peers.filter(|peer| *peer == old) {
+            *peer = new.to_string();
+            changed = true;
+        }
+        changed
+    }
+
+    /// Empreinte SHA-256 (hex) de la forme JSON du plan, identique chez tous les routeurs
+    pub fn digest(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        Sha256::digest(json).iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+/// Calcule le plan d'un modèle d'après le registre
+pub fn schedule_from_registry(manifest: &ModelManifest, registry: &Registry) -> Result<SchedulePlan> {
+    schedule(manifest, &capacities(registry, manifest.trust_domain.as_deref()))
+}
+
+/// Couches: le sous-ensemble de nœuds de coût total minimal dont la mémoire couvre le modèle,
+/// chacun recevant une plage contiguë. Experts: répartis couche par couche sur les nœuds qui
+/// ont encore de la place, le moins chargé en experts de la couche d'abord.
+pub fn schedule(manifest: &ModelManifest, nodes: &[NodeCapacity]) -> Result<SchedulePlan> {
+    manifest.validate()?;
+    let mut order: Vec<&NodeCapacity> = nodes.iter().collect();
+    order.sort_by(|a, b| {
+        a.cost_ms().total_cmp(&b.cost_ms())
+            .then(b.memory_mb.cmp(&a.memory_mb))
+            .then_with(|| a.peer.cmp(&b.peer))
+    });
+    let layers = manifest.num_layers;
+    let fits: Vec<usize> = order.iter()
+        .map(|n| ((n.memory_mb / manifest.layer_memory_mb) as usize).min(layers))
+        .collect();
+
+    // best[i][j]: coût minimal pour couvrir j couches avec les i premiers nœuds
+    let mut best = vec![vec![f32::INFINITY; layers + 1]; order.len() + 1];
+    best[0][0] = 0.0;
+    for (i, node) in order.iter().enumerate() {
+        best[i + 1] = best[i].clone();
+        if fits[i] == 0 {
+            continue;
+        }
+        for covered in 0..=layers {
+            let cost = best[i][covered] + node.cost_ms();
+            let reach = (covered + fits[i]).min(layers);
+            if cost < best[i + 1][reach] {
+                best[i + 1][reach] = cost;
+            }
+        }
+    }
+    let total = best[order.len()][layers];
+    if !total.is_finite() {
+        bail!("Not enough memory on eligible nodes for the {} layers of {}", layers, manifest.model);
+    }
+
+    // Reconstitution des nœuds retenus, puis plages contiguës dans l'ordre de coût
+    let mut chosen = Vec::new();
+    let mut covered = layers;
+    for i in (0..order.len()).rev() {
+        if covered == 0 {
+            break;
+        }
+        if best[i + 1][covered] == best[i][covered] {
+            continue;
+        }
+        chosen.push(i);
+        covered = (0..covered).rev()
+            .find(|&from| (from + fits[i]).min(layers) == covered && best[i][from] + order[i].cost_ms() == best[i + 1][covered])
+            .ok_or_else(|| anyhow!("Inconsistent schedule table"))?;
+    }
+    chosen.reverse();
+
+    let mut memory: Vec<u64> = order.iter().map(|n| n.memory_mb).collect();
+    let mut stages = Vec::new();
+    let mut next = 0;
+    for &i in &chosen {
+        let count = fits[i].min(layers - next);
+        if count == 0 {
+            continue;
+        }
+        let (first, last) = (next, next + count - 1);
+        stages.push(PipelineStage {
+            peer: order[i].peer.clone(),
+            shard_id: format!("{}/layers-{}-{}", manifest.model, first, last),
+            first_layer: first,
+            last_layer: last,
+        });
+        memory[i] -= count as u64 * manifest.layer_memory_mb;
+        next += count;
+    }
+    let mut expected = stages.iter()
+        .filter_map(|s| order.iter().find(|n| n.peer == s.peer))
+        .map(|n| n.cost_ms())
+        .sum::<f32>();
+
+    let mut experts = Vec::new();
+    for layer in 0..layers {
+        if manifest.experts_per_layer == 0 {
+            break;
+        }
+        let mut per_node = vec![0usize; order.len()];
+        let mut slowest = 0.0f32;
+        for expert in 0..manifest.experts_per_layer {
+            let i = (0..order.len())
+                .filter(|&i| memory[i] >= manifest.expert_memory_mb)
+                .min_by_key(|&i| per_node[i])
+                .ok_or_else(|| anyhow!("Not enough memory for expert {} of layer {}", expert, layer))?;
+            memory[i] -= manifest.expert_memory_mb;
+            per_node[i] += 1;
+            slowest = slowest.max(order[i].cost_ms());
+            experts.push(ExpertPlacement {
+                peer: order[i].peer.clone(),
+                shard_id: expert_shard_id(&manifest.model, layer, expert),
+                layer,
+                expert,
+            });
+        }
+        expected += slowest;
+    }
+
+    Ok(SchedulePlan { model: manifest.model.clone(), stages, experts, expected_latency_ms: expected })
+}
+
+/// Plan signé par le routeur qui l'a calculé.
+/// Les autres nœuds ne le retiennent que si le signataire a un certificat valide avec le rôle `router`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedPlan {
+    pub plan: SchedulePlan,
+    pub digest: String,
+    pub signer: String,
+    /// Clé publique ed25519 du signataire (base64)
+    pub public_key: String,
+    /// Date d'émission (secondes UNIX)
+    pub issued_at: u64,
+    pub signature: String,
+}
+
+impl SignedPlan {
+    pub fn sign(identity: &ed25519::Keypair, plan: SchedulePlan) -> Self {
+        Self::sign_at(identity, plan, unix_now())
+    }
+
+    /// Nouvelle version d'un plan déjà diffusé, datée au moins une seconde après lui pour le
+    /// remplacer partout, même émise dans la même seconde
+    pub fn reissue(&self, identity: &ed25519::Keypair, plan: SchedulePlan) -> Self {
+        Self::sign_at(identity, plan, unix_now().max(self.issued_at + 1))
+    }
+
+    fn sign_at(identity: &ed25519::Keypair, plan: SchedulePlan, issued_at: u64) -> Self {
+        let public = PublicKey::from(identity.public());
+        let mut signed = SignedPlan {
+            digest: plan.digest(),
+            plan,
+            signer: PeerId::from_public_key(&public).to_string(),
+            public_key: STANDARD.encode(identity.public().to_bytes()),
+            issued_at,
+            signature: String::new(),
+        };
+        signed.signature = STANDARD.encode(identity.sign(&signed.signing_bytes()));
+        signed
+    }
+
+    /// Vérifie l'empreinte du plan, la clé du signataire et la signature
+    pub fn verify(&self) -> Result<()> {
+        if self.plan.digest() != self.digest {
+            bail!("Plan digest mismatch");
+        }
+        verify_signed_by(&self.signer, &self.public_key, &self.signing_bytes(), &self.signature)
+            .context("Invalid plan signature")
+    }
+
+    /// Date d'émission trop en avance sur `now` pour venir d'une horloge simplement décalée
+    pub fn is_future_dated(&self, now: u64) -> bool {
+        self.issued_at > now + MAX_PLAN_CLOCK_SKEW_SECS
+    }
+
+    fn signing_bytes(&self) -> Vec<u8> {
+        format!("{}\n{}\n{}\n{}", SIGNING_DOMAIN, self.plan.model, self.digest, self.issued_at).into_bytes()
+    }
+}
@@ -0,0 +1,29 @@
+// src/shutdown/mod.rs
+use tokio::signal;
+
+/// Attend SIGINT (Ctrl-C) ou SIGTERM (`docker stop`)
+pub async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal as unix_signal, SignalKind};
+
+        match unix_signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = signal::ctrl_c() => println!("🛑 SIGINT reçu"),
+                    _ = sigterm.recv() => println!("🛑 SIGTERM reçu"),
+                }
+            }
+            Err(e) => {
+                println!("⚠️ Impossible d'écouter SIGTERM: {:?}", e);
+                let _ = signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = signal::ctrl_c().await;
+        println!("🛑 Ctrl-C reçu");
+    }
+}
@@ -0,0 +1,42 @@
+// src/signing/mod.rs
+//! Outils communs aux documents signés diffusés sur le mesh (successions, certificats,
+//! plans, attestations): horodatage et vérification d'une signature ed25519.
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use libp2p::identity::{ed25519, PeerId, PublicKey};
+
+/// Date courante en secondes UNIX
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Décode une clé publique ed25519 encodée en base64
+pub fn decode_public_key(encoded: &str) -> Result<ed25519::PublicKey> {
+    let bytes = STANDARD.decode(encoded.trim())?;
+    ed25519::PublicKey::try_from_bytes(&bytes).map_err(|e| anyhow!("Invalid ed25519 public key: {:?}", e))
+}
+
+/// Vérifie la signature base64 de `payload` par la clé `public_key` (base64)
+pub fn verify_signature(public_key: &str, payload: &[u8], signature: &str) -> Result<()> {
+    let key = decode_public_key(public_key)?;
+    if !key.verify(payload, &STANDARD.decode(signature)?) {
+        bail!("Invalid signature");
+    }
+    Ok(())
+}
+
+/// Vérifie qu'un document est signé par le nœud `signer`: la clé jointe doit être celle de
+/// ce PeerId, et signer `payload` (déjà préfixé par le domaine de signature du document)
+pub fn verify_signed_by(signer: &str, public_key: &str, payload: &[u8], signature: &str) -> Result<()> {
+    let key = decode_public_key(public_key)?;
+    if PeerId::from_public_key(&PublicKey::from(key.clone())).to_string() != signer {
+        bail!("Public key does not match {}", signer);
+    }
+    if !key.verify(payload, &STANDARD.decode(signature)?) {
+        bail!("Invalid signature from {}", signer);
+    }
+    Ok(())
+}
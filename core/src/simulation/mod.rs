@@ -0,0 +1,438 @@
+// src/simulation/mod.rs
+//! Réseau simulé pour le banc de test: latence, pertes, partitions entre nœuds choisis.
+//!
+//! Les fautes s'appliquent au flux d'octets sous Noise/Yamux, côté dialer de chaque
+//! connexion (ce qui couvre les deux sens). Chaque connexion tire ses aléas d'un RNG
+//! dérivé de la graine du réseau, du lien et du rang de la connexion sur ce lien:
+//! un même scénario rejoue les mêmes délais, indépendamment de l'ordre des tâches.
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use anyhow::Result;
+use libp2p::core::transport::MemoryTransport;
+use libp2p::core::upgrade::Version;
+use libp2p::core::ConnectedPoint;
+use libp2p::futures::{AsyncRead, AsyncWrite};
+use libp2p::identity::Keypair;
+use libp2p::multiaddr::Protocol;
+use libp2p::{noise, yamux, Multiaddr, Transport};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use tokio::time::{sleep, Duration, Sleep};
+
+use crate::discovery::BoxedTransport;
+use crate::harness::TransportFactory;
+
+/// Nombre maximal de retransmissions simulées pour un même segment
+const MAX_RETRANSMITS: u32 = 8;
+const READ_CHUNK: usize = 16 * 1024;
+
+/// Lien non orienté entre deux ports mémoire
+type Link = (u64, u64);
+
+fn link(a: u64, b: u64) -> Link {
+    (a.min(b), a.max(b))
+}
+
+/// Fautes appliquées à un lien
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkFaults {
+    /// Délai ajouté à chaque segment, dans chaque sens
+    pub latency: Duration,
+    /// Probabilité (0.0 - 1.0) qu'un segment soit perdu puis retransmis
+    pub loss: f64,
+    /// Pénalité par retransmission (délai de détection de la perte)
+    pub retransmit_delay: Duration,
+}
+
+impl Default for LinkFaults {
+    fn default() -> Self {
+        LinkFaults {
+            latency: Duration::ZERO,
+            loss: 0.0,
+            retransmit_delay: Duration::from_millis(200),
+        }
+    }
+}
+
+impl LinkFaults {
+    pub fn with_latency(latency: Duration) -> Self {
+        LinkFaults { latency, ..Default::default() }
+    }
+
+    /// Délai d'un segment: latence fixe plus les retransmissions tirées au sort
+    fn sample_delay(&self, rng: &mut StdRng, stats: &mut SimStats) -> Duration {
+        let mut delay = self.latency;
+        let mut retransmits = 0;
+        while self.loss > 0.0 && retransmits < MAX_RETRANSMITS && rng.gen_bool(self.loss.min(1.0)) {
+            delay += self.retransmit_delay;
+            retransmits += 1;
+        }
+        stats.lost_segments += u64::from(retransmits);
+        if !delay.is_zero() {
+            stats.delayed_segments += 1;
+        }
+        delay
+    }
+}
+
+/// Compteurs de la simulation, pour vérifier qu'un scénario a bien injecté ses fautes
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SimStats {
+    pub dials_refused: u64,
+    pub connections_cut: u64,
+    pub delayed_segments: u64,
+    pub lost_segments: u64,
+}
+
+struct SimState {
+    seed: u64,
+    default_faults: LinkFaults,
+    link_faults: HashMap<Link, LinkFaults>,
+    partitions: HashSet<Link>,
+    /// Rang de la prochaine connexion sur chaque lien (dérivation des RNG)
+    connection_counts: HashMap<Link, u64>,
+    /// Flux en attente sur chaque lien, réveillés lors d'une partition
+    wakers: HashMap<Link, Vec<Waker>>,
+    stats: SimStats,
+}
+
+impl SimState {
+    fn faults(&self, link: Link) -> LinkFaults {
+        self.link_faults.get(&link).copied().unwrap_or(self.default_faults)
+    }
+
+    fn register_waker(&mut self, link: Link, waker: &Waker) {
+        let wakers = self.wakers.entry(link).or_default();
+        if !wakers.iter().any(|w| w.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+    }
+
+    fn wake_link(&mut self, link: Link) {
+        if let Some(wakers) = self.wakers.remove(&link) {
+            for waker in wakers {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// Réseau simulé partagé par tous les nœuds d'un banc
+#[derive(Clone)]
+pub struct SimNetwork {
+    state: Arc<Mutex<SimState>>,
+}
+
+impl SimNetwork {
+    /// Réseau parfait (aucune faute), dont tous les aléas dérivent de `seed`
+    pub fn new(seed: u64) -> Self {
+        SimNetwork {
+            state: Arc::new(Mutex::new(SimState {
+                seed,
+                default_faults: LinkFaults::default(),
+                link_faults: HashMap::new(),
+                partitions: HashSet::new(),
+                connection_counts: HashMap::new(),
+                wakers: HashMap::new(),
+                stats: SimStats::default(),
+            })),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SimState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Fautes appliquées aux liens sans configuration spécifique
+    pub fn set_default_faults(&self, faults: LinkFaults) {
+        self.lock().default_faults = faults;
+    }
+
+    /// Fautes d'un lien précis (ports mémoire des deux nœuds)
+    pub fn set_link_faults(&self, a: u64, b: u64, faults: LinkFaults) {
+        self.lock().link_faults.insert(link(a, b), faults);
+    }
+
+    /// Coupe tous les liens entre les deux groupes: connexions existantes rompues, dials refusés
+    pub fn partition(&self, group_a: &[u64], group_b: &[u64]) {
+        let mut state = self.lock();
+        for &a in group_a {
+            for &b in group_b {
+                let l = link(a, b);
+                state.partitions.insert(l);
+                state.wake_link(l);
+            }
+        }
+    }
+
+    /// Isole un nœud de tous les autres ports donnés
+    pub fn isolate(&self, node: u64, others: &[u64]) {
+        self.partition(&[node], others);
+    }
+
+    /// Rétablit les liens entre les deux groupes
+    pub fn heal(&self, group_a: &[u64], group_b: &[u64]) {
+        let mut state = self.lock();
+        for &a in group_a {
+            for &b in group_b {
+                state.partitions.remove(&link(a, b));
+            }
+        }
+    }
+
+    /// Rétablit tous les liens
+    pub fn heal_all(&self) {
+        self.lock().partitions.clear();
+    }
+
+    pub fn is_partitioned(&self, a: u64, b: u64) -> bool {
+        self.lock().partitions.contains(&link(a, b))
+    }
+
+    pub fn stats(&self) -> SimStats {
+        self.lock().stats.clone()
+    }
+
+    /// RNG d'une connexion, dérivé de la graine, du lien et du rang de la connexion
+    fn connection_rng(seed: u64, link: Link, ordinal: u64) -> StdRng {
+        let mut mixed = seed;
+        for v in [link.0, link.1, ordinal] {
+            mixed = mixed.rotate_left(17) ^ v.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        }
+        StdRng::seed_from_u64(mixed)
+    }
+
+    /// Premiers délais qu'une connexion tirerait sur un lien: permet de vérifier la
+    /// reproductibilité d'un scénario sans lancer de nœuds
+    pub fn delay_schedule(&self, a: u64, b: u64, ordinal: u64, segments: usize) -> Vec<Duration> {
+        let l = link(a, b);
+        let (seed, faults) = {
+            let state = self.lock();
+            (state.seed, state.faults(l))
+        };
+        let mut rng = Self::connection_rng(seed, l, ordinal);
+        let mut stats = SimStats::default();
+        (0..segments).map(|_| faults.sample_delay(&mut rng, &mut stats)).collect()
+    }
+
+    /// Transport mémoire du nœud écoutant sur `port`, soumis aux fautes du réseau
+    pub fn transport(&self, keypair: &Keypair, port: u64) -> Result<BoxedTransport> {
+        let net = self.clone();
+        Ok(MemoryTransport::default()
+            .and_then(move |conn, endpoint| {
+                let net = net.clone();
+                async move {
+                    let ConnectedPoint::Dialer { address, .. } = endpoint else {
+                        // Côté listener: le dialer applique les fautes pour les deux sens
+                        return Ok(FaultyStream::passthrough(conn));
+                    };
+                    let remote = memory_port(&address)
+                        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a memory address"))?;
+                    net.open(conn, port, remote).await
+                }
+            })
+            .upgrade(Version::V1)
+            .authenticate(noise::Config::new(keypair)?)
+            .multiplex(yamux::Config::default())
+            .boxed())
+    }
+
+    /// Fabrique de transport à passer au banc de test
+    pub fn transport_factory(&self) -> TransportFactory {
+        let net = self.clone();
+        Box::new(move |keypair, port| net.transport(keypair, port))
+    }
+
+    /// Établit une connexion sortante: refus si partition, délai d'établissement sinon
+    async fn open<S>(&self, conn: S, local: u64, remote: u64) -> io::Result<FaultyStream<S>> {
+        let l = link(local, remote);
+        let (rng, setup_delay) = {
+            let mut state = self.lock();
+            if state.partitions.contains(&l) {
+                state.stats.dials_refused += 1;
+                return Err(io::Error::new(io::ErrorKind::ConnectionRefused, "simulated partition"));
+            }
+            let ordinal = {
+                let count = state.connection_counts.entry(l).or_insert(0);
+                *count += 1;
+                *count - 1
+            };
+            let mut rng = Self::connection_rng(state.seed, l, ordinal);
+            let faults = state.faults(l);
+            let delay = faults.sample_delay(&mut rng, &mut state.stats);
+            (rng, delay)
+        };
+        if !setup_delay.is_zero() {
+            sleep(setup_delay).await;
+        }
+        Ok(FaultyStream {
+            inner: conn,
+            fault: Some(StreamFaults {
+                net: self.clone(),
+                link: l,
+                rng,
+                read_delay: None,
+                write_delay: WriteGate::Idle,
+                cut: false,
+            }),
+            read_buf: Vec::new(),
+            read_pos: 0,
+        })
+    }
+}
+
+/// Extrait le port d'une adresse `/memory/<port>`
+fn memory_port(addr: &Multiaddr) -> Option<u64> {
+    addr.iter().find_map(|p| match p {
+        Protocol::Memory(port) => Some(port),
+        _ => None,
+    })
+}
+
+enum WriteGate {
+    Idle,
+    Waiting(Pin<Box<Sleep>>),
+    Open,
+}
+
+struct StreamFaults {
+    net: SimNetwork,
+    link: Link,
+    rng: StdRng,
+    read_delay: Option<Pin<Box<Sleep>>>,
+    write_delay: WriteGate,
+    cut: bool,
+}
+
+impl StreamFaults {
+    /// Vérifie la partition du lien; enregistre le waker pour être réveillé à la coupure
+    fn check(&mut self, cx: &mut Context<'_>) -> io::Result<()> {
+        let mut state = self.net.lock();
+        if state.partitions.contains(&self.link) {
+            if !self.cut {
+                self.cut = true;
+                state.stats.connections_cut += 1;
+            }
+            return Err(io::Error::new(io::ErrorKind::ConnectionReset, "simulated partition"));
+        }
+        state.register_waker(self.link, cx.waker());
+        Ok(())
+    }
+
+    fn next_delay(&mut self) -> Duration {
+        let mut state = self.net.lock();
+        let faults = state.faults(self.link);
+        faults.sample_delay(&mut self.rng, &mut state.stats)
+    }
+}
+
+/// Flux mémoire soumis aux fautes du lien (ou transparent côté listener)
+pub struct FaultyStream<S> {
+    inner: S,
+    fault: Option<StreamFaults>,
+    read_buf: Vec<u8>,
+    read_pos: usize,
+}
+
+impl<S> FaultyStream<S> {
+    fn passthrough(inner: S) -> Self {
+        FaultyStream { inner, fault: None, read_buf: Vec::new(), read_pos: 0 }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for FaultyStream<S> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        let Some(fault) = this.fault.as_mut() else {
+            return Pin::new(&mut this.inner).poll_read(cx, buf);
+        };
+        fault.check(cx)?;
+
+        loop {
+            // Segment déjà reçu, livré une fois son délai écoulé
+            if this.read_pos < this.read_buf.len() {
+                if let Some(delay) = fault.read_delay.as_mut() {
+                    if delay.as_mut().poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    fault.read_delay = None;
+                }
+                let n = buf.len().min(this.read_buf.len() - this.read_pos);
+                buf[..n].copy_from_slice(&this.read_buf[this.read_pos..this.read_pos + n]);
+                this.read_pos += n;
+                return Poll::Ready(Ok(n));
+            }
+
+            let mut chunk = [0u8; READ_CHUNK];
+            let n = match Pin::new(&mut this.inner).poll_read(cx, &mut chunk) {
+                Poll::Ready(Ok(n)) => n,
+                other => return other,
+            };
+            if n == 0 {
+                return Poll::Ready(Ok(0));
+            }
+            this.read_buf.clear();
+            this.read_buf.extend_from_slice(&chunk[..n]);
+            this.read_pos = 0;
+
+            let delay = fault.next_delay();
+            if !delay.is_zero() {
+                fault.read_delay = Some(Box::pin(sleep(delay)));
+            }
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for FaultyStream<S> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        let Some(fault) = this.fault.as_mut() else {
+            return Pin::new(&mut this.inner).poll_write(cx, buf);
+        };
+        fault.check(cx)?;
+
+        loop {
+            match &mut fault.write_delay {
+                WriteGate::Idle => {
+                    let delay = fault.next_delay();
+                    fault.write_delay = if delay.is_zero() {
+                        WriteGate::Open
+                    } else {
+                        WriteGate::Waiting(Box::pin(sleep(delay)))
+                    };
+                }
+                WriteGate::Waiting(delay) => {
+                    if delay.as_mut().poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    fault.write_delay = WriteGate::Open;
+                }
+                WriteGate::Open => break,
+            }
+        }
+
+        let res = Pin::new(&mut this.inner).poll_write(cx, buf);
+        if res.is_ready() {
+            fault.write_delay = WriteGate::Idle;
+        }
+        res
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+        if let Some(fault) = this.fault.as_mut() {
+            fault.check(cx)?;
+        }
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
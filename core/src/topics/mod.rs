@@ -0,0 +1,216 @@
+// src/topics/mod.rs
+use libp2p::gossipsub::{IdentTopic, TopicHash};
+use libp2p::kad::RecordKey;
+
+use crate::config::MeshConfig;
+
+/// Topics historiques (protocole v1), sans nom de mesh ni version
+const LEGACY_ANNOUNCE_TOPIC: &str = "cortex/announce";
+const LEGACY_COMMUNICATOR_TOPIC: &str = "cortex/communicator";
+const LEGACY_DISCOVERY_KEY: &[u8] = b"cortex-mesh:v1";
+
+/// Canaux PubSub utilisés par un nœud
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Announce,
+    Communicator,
+    /// Certificats de succession de clé (pas d'équivalent v1)
+    Succession,
+    /// Listes de révocation des organisations (pas d'équivalent v1)
+    Trust,
+    /// Plans d'affectation des shards, signés par le routeur qui les a calculés
+    Plan,
+    /// Attestations de réputation signées par le nœud qui a observé les pairs
+    Reputation,
+}
+
+impl Channel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Channel::Announce => "announce",
+            Channel::Communicator => "communicator",
+            Channel::Succession => "succession",
+            Channel::Trust => "trust",
+            Channel::Plan => "plan",
+            Channel::Reputation => "reputation",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "announce" => Some(Channel::Announce),
+            "communicator" => Some(Channel::Communicator),
+            "succession" => Some(Channel::Succession),
+            "trust" => Some(Channel::Trust),
+            "plan" => Some(Channel::Plan),
+            "reputation" => Some(Channel::Reputation),
+            _ => None,
+        }
+    }
+}
+
+/// Topic décodé: à quel mesh, quelle version et quel canal il appartient
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedTopic {
+    pub mesh: Option<String>,
+    pub version: u32,
+    pub channel: Channel,
+}
+
+/// Espace de noms des topics, construit à partir du nom du mesh et de la version du protocole.
+///
+/// Format: `cortex/<mesh>/v<version>/<canal>`, ex. `cortex/default/v2/announce`.
+#[derive(Debug, Clone)]
+pub struct TopicNamespace {
+    mesh: String,
+    version: u32,
+    min_version: u32,
+    legacy_compat: bool,
+}
+
+impl TopicNamespace {
+    pub fn new(mesh: &str, version: u32, min_version: u32, legacy_compat: bool) -> Self {
+        TopicNamespace {
+            mesh: mesh.to_string(),
+            version,
+            min_version,
+            legacy_compat,
+        }
+    }
+
+    pub fn from_config(mesh: &MeshConfig) -> Self {
+        Self::new(&mesh.name, mesh.protocol_version, mesh.min_protocol_version, mesh.legacy_compat)
+    }
+
+    pub fn mesh(&self) -> &str {
+        &self.mesh
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Topic courant pour un canal donné
+    pub fn topic(&self, channel: Channel) -> IdentTopic {
+        self.topic_for_version(channel, self.version)
+    }
+
+    fn topic_for_version(&self, channel: Channel, version: u32) -> IdentTopic {
+        if version <= 1 {
+            if let Some(topic) = Self::legacy_topic(channel) {
+                return topic;
+            }
+        }
+        IdentTopic::new(format!("cortex/{}/v{}/{}", self.mesh, version, channel.as_str()))
+    }
+
+    pub fn announce(&self) -> IdentTopic {
+        self.topic(Channel::Announce)
+    }
+
+    pub fn communicator(&self) -> IdentTopic {
+        self.topic(Channel::Communicator)
+    }
+
+    /// Topic v1 équivalent, utilisé pendant une migration (aucun pour les canaux apparus après v1)
+    pub fn legacy_topic(channel: Channel) -> Option<IdentTopic> {
+        match channel {
+            Channel::Announce => Some(IdentTopic::new(LEGACY_ANNOUNCE_TOPIC)),
+            Channel::Communicator => Some(IdentTopic::new(LEGACY_COMMUNICATOR_TOPIC)),
+            Channel::Succession | Channel::Trust | Channel::Plan | Channel::Reputation => None,
+        }
+    }
+
+    /// Tous les topics sur lesquels s'abonner pour un canal:
+    /// chaque version acceptée, du plus récent au plus ancien.
+    pub fn subscriptions(&self, channel: Channel) -> Vec<IdentTopic> {
+        let mut topics = Vec::new();
+        for version in (self.min_version.max(1)..=self.version).rev() {
+            if version == 1 && !self.legacy_compat {
+                continue;
+            }
+            // Canal inconnu des nœuds v1: rien à écouter côté compatibilité
+            if version == 1 && self.version > 1 && Self::legacy_topic(channel).is_none() {
+                continue;
+            }
+            topics.push(self.topic_for_version(channel, version));
+        }
+        topics
+    }
+
+    /// Topics sur lesquels publier: le topic courant, plus le topic v1 en mode compatibilité
+    pub fn publications(&self, channel: Channel) -> Vec<IdentTopic> {
+        let mut topics = vec![self.topic(channel)];
+        if self.legacy_compat && self.version > 1 && self.min_version <= 1 {
+            topics.extend(Self::legacy_topic(channel));
+        }
+        topics
+    }
+
+    /// Clé DHT sous laquelle les nœuds de ce mesh s'annoncent comme fournisseurs
+    pub fn discovery_key(&self) -> RecordKey {
+        if self.version <= 1 {
+            return RecordKey::new(&LEGACY_DISCOVERY_KEY);
+        }
+        RecordKey::new(&format!("cortex/{}/v{}", self.mesh, self.version))
+    }
+
+    /// Clé DHT du certificat de succession d'une identité retirée
+    pub fn succession_key(&self, old_peer_id: &str) -> RecordKey {
+        RecordKey::new(&format!("cortex/{}/succession/{}", self.mesh, old_peer_id))
+    }
+
+    /// Clé DHT v1, encore fournie en mode compatibilité
+    pub fn legacy_discovery_key(&self) -> Option<RecordKey> {
+        if self.legacy_compat && self.version > 1 && self.min_version <= 1 {
+            Some(RecordKey::new(&LEGACY_DISCOVERY_KEY))
+        } else {
+            None
+        }
+    }
+
+    /// Décode un topic (courant ou historique)
+    pub fn parse(topic: &str) -> Option<ParsedTopic> {
+        match topic {
+            LEGACY_ANNOUNCE_TOPIC => {
+                return Some(ParsedTopic { mesh: None, version: 1, channel: Channel::Announce })
+            }
+            LEGACY_COMMUNICATOR_TOPIC => {
+                return Some(ParsedTopic { mesh: None, version: 1, channel: Channel::Communicator })
+            }
+            _ => {}
+        }
+
+        let mut parts = topic.split('/');
+        if parts.next() != Some("cortex") {
+            return None;
+        }
+        let mesh = parts.next()?.to_string();
+        let version = parts.next()?.strip_prefix('v')?.parse().ok()?;
+        let channel = Channel::parse(parts.next()?)?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(ParsedTopic { mesh: Some(mesh), version, channel })
+    }
+
+    /// Décode un `TopicHash` reçu de gossipsub (les `IdentTopic` sont hachés par identité)
+    pub fn parse_hash(hash: &TopicHash) -> Option<ParsedTopic> {
+        Self::parse(hash.as_str())
+    }
+
+    /// Indique si un message de cette version peut être traité par ce nœud
+    pub fn accepts(&self, version: u32) -> bool {
+        version >= self.min_version && version <= self.version
+    }
+
+    /// Vérifie qu'un topic reçu appartient à ce mesh et à une version acceptée
+    pub fn accepts_topic(&self, parsed: &ParsedTopic) -> bool {
+        let same_mesh = match &parsed.mesh {
+            Some(mesh) => mesh == &self.mesh,
+            // Les topics v1 n'ont pas de nom de mesh
+            None => self.legacy_compat,
+        };
+        same_mesh && self.accepts(parsed.version)
+    }
+}
@@ -0,0 +1,242 @@
+// src/trust/mod.rs
+//! Confiance au niveau organisation: une clé ed25519 d'organisation signe des certificats
+//! de nœud (PeerId, rôles, expiration) et des listes de révocation.
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use libp2p::identity::{ed25519, PeerId};
+use serde::{Deserialize, Serialize};
+
+use crate::config::TrustConfig;
+use crate::signing::{decode_public_key, unix_now, verify_signature};
+
+const CERT_DOMAIN: &str = "cortex-node-cert/v1";
+const CRL_DOMAIN: &str = "cortex-revocation-list/v1";
+
+/// Certificat de nœud émis par une organisation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeCertificate {
+    pub trust_domain: String,
+    pub peer_id: String,
+    pub roles: Vec<String>,
+    /// Numéro de série, pour révoquer un certificat précis
+    pub serial: u64,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    /// Clé publique de l'organisation émettrice (base64)
+    pub issuer_key: String,
+    pub signature: String,
+}
+
+impl NodeCertificate {
+    pub fn issue(
+        org: &ed25519::Keypair,
+        trust_domain: &str,
+        peer_id: &PeerId,
+        roles: Vec<String>,
+        validity: Duration,
+    ) -> Self {
+        let issued_at = unix_now();
+        let mut cert = NodeCertificate {
+            trust_domain: trust_domain.to_string(),
+            peer_id: peer_id.to_string(),
+            roles,
+            serial: rand::random::<u64>() >> 1,
+            issued_at,
+            expires_at: issued_at + validity.as_secs(),
+            issuer_key: encode_public_key(&org.public()),
+            signature: String::new(),
+        };
+        cert.signature = STANDARD.encode(org.sign(&cert.signing_bytes()));
+        cert
+    }
+
+    /// Vérifie la signature par `issuer_key` (sans juger de la confiance accordée à l'émetteur)
+    pub fn verify_signature(&self) -> Result<()> {
+        verify_signature(&self.issuer_key, &self.signing_bytes(), &self.signature)
+    }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+
+    fn signing_bytes(&self) -> Vec<u8> {
+        let unsigned = NodeCertificate { signature: String::new(), ..self.clone() };
+        signing_payload(CERT_DOMAIN, &unsigned)
+    }
+}
+
+/// Liste de révocation signée par l'organisation; la plus récente remplace les précédentes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevocationList {
+    pub trust_domain: String,
+    pub issued_at: u64,
+    #[serde(default)]
+    pub revoked_peers: Vec<String>,
+    #[serde(default)]
+    pub revoked_serials: Vec<u64>,
+    pub issuer_key: String,
+    pub signature: String,
+}
+
+impl RevocationList {
+    pub fn sign(org: &ed25519::Keypair, trust_domain: &str, revoked_peers: Vec<String>, revoked_serials: Vec<u64>) -> Self {
+        let mut list = RevocationList {
+            trust_domain: trust_domain.to_string(),
+            issued_at: unix_now(),
+            revoked_peers,
+            revoked_serials,
+            issuer_key: encode_public_key(&org.public()),
+            signature: String::new(),
+        };
+        list.signature = STANDARD.encode(org.sign(&list.signing_bytes()));
+        list
+    }
+
+    pub fn verify_signature(&self) -> Result<()> {
+        verify_signature(&self.issuer_key, &self.signing_bytes(), &self.signature)
+    }
+
+    pub fn revokes(&self, peer_id: &str, serial: u64) -> bool {
+        self.revoked_peers.iter().any(|p| p == peer_id) || self.revoked_serials.contains(&serial)
+    }
+
+    fn signing_bytes(&self) -> Vec<u8> {
+        let unsigned = RevocationList { signature: String::new(), ..self.clone() };
+        signing_payload(CRL_DOMAIN, &unsigned)
+    }
+}
+
+/// Résultat d'une validation réussie, conservé dans le registre
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustInfo {
+    pub domain: String,
+    pub roles: Vec<String>,
+    pub serial: u64,
+    pub expires_at: u64,
+}
+
+impl TrustInfo {
+    pub fn is_valid(&self) -> bool {
+        self.expires_at > unix_now()
+    }
+}
+
+/// Racines de confiance (domaine → clé d'organisation) et révocations connues
+#[derive(Debug, Clone, Default)]
+pub struct TrustStore {
+    roots: HashMap<String, String>,
+    revocations: HashMap<String, RevocationList>,
+    /// N'accepter que les nœuds présentant un certificat valide
+    pub require_certificate: bool,
+}
+
+impl TrustStore {
+    /// Construit le magasin depuis la section `trust:` et charge les listes de révocation
+    pub fn from_config(config: &TrustConfig) -> Result<Self> {
+        let mut store = TrustStore {
+            require_certificate: config.require_certificate,
+            ..TrustStore::default()
+        };
+        for root in &config.roots {
+            store.add_root(&root.domain, &root.public_key)?;
+        }
+        for path in &config.revocation_lists {
+            let list: RevocationList = load_json(path)?;
+            store.apply_revocation(list)?;
+        }
+        Ok(store)
+    }
+
+    /// Ajoute un domaine de confiance (clé publique ed25519 de l'organisation, en base64)
+    pub fn add_root(&mut self, domain: &str, public_key: &str) -> Result<()> {
+        decode_public_key(public_key)?;
+        self.roots.insert(domain.to_string(), public_key.to_string());
+        Ok(())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.roots.is_empty()
+    }
+
+    pub fn domains(&self) -> impl Iterator<Item = &String> {
+        self.roots.keys()
+    }
+
+    /// Valide le certificat présenté par `peer_id`
+    pub fn validate(&self, cert: &NodeCertificate, peer_id: &str) -> Result<TrustInfo> {
+        let root = self.roots.get(&cert.trust_domain)
+            .ok_or_else(|| anyhow!("Unknown trust domain {}", cert.trust_domain))?;
+        if *root != cert.issuer_key {
+            bail!("Certificate not issued by the {} organisation key", cert.trust_domain);
+        }
+        cert.verify_signature()?;
+        if cert.peer_id != peer_id {
+            bail!("Certificate issued to {}, presented by {}", cert.peer_id, peer_id);
+        }
+        if cert.expires_at <= unix_now() {
+            bail!("Certificate expired");
+        }
+        if let Some(list) = self.revocations.get(&cert.trust_domain) {
+            if list.revokes(&cert.peer_id, cert.serial) {
+                bail!("Certificate revoked");
+            }
+        }
+        Ok(TrustInfo {
+            domain: cert.trust_domain.clone(),
+            roles: cert.roles.clone(),
+            serial: cert.serial,
+            expires_at: cert.expires_at,
+        })
+    }
+
+    /// Applique une liste de révocation signée par la racine de son domaine.
+    /// Renvoie `true` si elle remplace la liste connue (plus récente).
+    pub fn apply_revocation(&mut self, list: RevocationList) -> Result<bool> {
+        let root = self.roots.get(&list.trust_domain)
+            .ok_or_else(|| anyhow!("Unknown trust domain {}", list.trust_domain))?;
+        if *root != list.issuer_key {
+            bail!("Revocation list not issued by the {} organisation key", list.trust_domain);
+        }
+        list.verify_signature()?;
+        if let Some(current) = self.revocations.get(&list.trust_domain) {
+            if current.issued_at >= list.issued_at {
+                return Ok(false);
+            }
+        }
+        self.revocations.insert(list.trust_domain.clone(), list);
+        Ok(true)
+    }
+
+    pub fn revocation_lists(&self) -> impl Iterator<Item = &RevocationList> {
+        self.revocations.values()
+    }
+}
+
+/// Lit un certificat ou une liste de révocation au format JSON
+pub fn load_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T> {
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {:?}", path))
+}
+
+/// Écrit un certificat ou une liste de révocation (documents publics)
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(value)?).with_context(|| format!("Failed to write {:?}", path))
+}
+
+pub fn encode_public_key(key: &ed25519::PublicKey) -> String {
+    STANDARD.encode(key.to_bytes())
+}
+
+fn signing_payload<T: Serialize>(domain: &str, value: &T) -> Vec<u8> {
+    let mut payload = format!("{}\n", domain).into_bytes();
+    payload.extend(serde_json::to_vec(value).unwrap_or_default());
+    payload
+}
@@ -0,0 +1,142 @@
+// src/weights/gguf.rs
+//! En-tête GGUF (versions 2 et 3): clés/valeurs, descriptions des tenseurs, puis données alignées.
+//! Les dimensions GGUF sont stockées de la plus rapide à la plus lente: on les inverse
+//! pour obtenir une forme en ordre ligne, comme safetensors.
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Result};
+
+use super::{DType, Header, TensorInfo};
+
+pub(super) const MAGIC: &[u8] = b"GGUF";
+const ALIGNMENT_KEY: &str = "general.alignment";
+const DEFAULT_ALIGNMENT: usize = 32;
+
+// Types de valeurs des métadonnées
+const TYPE_STRING: u32 = 8;
+const TYPE_ARRAY: u32 = 9;
+
+/// Lecteur borné de l'en-tête
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| anyhow!("Truncated GGUF header"))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn len(&mut self) -> Result<usize> {
+        Ok(usize::try_from(self.u64()?)?)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.len()?;
+        Ok(String::from_utf8(self.take(len)?.to_vec())?)
+    }
+
+    /// Lit une valeur scalaire sous forme de texte (`None` pour un tableau, ignoré)
+    fn value(&mut self, kind: u32) -> Result<Option<String>> {
+        let text = match kind {
+            0 => self.take(1)?[0].to_string(),
+            1 => (self.take(1)?[0] as i8).to_string(),
+            2 => u16::from_le_bytes(self.take(2)?.try_into()?).to_string(),
+            3 => i16::from_le_bytes(self.take(2)?.try_into()?).to_string(),
+            4 => self.u32()?.to_string(),
+            5 => i32::from_le_bytes(self.take(4)?.try_into()?).to_string(),
+            6 => f32::from_le_bytes(self.take(4)?.try_into()?).to_string(),
+            7 => (self.take(1)?[0] != 0).to_string(),
+            TYPE_STRING => self.string()?,
+            TYPE_ARRAY => {
+                let item_kind = self.u32()?;
+                let count = self.len()?;
+                for _ in 0..count {
+                    self.value(item_kind)?;
+                }
+                return Ok(None);
+            }
+            10 => self.u64()?.to_string(),
+            11 => i64::from_le_bytes(self.take(8)?.try_into()?).to_string(),
+            12 => f64::from_le_bytes(self.take(8)?.try_into()?).to_string(),
+            other => bail!("Unknown GGUF value type {}", other),
+        };
+        Ok(Some(text))
+    }
+}
+
+/// Types ggml pris en charge
+fn dtype_of(kind: u32) -> Option<DType> {
+    match kind {
+        0 => Some(DType::F32),
+        1 => Some(DType::F16),
+        2 => Some(DType::Q4_0),
+        8 => Some(DType::Q8_0),
+        30 => Some(DType::BF16),
+        _ => None,
+    }
+}
+
+pub(super) fn parse(bytes: &[u8]) -> Result<Header> {
+    let mut cursor = Cursor { bytes, pos: MAGIC.len() };
+    let version = cursor.u32()?;
+    if !(2..=3).contains(&version) {
+        bail!("Unsupported GGUF version {}", version);
+    }
+    let tensor_count = cursor.len()?;
+    let kv_count = cursor.len()?;
+
+    let mut metadata = BTreeMap::new();
+    for _ in 0..kv_count {
+        let key = cursor.string()?;
+        let kind = cursor.u32()?;
+        if let Some(value) = cursor.value(kind)? {
+            metadata.insert(key, value);
+        }
+    }
+    let alignment = match metadata.get(ALIGNMENT_KEY) {
+        Some(value) => value.parse::<usize>().ok().filter(|a| a.is_power_of_two())
+            .ok_or_else(|| anyhow!("Invalid {}", ALIGNMENT_KEY))?,
+        None => DEFAULT_ALIGNMENT,
+    };
+
+    let mut infos = Vec::new();
+    for _ in 0..tensor_count {
+        let name = cursor.string()?;
+        let dims = cursor.u32()? as usize;
+        let mut shape = (0..dims).map(|_| cursor.len()).collect::<Result<Vec<_>>>()?;
+        shape.reverse();
+        let kind = cursor.u32()?;
+        let offset = cursor.len()?;
+        infos.push((name, shape, kind, offset));
+    }
+    let data_start = cursor.pos.div_ceil(alignment) * alignment;
+
+    let mut tensors = BTreeMap::new();
+    let mut unsupported = BTreeMap::new();
+    for (name, shape, kind, offset) in infos {
+        let Some(dtype) = dtype_of(kind) else {
+            unsupported.insert(name, format!("ggml type {}", kind));
+            continue;
+        };
+        let elements = shape.iter().try_fold(1usize, |n, d| n.checked_mul(*d));
+        let len = elements.and_then(|n| dtype.storage_len(n))
+            .ok_or_else(|| anyhow!("Tensor {} has an invalid shape {:?} for {:?}", name, shape, dtype))?;
+        let start = data_start.checked_add(offset).ok_or_else(|| anyhow!("Tensor {} offset overflows", name))?;
+        let end = start.checked_add(len).ok_or_else(|| anyhow!("Tensor {} size overflows", name))?;
+        tensors.insert(name, TensorInfo { dtype, shape, start, end });
+    }
+    Ok(Header { tensors, unsupported, metadata })
+}
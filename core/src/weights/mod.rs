@@ -0,0 +1,391 @@
+// src/weights/mod.rs
+//! Chargement des poids des shards: fichiers safetensors et GGUF projetés en mémoire (mmap),
+//! restreints aux tenseurs listés dans le manifeste du shard et vérifiés (forme, type, empreinte).
+//! Seules les pages des tenseurs lus sont chargées: un nœud ne lit que sa tranche d'un grand modèle.
+mod gguf;
+mod safetensors;
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use half::{bf16, f16};
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+
+use crate::audit::sha256_hex;
+
+/// Manifeste cherché quand le chemin d'un shard est un répertoire
+pub const MANIFEST_FILE: &str = "manifest.json";
+/// Fichier de poids cherché dans un répertoire sans manifeste
+pub const WEIGHTS_FILE: &str = "model.safetensors";
+
+/// Éléments par bloc quantifié (Q4_0 et Q8_0)
+pub const QK: usize = 32;
+
+/// Type des éléments d'un tenseur
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DType {
+    F32,
+    F16,
+    BF16,
+    /// Blocs de 32 entiers 8 bits et une échelle f16
+    Q8_0,
+    /// Blocs de 32 entiers 4 bits (décalés de 8) et une échelle f16
+    Q4_0,
+}
+
+impl DType {
+    /// Taille en octets de `elements` éléments (multiple de 32 pour les types quantifiés)
+    pub fn storage_len(self, elements: usize) -> Option<usize> {
+        match self {
+            DType::F32 => elements.checked_mul(4),
+            DType::F16 | DType::BF16 => elements.checked_mul(2),
+            DType::Q8_0 if elements % QK == 0 => Some(elements / QK * BlockQ8_0::SIZE),
+            DType::Q4_0 if elements % QK == 0 => Some(elements / QK * BlockQ4_0::SIZE),
+            _ => None,
+        }
+    }
+
+    pub fn is_quantized(self) -> bool {
+        matches!(self, DType::Q8_0 | DType::Q4_0)
+    }
+}
+
+/// Bloc Q8_0: `x[i] = scale * quants[i]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockQ8_0 {
+    pub scale: f16,
+    pub quants: [i8; QK],
+}
+
+impl BlockQ8_0 {
+    pub const SIZE: usize = 2 + QK;
+
+    fn parse(bytes: &[u8]) -> Self {
+        let mut quants = [0i8; QK];
+        for (q, b) in quants.iter_mut().zip(&bytes[2..Self::SIZE]) {
+            *q = *b as i8;
+        }
+        BlockQ8_0 { scale: f16::from_le_bytes([bytes[0], bytes[1]]), quants }
+    }
+
+    pub fn dequantize(&self) -> [f32; QK] {
+        let scale = self.scale.to_f32();
+        self.quants.map(|q| q as f32 * scale)
+    }
+}
+
+/// Bloc Q4_0: l'octet `j` porte les éléments `j` (4 bits de poids faible) et `j + 16`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockQ4_0 {
+    pub scale: f16,
+    pub quants: [u8; QK / 2],
+}
+
+impl BlockQ4_0 {
+    pub const SIZE: usize = 2 + QK / 2;
+
+    fn parse(bytes: &[u8]) -> Self {
+        let mut quants = [0u8; QK / 2];
+        quants.copy_from_slice(&bytes[2..Self::SIZE]);
+        BlockQ4_0 { scale: f16::from_le_bytes([bytes[0], bytes[1]]), quants }
+    }
+
+    pub fn dequantize(&self) -> [f32; QK] {
+        let scale = self.scale.to_f32();
+        let mut out = [0.0f32; QK];
+        for (j, q) in self.quants.iter().enumerate() {
+            out[j] = ((q & 0x0f) as i32 - 8) as f32 * scale;
+            out[j + QK / 2] = ((q >> 4) as i32 - 8) as f32 * scale;
+        }
+        out
+    }
+}
+
+/// Format d'un fichier de poids
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeightFormat {
+    Safetensors,
+    Gguf,
+}
+
+/// Position d'un tenseur dans le fichier
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TensorInfo {
+    dtype: DType,
+    /// Forme en ordre ligne (la dernière dimension est contiguë)
+    shape: Vec<usize>,
+    start: usize,
+    end: usize,
+}
+
+/// Contenu d'un en-tête décodé
+struct Header {
+    tensors: BTreeMap<String, TensorInfo>,
+    /// Tenseurs présents mais d'un type non pris en charge (nom → type)
+    unsupported: BTreeMap<String, String>,
+    metadata: BTreeMap<String, String>,
+}
+
+/// Tenseur lu dans un fichier projeté en mémoire (aucune copie)
+#[derive(Debug, Clone, Copy)]
+pub struct WeightTensor<'a> {
+    pub name: &'a str,
+    pub dtype: DType,
+    pub shape: &'a [usize],
+    pub data: &'a [u8],
+}
+
+impl<'a> WeightTensor<'a> {
+    pub fn elements(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    pub fn sha256(&self) -> String {
+        sha256_hex(self.data)
+    }
+
+    /// Valeurs en f32, blocs quantifiés déquantifiés
+    pub fn to_f32(&self) -> Result<Vec<f32>> {
+        Ok(match self.dtype {
+            DType::F32 => self.data.chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect(),
+            DType::F16 => self.data.chunks_exact(2)
+                .map(|b| f16::from_le_bytes([b[0], b[1]]).to_f32())
+                .collect(),
+            DType::BF16 => self.data.chunks_exact(2)
+                .map(|b| bf16::from_le_bytes([b[0], b[1]]).to_f32())
+                .collect(),
+            DType::Q8_0 => self.q8_0_blocks()?.flat_map(|b| b.dequantize()).collect(),
+            DType::Q4_0 => self.q4_0_blocks()?.flat_map(|b| b.dequantize()).collect(),
+        })
+    }
+
+    /// Blocs Q8_0, pour les backends qui calculent directement sur les poids quantifiés
+    pub fn q8_0_blocks(&self) -> Result<impl Iterator<Item = BlockQ8_0> + 'a> {
+        if self.dtype != DType::Q8_0 {
+            bail!("Tensor {} is {:?}, not Q8_0", self.name, self.dtype);
+        }
+        Ok(self.data.chunks_exact(BlockQ8_0::SIZE).map(BlockQ8_0::parse))
+    }
+
+    /// Blocs Q4_0, pour les backends qui calculent directement sur les poids quantifiés
+    pub fn q4_0_blocks(&self) -> Result<impl Iterator<Item = BlockQ4_0> + 'a> {
+        if self.dtype != DType::Q4_0 {
+            bail!("Tensor {} is {:?}, not Q4_0", self.name, self.dtype);
+        }
+        Ok(self.data.chunks_exact(BlockQ4_0::SIZE).map(BlockQ4_0::parse))
+    }
+}
+
+/// Fichier de poids projeté en mémoire
+pub struct WeightFile {
+    path: PathBuf,
+    format: WeightFormat,
+    mmap: Mmap,
+    header: Header,
+}
+
+impl std::fmt::Debug for WeightFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WeightFile")
+            .field("path", &self.path)
+            .field("format", &self.format)
+            .field("tensors", &self.header.tensors.len())
+            .finish()
+    }
+}
+
+impl WeightFile {
+    /// Projette le fichier et décode son en-tête (format détecté par le nombre magique)
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+        // SAFETY: projection en lecture seule; les fichiers de poids ne sont pas modifiés
+        // pendant que le shard est chargé (même hypothèse que llama.cpp et safetensors)
+        let mmap = unsafe { Mmap::map(&file) }.with_context(|| format!("Failed to map {:?}", path))?;
+        let (format, header) = if mmap.starts_with(gguf::MAGIC) {
+            (WeightFormat::Gguf, gguf::parse(&mmap))
+        } else {
+            (WeightFormat::Safetensors, safetensors::parse(&mmap))
+        };
+        let header = header.with_context(|| format!("Invalid weight file {:?}", path))?;
+        for (name, info) in &header.tensors {
+            let elements: usize = info.shape.iter().product();
+            if info.dtype.storage_len(elements) != Some(info.end - info.start) || info.end > mmap.len() {
+                bail!("Tensor {} in {:?} has inconsistent size or offsets", name, path);
+            }
+        }
+        Ok(WeightFile { path: path.to_path_buf(), format, mmap, header })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn format(&self) -> WeightFormat {
+        self.format
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.header.tensors.keys().chain(self.header.unsupported.keys()).map(String::as_str)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.header.tensors.contains_key(name) || self.header.unsupported.contains_key(name)
+    }
+
+    pub fn tensor(&self, name: &str) -> Result<WeightTensor<'_>> {
+        if let Some(kind) = self.header.unsupported.get(name) {
+            bail!("Tensor {} has unsupported type {}", name, kind);
+        }
+        let (name, info) = self.header.tensors.get_key_value(name)
+            .ok_or_else(|| anyhow!("Missing tensor {} in {:?}", name, self.path))?;
+        Ok(WeightTensor {
+            name,
+            dtype: info.dtype,
+            shape: &info.shape,
+            data: &self.mmap[info.start..info.end],
+        })
+    }
+
+    /// Métadonnées scalaires (`__metadata__` safetensors, clés/valeurs GGUF)
+    pub fn metadata(&self, key: &str) -> Option<&str> {
+        self.header.metadata.get(key).map(String::as_str)
+    }
+}
+
+/// Tenseur attendu par un shard
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestTensor {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dtype: Option<DType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shape: Option<Vec<usize>>,
+    /// Empreinte SHA-256 des octets du tenseur, tels que stockés dans le fichier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+/// Manifeste d'un shard: fichiers à projeter et tenseurs à en extraire
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardManifest {
+    pub id: String,
+    /// Fichiers de poids, relatifs au répertoire du manifeste
+    pub files: Vec<PathBuf>,
+    pub tensors: Vec<ManifestTensor>,
+}
+
+impl ShardManifest {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+        serde_json::from_str(&content).with_context(|| format!("Invalid shard manifest {:?}", path))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?).with_context(|| format!("Failed to write {:?}", path))
+    }
+
+    /// Décrit les tenseurs de `files` retenus par `select`, avec forme, type et empreinte
+    pub fn describe(id: &str, dir: &Path, files: &[PathBuf], select: impl Fn(&str) -> bool) -> Result<Self> {
+        let mut tensors = Vec::new();
+        for file in files {
+            let weights = WeightFile::open(&dir.join(file))?;
+            for name in weights.names().filter(|n| select(n)) {
+                let tensor = weights.tensor(name)?;
+                tensors.push(ManifestTensor {
+                    name: name.to_string(),
+                    dtype: Some(tensor.dtype),
+                    shape: Some(tensor.shape.to_vec()),
+                    sha256: Some(tensor.sha256()),
+                });
+            }
+        }
+        Ok(ShardManifest { id: id.to_string(), files: files.to_vec(), tensors })
+    }
+}
+
+/// Poids d'un shard: seuls les tenseurs du manifeste sont accessibles
+#[derive(Debug)]
+pub struct ShardWeights {
+    dir: PathBuf,
+    files: Vec<WeightFile>,
+    /// Tenseur → indice du fichier qui le porte
+    index: BTreeMap<String, usize>,
+}
+
+impl ShardWeights {
+    /// Ouvre un shard depuis un manifeste, un répertoire (manifeste ou `model.safetensors`)
+    /// ou un fichier de poids seul (tous ses tenseurs)
+    pub fn open(path: &Path) -> Result<Self> {
+        let manifest_path = if path.is_dir() { path.join(MANIFEST_FILE) } else { path.to_path_buf() };
+        let is_manifest = manifest_path.extension().is_some_and(|e| e == "json") && manifest_path.exists();
+        if is_manifest {
+            let dir = manifest_path.parent().map(Path::to_path_buf).unwrap_or_default();
+            return Self::from_manifest(&ShardManifest::load(&manifest_path)?, &dir);
+        }
+        let (file, dir) = if path.is_dir() {
+            (path.join(WEIGHTS_FILE), path.to_path_buf())
+        } else {
+            (path.to_path_buf(), path.parent().map(Path::to_path_buf).unwrap_or_default())
+        };
+        let file = WeightFile::open(&file)?;
+        let index = file.names().map(|name| (name.to_string(), 0)).collect();
+        Ok(ShardWeights { dir, files: vec![file], index })
+    }
+
+    /// Projette les fichiers du manifeste et vérifie chaque tenseur listé
+    pub fn from_manifest(manifest: &ShardManifest, dir: &Path) -> Result<Self> {
+        let files = manifest.files.iter()
+            .map(|file| WeightFile::open(&dir.join(file)))
+            .collect::<Result<Vec<_>>>()?;
+        let mut index = BTreeMap::new();
+        for expected in &manifest.tensors {
+            let position = files.iter().position(|f| f.contains(&expected.name))
+                .ok_or_else(|| anyhow!("Shard {}: tensor {} not found in its files", manifest.id, expected.name))?;
+            let tensor = files[position].tensor(&expected.name)?;
+            if expected.dtype.is_some_and(|d| d != tensor.dtype) {
+                bail!("Shard {}: tensor {} is {:?}, manifest says {:?}", manifest.id, tensor.name, tensor.dtype, expected.dtype);
+            }
+            if expected.shape.as_deref().is_some_and(|s| s != tensor.shape) {
+                bail!("Shard {}: tensor {} has shape {:?}, manifest says {:?}", manifest.id, tensor.name, tensor.shape, expected.shape);
+            }
+            if expected.sha256.as_ref().is_some_and(|h| !h.eq_ignore_ascii_case(&tensor.sha256())) {
+                bail!("Shard {}: tensor {} does not match its sha256", manifest.id, tensor.name);
+            }
+            index.insert(expected.name.clone(), position);
+        }
+        Ok(ShardWeights { dir: dir.to_path_buf(), files, index })
+    }
+
+    /// Répertoire du shard (configuration du modèle à côté des poids)
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.index.keys().map(String::as_str)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    pub fn tensor(&self, name: &str) -> Result<WeightTensor<'_>> {
+        let file = self.index.get(name).ok_or_else(|| anyhow!("Tensor {} is not part of this shard", name))?;
+        self.files[*file].tensor(name)
+    }
+
+    pub fn metadata(&self, key: &str) -> Option<&str> {
+        self.files.iter().find_map(|f| f.metadata(key))
+    }
+
+    /// Taille des tenseurs du shard dans les fichiers, en octets
+    pub fn size_bytes(&self) -> usize {
+        self.names().filter_map(|name| self.tensor(name).ok()).map(|t| t.data.len()).sum()
+    }
+}
@@ -0,0 +1,60 @@
+// src/weights/safetensors.rs
+//! En-tête safetensors: longueur u64 little-endian, puis JSON `nom → {dtype, shape, data_offsets}`
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+
+use super::{DType, Header, TensorInfo};
+
+const METADATA_KEY: &str = "__metadata__";
+
+#[derive(Deserialize)]
+struct Entry {
+    dtype: String,
+    shape: Vec<usize>,
+    data_offsets: (usize, usize),
+}
+
+pub(super) fn parse(bytes: &[u8]) -> Result<Header> {
+    let len_bytes: [u8; 8] = bytes.get(..8)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| anyhow!("File too short for a safetensors header"))?;
+    let header_len = usize::try_from(u64::from_le_bytes(len_bytes))?;
+    let data_start = 8usize.checked_add(header_len).filter(|&end| end <= bytes.len())
+        .ok_or_else(|| anyhow!("Safetensors header longer than the file"))?;
+    let json: BTreeMap<String, serde_json::Value> = serde_json::from_slice(&bytes[8..data_start])?;
+
+    let mut header = Header {
+        tensors: BTreeMap::new(),
+        unsupported: BTreeMap::new(),
+        metadata: BTreeMap::new(),
+    };
+    for (name, value) in json {
+        if name == METADATA_KEY {
+            header.metadata = serde_json::from_value(value)?;
+            continue;
+        }
+        let entry: Entry = serde_json::from_value(value)?;
+        let dtype = match entry.dtype.as_str() {
+            "F32" => DType::F32,
+            "F16" => DType::F16,
+            "BF16" => DType::BF16,
+            other => {
+                header.unsupported.insert(name, other.to_string());
+                continue;
+            }
+        };
+        let (start, end) = entry.data_offsets;
+        if start > end {
+            bail!("Tensor {} has invalid offsets", name);
+        }
+        header.tensors.insert(name, TensorInfo {
+            dtype,
+            shape: entry.shape,
+            start: data_start + start,
+            end: data_start + end,
+        });
+    }
+    Ok(header)
+}
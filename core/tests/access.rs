@@ -0,0 +1,69 @@
+// Listes d'autorisation et de blocage des pairs
+use std::time::Duration;
+
+use cortex_id::access::{AccessLists, AccessUpdate};
+use cortex_id::discovery::NodeRole;
+use cortex_id::harness::MeshHarness;
+use libp2p::PeerId;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+#[test]
+fn lists_permit_and_persist() {
+    let friend = PeerId::random().to_string();
+    let foe = PeerId::random().to_string();
+    let mut lists = AccessLists::default();
+
+    assert!(lists.apply(&AccessUpdate::Deny(foe.clone())).unwrap());
+    assert!(!lists.apply(&AccessUpdate::Deny(foe.clone())).unwrap());
+    assert!(lists.apply(&AccessUpdate::Allow("not-a-peer".into())).is_err());
+    assert!(!lists.permits(&foe));
+    assert!(lists.permits(&friend));
+
+    lists.allowlist_only = true;
+    assert!(!lists.permits(&friend));
+    lists.apply(&AccessUpdate::Allow(friend.clone())).unwrap();
+    assert!(lists.permits(&friend));
+
+    let update: AccessUpdate = serde_json::from_str(&format!(r#"{{"action":"undeny","peer_id":"{}"}}"#, foe)).unwrap();
+    lists.apply(&update).unwrap();
+    assert!(lists.deny.is_empty());
+
+    let dir = std::env::temp_dir().join(format!("cortex-access-{}", PeerId::random()));
+    let path = dir.join("access.json");
+    assert!(AccessLists::load(&path).unwrap().is_none());
+    lists.save(&path).unwrap();
+    assert_eq!(AccessLists::load(&path).unwrap(), Some(lists));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn denied_peer_is_dropped_at_runtime() {
+    let mut harness = MeshHarness::new();
+    let bootstrap = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    let light = harness.spawn_node(NodeRole::Light).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+
+    let light_id = harness.node(light).unwrap().peer_id();
+    let known = harness
+        .announce_until(TIMEOUT, |h| h.node(bootstrap).unwrap().knows(&light_id))
+        .await
+        .unwrap();
+    assert!(known);
+
+    let handle = harness.node(bootstrap).unwrap().handle.clone();
+    let lists = handle.update_access(AccessUpdate::Deny(light_id.to_string())).await.unwrap();
+    assert!(lists.deny.contains(&light_id.to_string()));
+    assert!(!harness.node(bootstrap).unwrap().knows(&light_id));
+
+    // Le pair bloqué n'est plus réadmis, même en continuant d'annoncer
+    let readmitted = harness
+        .announce_until(Duration::from_secs(3), |h| h.node(bootstrap).unwrap().knows(&light_id))
+        .await
+        .unwrap();
+    assert!(!readmitted);
+
+    handle.update_access(AccessUpdate::Undeny(light_id.to_string())).await.unwrap();
+    assert!(handle.access_lists().await.unwrap().deny.is_empty());
+    harness.shutdown().await.unwrap();
+}
@@ -0,0 +1,99 @@
+// Coût d'admission des identités (preuve de travail)
+use std::time::Duration;
+
+use cortex_id::admission::{load_or_solve, AdmissionProof};
+use cortex_id::config::CortexConfig;
+use cortex_id::discovery::NodeRole;
+use cortex_id::harness::MeshHarness;
+use cortex_id::registry::AnnounceMsg;
+use libp2p::PeerId;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+#[test]
+fn proof_is_bound_to_peer_and_mesh() {
+    let peer = PeerId::random();
+    let proof = AdmissionProof::solve("acme", &peer, 12);
+    assert!(proof.verify("acme", &peer, 12));
+    assert!(proof.work("acme", &peer) >= 12);
+    // Une preuve ne se transfère ni à une autre identité ni à un autre mesh
+    assert!(!proof.verify("acme", &PeerId::random(), 12) || !proof.verify("other", &peer, 12));
+    assert!(AdmissionProof { nonce: proof.nonce }.verify("acme", &peer, 0));
+
+    let dir = std::env::temp_dir().join(format!("cortex-admission-{}", peer));
+    let path = dir.join("admission.json");
+    let cached = load_or_solve(&path, "acme", &peer, 8).unwrap();
+    assert_eq!(load_or_solve(&path, "acme", &peer, 8).unwrap(), cached);
+    assert!(load_or_solve(&path, "other", &peer, 8).unwrap().verify("other", &peer, 8));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn nodes_without_proof_are_not_eligible() {
+    let mut config = CortexConfig::default();
+    config.mesh.admission_difficulty = 8;
+    let mut harness = MeshHarness::with_config(config);
+    let bootstrap = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    let honest = harness.spawn_node(NodeRole::Light).await.unwrap();
+    // Un nœud qui ignore la règle du mesh: aucune preuve dans ses annonces
+    harness.config_mut().mesh.admission_difficulty = 0;
+    let sybil = harness.spawn_node(NodeRole::Light).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+
+    let honest_id = harness.node(honest).unwrap().peer_id();
+    let sybil_id = harness.node(sybil).unwrap().peer_id();
+    let seen = harness
+        .announce_until(TIMEOUT, |h| {
+            let node = h.node(bootstrap).unwrap();
+            node.knows(&honest_id) && node.knows(&sybil_id)
+        })
+        .await
+        .unwrap();
+    assert!(seen);
+
+    let registry = harness.node(bootstrap).unwrap().registry();
+    assert!(registry.nodes[&honest_id.to_string()].admitted);
+    assert!(!registry.nodes[&sybil_id.to_string()].admitted);
+    assert_eq!(registry.eligible_nodes(), vec![honest_id.to_string()]);
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn replayed_announce_from_another_source_is_rejected() {
+    let mut config = CortexConfig::default();
+    config.mesh.admission_difficulty = 8;
+    let mesh = config.mesh.name.clone();
+    let mut harness = MeshHarness::with_config(config);
+    let bootstrap = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    let victim = harness.spawn_node(NodeRole::Light).await.unwrap();
+    let attacker = harness.spawn_node(NodeRole::Light).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    harness.converge(TIMEOUT).await.unwrap();
+
+    // L'annonce de la victime, preuve d'admission valide comprise, republiée avec une capacité gonflée
+    let victim_id = harness.node(victim).unwrap().peer_id();
+    let attacker = harness.node(attacker).unwrap();
+    let replay = AnnounceMsg {
+        ram_free_mb: 1_000_000,
+        admission: Some(AdmissionProof::solve(&mesh, &victim_id, 8)),
+        ..AnnounceMsg::new(victim_id.to_string())
+    };
+    attacker.handle.publish_announce(replay).await.unwrap();
+    let marker = AnnounceMsg {
+        shards: vec!["marker".into()],
+        admission: Some(AdmissionProof::solve(&mesh, &attacker.peer_id(), 8)),
+        ..AnnounceMsg::new(attacker.peer_id().to_string())
+    };
+    attacker.handle.publish_announce(marker).await.unwrap();
+
+    let attacker_id = attacker.peer_id().to_string();
+    let received = harness
+        .wait_until(TIMEOUT, |h| {
+            h.node(bootstrap).unwrap().registry().nodes[&attacker_id].shards.iter().any(|s| s.shard_id == "marker")
+        })
+        .await;
+    assert!(received);
+    let registry = harness.node(bootstrap).unwrap().registry();
+    assert!(registry.nodes[&victim_id.to_string()].ram_free_mb < 1_000_000, "replayed announce overwrote the victim");
+    harness.shutdown().await.unwrap();
+}
@@ -0,0 +1,123 @@
+// Journal d'audit chaîné et signé
+use std::fs;
+use std::time::Duration;
+
+use cortex_id::audit::{export_range, read_entries, verify_chain, AuditEvent, AuditLog, AUDIT_FILE};
+use cortex_id::communicator::CommunicatorMessage;
+use cortex_id::discovery::NodeRole;
+use cortex_id::harness::MeshHarness;
+use cortex_id::identity::succession::KeySuccession;
+use cortex_id::policy::DataClass;
+use libp2p::identity::{ed25519, Keypair};
+use libp2p::PeerId;
+
+mod common;
+use common::temp_path;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+#[test]
+fn chain_detects_tampering() {
+    let dir = temp_path("chain");
+    let identity = ed25519::Keypair::generate();
+    let peer = PeerId::random();
+    let log = AuditLog::open(&dir, identity.clone()).unwrap();
+    log.record_egress(&peer, DataClass::Prompt, b"bonjour").unwrap();
+    log.record_execution(&peer, "layers-0-7", b"activations").unwrap();
+    drop(log);
+
+    // Réouverture: la chaîne reprend là où elle s'était arrêtée
+    let log = AuditLog::open(&dir, identity).unwrap();
+    let last = log.record_egress(&peer, DataClass::Output, b"reponse").unwrap();
+    assert_eq!(last.seq, 2);
+    let path = dir.join(AUDIT_FILE);
+    let owner = last.signer.clone();
+    let report = verify_chain(&path, &owner, &[]).unwrap();
+    assert_eq!(report.entries, 3);
+    assert_eq!(report.head_hash, last.hash);
+    let first = read_entries(&path).unwrap()[0].timestamp;
+    assert_eq!(export_range(&path, Some(first), None).unwrap().len(), 3);
+    assert!(export_range(&path, None, Some(first - 1)).unwrap().is_empty());
+    assert_eq!(export_range(&path, Some(last.timestamp), None).unwrap().last(), Some(&last));
+
+    let original = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = original.lines().collect();
+
+    // Taille modifiée après coup
+    fs::write(&path, original.replacen("\"size\":7", "\"size\":3", 1)).unwrap();
+    assert!(verify_chain(&path, &owner, &[]).is_err());
+    // Entrée supprimée
+    fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+    assert!(verify_chain(&path, &owner, &[]).is_err());
+    // Entrée réécrite et re-signée par une autre clé
+    let forger = AuditLog::open(&temp_path("forger"), ed25519::Keypair::generate()).unwrap();
+    let forged = forger.record_egress(&peer, DataClass::Prompt, b"x").unwrap();
+    fs::write(&path, format!("{}\n", serde_json::to_string(&forged).unwrap())).unwrap();
+    assert!(verify_chain(&path, &owner, &[]).is_err());
+
+    fs::remove_dir_all(dir).unwrap();
+    fs::remove_dir_all(forger.path().parent().unwrap()).unwrap();
+}
+
+#[test]
+fn signer_change_requires_key_succession() {
+    let dir = temp_path("rotation");
+    let peer = PeerId::random();
+    let old = ed25519::Keypair::generate();
+    let new = ed25519::Keypair::generate();
+    let record = |key: &ed25519::Keypair| {
+        let log = AuditLog::open(&dir, key.clone()).unwrap();
+        log.record_egress(&peer, DataClass::Prompt, b"data").unwrap().signer
+    };
+    let old_id = record(&old);
+    // Rotation: le nœud rouvre son journal avec la nouvelle clé
+    let new_id = record(&new);
+    let path = dir.join(AUDIT_FILE);
+
+    assert!(verify_chain(&path, &new_id, &[]).is_err());
+    let succession = KeySuccession::sign(&old, &new, None);
+    let report = verify_chain(&path, &new_id, std::slice::from_ref(&succession)).unwrap();
+    assert_eq!(report.signers, vec![old_id.clone(), new_id]);
+    // Le journal n'appartient pas à l'ancienne identité, ni à une clé inconnue
+    assert!(verify_chain(&path, &old_id, std::slice::from_ref(&succession)).is_err());
+    assert!(verify_chain(&path, &PeerId::random().to_string(), &[succession]).is_err());
+
+    // Un certificat qui désigne une autre clé ne couvre pas le changement
+    let other = KeySuccession::sign(&old, &ed25519::Keypair::generate(), None);
+    let target = other.new_peer_id.clone();
+    assert!(verify_chain(&path, &target, &[other]).is_err());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn sealed_send_is_audited() {
+    let dir = temp_path("node");
+    let mut harness = MeshHarness::new();
+    let key = Keypair::generate_ed25519();
+    let audit_dir = dir.clone();
+    let sender = harness
+        .spawn_node_with(NodeRole::Bootstrap, key, |options| options.audit_dir = Some(audit_dir))
+        .await
+        .unwrap();
+    let receiver = harness.spawn_node(NodeRole::Light).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+
+    let handle = harness.node(sender).unwrap().handle.clone();
+    let target = harness.node(receiver).unwrap().peer_id();
+    let message = CommunicatorMessage {
+        sender: "test".into(),
+        payload: "contexte".into(),
+        timestamp: 0,
+    };
+    handle.send_sealed(DataClass::Prompt, vec![target], message).await.unwrap();
+
+    let path = handle.audit().unwrap().path().to_path_buf();
+    let entries = read_entries(&path).unwrap();
+    assert!(matches!(&entries[0].event, AuditEvent::Policy(d) if d.allowed));
+    assert!(matches!(&entries[1].event, AuditEvent::Egress { peer, data: DataClass::Prompt, .. } if *peer == target.to_string()));
+    assert_eq!(entries[1].signer, handle.peer_id().to_string());
+    assert_eq!(verify_chain(&path, &handle.peer_id().to_string(), &[]).unwrap().entries, 2);
+
+    harness.shutdown().await.unwrap();
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,122 @@
+// Outils partagés par les tests d'intégration: chemins temporaires et petit modèle de référence
+#![allow(dead_code)]
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use cortex_id::config::{CortexConfig, TrustRootConfig};
+use cortex_id::discovery::NodeRole;
+use cortex_id::harness::MeshHarness;
+use cortex_id::scheduler::ROUTER_ROLE;
+use cortex_id::trust::{encode_public_key, NodeCertificate};
+use libp2p::identity::{ed25519, Keypair};
+use libp2p::PeerId;
+
+/// Chemin temporaire unique, non créé (ex: répertoire dont le code testé fixe lui-même les droits)
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("cortex-{}-{}", name, PeerId::random()))
+}
+
+/// Répertoire temporaire unique, créé
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = temp_path(name);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Banc dont le bootstrap (nœud 0) est certifié routeur par l'organisation « acme »,
+/// suivi de `n - 1` nœuds légers sans certificat
+pub async fn start_with_router(n: usize) -> MeshHarness {
+    let org = ed25519::Keypair::generate();
+    let mut config = CortexConfig::default();
+    config.trust.roots.push(TrustRootConfig { domain: "acme".into(), public_key: encode_public_key(&org.public()) });
+    let mut harness = MeshHarness::with_config(config);
+    let key = Keypair::generate_ed25519();
+    let year = Duration::from_secs(365 * 24 * 3600);
+    let cert = NodeCertificate::issue(&org, "acme", &key.public().to_peer_id(), vec![ROUTER_ROLE.into()], year);
+    harness
+        .spawn_node_with(NodeRole::Bootstrap, key, |options| options.certificate = Some(cert.clone()))
+        .await
+        .unwrap();
+    harness.spawn_many(n - 1).await.unwrap();
+    harness
+}
+
+/// Transformer décodeur minuscule, écrit en safetensors pour le backend CPU
+#[cfg(feature = "backend-cpu")]
+pub mod tiny {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use cortex_id::executor::cpu::CONFIG_FILE;
+    use safetensors::tensor::TensorView;
+    use safetensors::Dtype;
+
+    pub const HIDDEN: usize = 8;
+    pub const KV_DIM: usize = 4;
+    pub const INTER: usize = 16;
+    pub const VOCAB: usize = 11;
+    pub const LAYERS: usize = 2;
+
+    /// Poids nommés comme dans un checkpoint Llama: forme et valeurs
+    pub type Weights = BTreeMap<String, (Vec<usize>, Vec<f32>)>;
+
+    /// Poids pseudo-aléatoires déterministes (générateur congruentiel)
+    pub fn tiny_model() -> Weights {
+        let mut state = 0x2545_f491_u64;
+        let mut random = |shape: Vec<usize>| {
+            let data = (0..shape.iter().product::<usize>())
+                .map(|_| {
+                    state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                    ((state >> 40) as f32 / (1u64 << 24) as f32 - 0.5) * 0.8
+                })
+                .collect();
+            (shape, data)
+        };
+        let mut weights = BTreeMap::new();
+        weights.insert("model.embed_tokens.weight".to_string(), random(vec![VOCAB, HIDDEN]));
+        for i in 0..LAYERS {
+            let p = format!("model.layers.{}", i);
+            for (name, shape) in [
+                ("input_layernorm", vec![HIDDEN]),
+                ("self_attn.q_proj", vec![HIDDEN, HIDDEN]),
+                ("self_attn.k_proj", vec![KV_DIM, HIDDEN]),
+                ("self_attn.v_proj", vec![KV_DIM, HIDDEN]),
+                ("self_attn.o_proj", vec![HIDDEN, HIDDEN]),
+                ("post_attention_layernorm", vec![HIDDEN]),
+                ("mlp.gate_proj", vec![INTER, HIDDEN]),
+                ("mlp.up_proj", vec![INTER, HIDDEN]),
+                ("mlp.down_proj", vec![HIDDEN, INTER]),
+            ] {
+                weights.insert(format!("{}.{}.weight", p, name), random(shape));
+            }
+        }
+        weights.insert("model.norm.weight".to_string(), random(vec![HIDDEN]));
+        weights.insert("lm_head.weight".to_string(), random(vec![VOCAB, HIDDEN]));
+        weights
+    }
+
+    /// Écrit les tenseurs dont le nom satisfait `keep`, et la configuration à côté
+    pub fn write_shard(dir: &Path, file: &str, weights: &Weights, keep: impl Fn(&str) -> bool) -> PathBuf {
+        let bytes: BTreeMap<&String, (Vec<usize>, Vec<u8>)> = weights.iter()
+            .filter(|(name, _)| keep(name))
+            .map(|(name, (shape, data))| (name, (shape.clone(), data.iter().flat_map(|v| v.to_le_bytes()).collect())))
+            .collect();
+        let views: Vec<(&String, TensorView)> = bytes.iter()
+            .map(|(name, (shape, data))| (*name, TensorView::new(Dtype::F32, shape.clone(), data).unwrap()))
+            .collect();
+        let path = dir.join(file);
+        safetensors::serialize_to_file(views, &None, &path).unwrap();
+        let config = serde_json::json!({
+            "hidden_size": HIDDEN,
+            "intermediate_size": INTER,
+            "num_attention_heads": 2,
+            "num_key_value_heads": 1,
+            "vocab_size": VOCAB,
+            "rms_norm_eps": 1e-5,
+        });
+        fs::write(dir.join(CONFIG_FILE), config.to_string()).unwrap();
+        path
+    }
+}
@@ -0,0 +1,125 @@
+// Backend CPU de référence: transformer décodeur chargé depuis safetensors
+#![cfg(feature = "backend-cpu")]
+use std::fs;
+use std::path::PathBuf;
+
+use cortex_id::executor::cpu::CpuTransformer;
+use cortex_id::executor::{BackendRegistry, ShardExecutor, ShardHost, ShardSpec, Tensor};
+
+mod common;
+use common::temp_dir;
+use common::tiny::{tiny_model, write_shard, HIDDEN, VOCAB};
+
+fn load(path: PathBuf) -> CpuTransformer {
+    let mut executor = CpuTransformer::default();
+    executor
+        .load(&ShardSpec { id: "tiny".into(), version: "1".into(), backend: "cpu".into(), path: Some(path) })
+        .unwrap();
+    executor
+}
+
+fn tokens(ids: &[u32]) -> Tensor {
+    Tensor::new(vec![ids.len()], ids.iter().map(|&t| t as f32).collect()).unwrap()
+}
+
+#[test]
+fn split_shards_match_full_model_bit_for_bit() {
+    let weights = tiny_model();
+    let dir = temp_dir("split");
+    let mut full = load(write_shard(&dir, "full.safetensors", &weights, |_| true));
+    let mut first = load(write_shard(&dir, "first.safetensors", &weights, |n| {
+        n.starts_with("model.embed_tokens") || n.starts_with("model.layers.0.")
+    }));
+    let mut last = load(write_shard(&dir, "last.safetensors", &weights, |n| {
+        n.starts_with("model.layers.1.") || n == "model.norm.weight" || n == "lm_head.weight"
+    }));
+    assert_eq!(first.layers(), vec![0]);
+    assert_eq!(last.layers(), vec![1]);
+
+    let prompt = tokens(&[3, 1, 4, 1, 5]);
+    let expected = full.forward(&prompt).unwrap();
+    assert_eq!(expected.shape, vec![5, VOCAB]);
+
+    let hidden = first.forward(&prompt).unwrap();
+    assert_eq!(hidden.shape, vec![5, HIDDEN]);
+    let logits = last.forward(&hidden).unwrap();
+    let bits = |t: &Tensor| t.data.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+    assert_eq!(bits(&logits), bits(&expected));
+
+    // Un second passage depuis zéro redonne exactement les mêmes bits
+    full.reset();
+    assert_eq!(bits(&full.forward(&prompt).unwrap()), bits(&expected));
+    assert!(full.memory_usage() > 0);
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn incremental_decoding_matches_prefill() {
+    let weights = tiny_model();
+    let dir = temp_dir("decode");
+    let path = write_shard(&dir, "model.safetensors", &weights, |_| true);
+    let mut prefill = load(dir.clone());
+    let mut stepwise = load(path);
+
+    let ids = [2u32, 7, 1, 8];
+    let all = prefill.forward(&tokens(&ids)).unwrap();
+    let mut last = Tensor::zeros(vec![0]);
+    for id in ids {
+        last = stepwise.forward(&tokens(&[id])).unwrap();
+    }
+    assert_eq!(stepwise.position(), ids.len());
+    assert_eq!(last.data, all.data[(ids.len() - 1) * VOCAB..]);
+
+    prefill.reset();
+    let generated = prefill.generate(&ids, 3).unwrap();
+    assert_eq!(generated.len(), 3);
+    assert!(generated.iter().all(|&t| (t as usize) < VOCAB));
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn inconsistent_remote_input_is_rejected() {
+    let weights = tiny_model();
+    let dir = temp_dir("mismatch");
+    let mut last = load(write_shard(&dir, "last.safetensors", &weights, |n| {
+        n.starts_with("model.layers.1.") || n == "model.norm.weight" || n == "lm_head.weight"
+    }));
+
+    // Tenseur désérialisé tel quel: deux lignes annoncées, une seule fournie
+    let short = Tensor { shape: vec![2, HIDDEN], data: vec![0.1; HIDDEN] };
+    assert!(last.forward(&short).is_err());
+    let overflowing = Tensor { shape: vec![usize::MAX, HIDDEN], data: vec![0.1; HIDDEN] };
+    assert!(last.forward(&overflowing).is_err());
+    assert_eq!(last.position(), 0);
+
+    last.forward(&Tensor::zeros(vec![1, HIDDEN])).unwrap();
+    assert_eq!(last.position(), 1);
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn stateless_forward_leaves_sequence_untouched() {
+    let weights = tiny_model();
+    let dir = temp_dir("stateless");
+    let path = write_shard(&dir, "model.safetensors", &weights, |_| true);
+    let mut fresh = load(path.clone());
+    let mut stepwise = load(path.clone());
+    let host = ShardHost::new();
+    host.load(
+        &BackendRegistry::builtin(),
+        ShardSpec { id: "tiny".into(), version: "1".into(), backend: "cpu".into(), path: Some(path) },
+    )
+    .unwrap();
+    // Les poids sont déjà en mémoire: une réplique n'a pas à relire le fichier
+    fs::remove_dir_all(&dir).unwrap();
+
+    let prompt = tokens(&[3, 1, 4]);
+    host.forward("tiny", &tokens(&[2, 7])).unwrap();
+    stepwise.forward(&tokens(&[2, 7])).unwrap();
+    let memory = host.loaded()[0].memory_bytes;
+
+    assert_eq!(host.forward_isolated("tiny", &prompt).unwrap(), fresh.forward(&prompt).unwrap());
+    assert_eq!(host.loaded()[0].memory_bytes, memory);
+    // La séquence servie aux pipelines reprend là où elle en était
+    assert_eq!(host.forward("tiny", &tokens(&[8])).unwrap(), stepwise.forward(&tokens(&[8])).unwrap());
+}
@@ -0,0 +1,80 @@
+// Enveloppes scellées entre nœuds
+use std::time::Duration;
+
+use cortex_id::communicator::CommunicatorMessage;
+use cortex_id::discovery::NodeRole;
+use cortex_id::envelope::SealedEnvelope;
+use cortex_id::harness::MeshHarness;
+use cortex_id::policy::DataClass;
+use libp2p::identity::{ed25519, PublicKey};
+use libp2p::PeerId;
+use tokio::sync::broadcast::error::TryRecvError;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+fn peer_of(keypair: &ed25519::Keypair) -> PeerId {
+    PeerId::from(PublicKey::from(keypair.public()))
+}
+
+#[test]
+fn only_recipients_can_open() {
+    let sender = ed25519::Keypair::generate();
+    let alice = ed25519::Keypair::generate();
+    let bob = ed25519::Keypair::generate();
+    let eve = ed25519::Keypair::generate();
+
+    let envelope = SealedEnvelope::seal(&sender, &[peer_of(&alice), peer_of(&bob)], b"prompt secret").unwrap();
+    for recipient in [&alice, &bob] {
+        let opened = envelope.open(recipient).unwrap().unwrap();
+        assert_eq!(opened.sender, peer_of(&sender));
+        assert_eq!(opened.payload, b"prompt secret");
+    }
+    assert!(envelope.open(&eve).unwrap().is_none());
+
+    // Ni le contenu, ni l'expéditeur, ni les destinataires ne circulent en clair
+    let wire = serde_json::to_string(&envelope).unwrap();
+    for hidden in [peer_of(&sender).to_string(), peer_of(&alice).to_string(), "prompt secret".into()] {
+        assert!(!wire.contains(&hidden));
+    }
+
+    let mut tampered = envelope.clone();
+    tampered.recipients.pop();
+    assert!(tampered.open(&alice).is_err() || tampered.open(&bob).is_err());
+    assert!(SealedEnvelope::seal(&sender, &[], b"x").is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn sealed_message_reaches_only_its_recipient() {
+    let mut harness = MeshHarness::new();
+    let bootstrap = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    let alice = harness.spawn_node(NodeRole::Light).await.unwrap();
+    let bob = harness.spawn_node(NodeRole::Light).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+
+    let mut alice_inbox = harness.node(alice).unwrap().handle.subscribe_messages();
+    let mut bob_inbox = harness.node(bob).unwrap().handle.subscribe_messages();
+    let alice_id = harness.node(alice).unwrap().peer_id();
+    let sender = harness.node(bootstrap).unwrap().handle.clone();
+    let message = CommunicatorMessage {
+        sender: "test".into(),
+        payload: "résume ce document".into(),
+        timestamp: 0,
+    };
+
+    // Le maillage gossip du canal se forme après les connexions: on renvoie jusqu'à réception
+    let deadline = tokio::time::Instant::now() + TIMEOUT;
+    let received = loop {
+        sender.send_sealed(DataClass::Prompt, vec![alice_id], message.clone()).await.unwrap();
+        if let Ok(Ok(inbound)) = tokio::time::timeout(Duration::from_millis(500), alice_inbox.recv()).await {
+            break Some(inbound);
+        }
+        if tokio::time::Instant::now() > deadline {
+            break None;
+        }
+    };
+    let inbound = received.expect("message not delivered");
+    assert_eq!(inbound.from, sender.peer_id());
+    assert_eq!(inbound.message.payload, message.payload);
+    assert!(matches!(bob_inbox.try_recv(), Err(TryRecvError::Empty)));
+    harness.shutdown().await.unwrap();
+}
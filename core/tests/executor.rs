@@ -0,0 +1,111 @@
+// Exécution des shards: trait, registre de backends et hôte du nœud
+#![cfg(feature = "backend-identity")]
+use std::time::Duration;
+
+use anyhow::Result;
+use cortex_id::config::CortexConfig;
+use cortex_id::discovery::NodeRole;
+use cortex_id::executor::{BackendRegistry, ShardExecutor, ShardHost, ShardSpec, Tensor};
+use cortex_id::harness::MeshHarness;
+use libp2p::identity::Keypair;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Backend de test: multiplie l'entrée par deux
+#[derive(Default)]
+struct Doubler {
+    weights: Vec<f32>,
+}
+
+impl ShardExecutor for Doubler {
+    fn load(&mut self, _spec: &ShardSpec) -> Result<()> {
+        self.weights = vec![2.0; 256];
+        Ok(())
+    }
+
+    fn forward(&mut self, input: &Tensor) -> Result<Tensor> {
+        self.forward_stateless(input)
+    }
+
+    fn forward_stateless(&self, input: &Tensor) -> Result<Tensor> {
+        Tensor::new(input.shape.clone(), input.data.iter().map(|x| x * self.weights[0]).collect())
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.weights.len() * std::mem::size_of::<f32>()
+    }
+
+    fn unload(&mut self) {
+        self.weights.clear();
+    }
+}
+
+fn create_doubler() -> Box<dyn ShardExecutor> {
+    Box::<Doubler>::default()
+}
+
+fn spec(id: &str, backend: &str) -> ShardSpec {
+    ShardSpec {
+        id: id.into(),
+        version: "1".into(),
+        backend: backend.into(),
+        path: None,
+    }
+}
+
+#[test]
+fn host_runs_loaded_shards() {
+    let mut backends = BackendRegistry::new();
+    backends.register("doubler", create_doubler);
+    let host = ShardHost::new();
+    assert!(host.load(&backends, spec("a", "missing")).is_err());
+    host.load(&backends, spec("a", "doubler")).unwrap();
+
+    let input = Tensor::new(vec![1, 2], vec![1.0, -3.0]).unwrap();
+    assert_eq!(host.forward("a", &input).unwrap().data, vec![2.0, -6.0]);
+    assert!(host.forward("b", &input).is_err());
+    assert_eq!(host.loaded()[0].memory_bytes, 1024);
+    assert!(host.shard_infos()[0].available);
+
+    assert!(host.unload("a"));
+    assert!(!host.is_loaded("a"));
+    assert!(Tensor::new(vec![2, 2], vec![0.0]).is_err());
+    assert!(BackendRegistry::builtin().names().contains(&"identity"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn node_announces_and_executes_its_shards() {
+    let mut config = CortexConfig::default();
+    config.shards.push(spec("layers-0-3", "identity"));
+    config.shards.push(spec("broken", "missing"));
+    let mut harness = MeshHarness::with_config(config);
+    let bootstrap = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    let worker = harness
+        .spawn_node_with(NodeRole::Light, Keypair::generate_ed25519(), |options| {
+            options.backends.register("doubler", create_doubler);
+        })
+        .await
+        .unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+
+    let handle = harness.node(worker).unwrap().handle.clone();
+    let input = Tensor::new(vec![3], vec![1.0, 2.0, 3.0]).unwrap();
+    assert_eq!(handle.execute("layers-0-3", input.clone()).await.unwrap(), input);
+    assert!(handle.execute("broken", input.clone()).await.is_err());
+    handle.load_shard(spec("layers-4-7", "doubler")).await.unwrap();
+    assert_eq!(handle.execute("layers-4-7", input).await.unwrap().data, vec![2.0, 4.0, 6.0]);
+
+    // Seuls les shards chargés sont annoncés
+    let worker_id = handle.peer_id().to_string();
+    let announced = harness
+        .announce_until(TIMEOUT, |h| {
+            h.node(bootstrap).unwrap().registry().nodes.get(&worker_id)
+                .is_some_and(|entry| entry.shards.iter().any(|s| s.shard_id == "layers-4-7"))
+        })
+        .await
+        .unwrap();
+    assert!(announced);
+    let entry = harness.node(bootstrap).unwrap().registry().nodes[&worker_id].clone();
+    assert!(entry.shards.iter().all(|s| s.shard_id != "broken"));
+    harness.shutdown().await.unwrap();
+}
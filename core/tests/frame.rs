@@ -0,0 +1,105 @@
+// Trames binaires de tenseurs et flux dédié entre nœuds
+use std::time::Duration;
+
+use cortex_id::executor::Tensor;
+use cortex_id::frame::{decode_frame, encode_frame, Compression, FrameOptions, Layout, WireDType, FRAME_PREFIX_LEN};
+use cortex_id::harness::MeshHarness;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+fn sample() -> Tensor {
+    Tensor::new(vec![2, 3, 4], (0..24).map(|i| (i as f32 - 11.5) * 0.37).collect()).unwrap()
+}
+
+#[test]
+fn frames_round_trip_and_detect_corruption() {
+    let tensor = sample();
+    let options = |dtype, layout, compression| FrameOptions { dtype, layout, compression };
+
+    // Encodages sans perte
+    for layout in [Layout::RowMajor, Layout::ColumnMajor] {
+        for compression in [Compression::None, Compression::Zstd] {
+            let frame = encode_frame(&tensor, 7, 3, &options(WireDType::F32, layout, compression)).unwrap();
+            let (header, decoded) = decode_frame(&frame).unwrap();
+            assert_eq!((header.request_id, header.sequence, header.layout), (7, 3, layout));
+            assert_eq!(decoded, tensor);
+        }
+    }
+
+    // Encodages réduits: erreur bornée par la précision du type
+    for (dtype, tolerance) in [(WireDType::F16, 4e-3), (WireDType::BF16, 3e-2), (WireDType::I8, 3e-2)] {
+        let frame = encode_frame(&tensor, 1, 0, &options(dtype, Layout::RowMajor, Compression::Zstd)).unwrap();
+        let (header, decoded) = decode_frame(&frame).unwrap();
+        assert_eq!((header.dtype, &header.shape), (dtype, &tensor.shape));
+        for (a, b) in decoded.data.iter().zip(&tensor.data) {
+            assert!((a - b).abs() <= tolerance * b.abs().max(1.0), "{:?}: {} vs {}", dtype, a, b);
+        }
+    }
+    let wide = Tensor::new(vec![16, 64], vec![0.25; 1024]).unwrap();
+    let full = encode_frame(&wide, 1, 0, &FrameOptions::default()).unwrap();
+    let int8 = encode_frame(&wide, 1, 0, &options(WireDType::I8, Layout::RowMajor, Compression::None)).unwrap();
+    assert!(int8.len() < full.len() / 3);
+    let plain = encode_frame(&tensor, 1, 0, &FrameOptions::default()).unwrap();
+
+    // Un octet modifié ou une trame tronquée sont rejetés
+    let mut corrupted = plain.clone();
+    let last = corrupted.len() - 1;
+    corrupted[last] ^= 0x40;
+    assert!(decode_frame(&corrupted).is_err());
+    assert!(decode_frame(&plain[..plain.len() - 4]).is_err());
+    assert!(decode_frame(b"not a frame at all, clearly").is_err());
+}
+
+#[test]
+fn oversized_declared_shape_is_rejected_before_decompression() {
+    let options = FrameOptions { compression: Compression::Zstd, ..FrameOptions::default() };
+    let mut frame = encode_frame(&Tensor::zeros(vec![4, 4]), 1, 0, &options).unwrap();
+    // Forme réécrite en 65536 × 65536 f32 (16 Gio), somme de contrôle recalculée: la trame reste
+    // minuscule et intègre, seule la taille décompressée annoncée est démesurée
+    let dims = FRAME_PREFIX_LEN..FRAME_PREFIX_LEN + 8;
+    frame[dims.clone()].copy_from_slice(&[65536u32.to_le_bytes(), 65536u32.to_le_bytes()].concat());
+    let crc_at = dims.end + 4;
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(&frame[..crc_at]);
+    crc.update(&frame[crc_at + 4..]);
+    let checksum = crc.finalize().to_le_bytes();
+    frame[crc_at..crc_at + 4].copy_from_slice(&checksum);
+
+    let error = decode_frame(&frame).unwrap_err().to_string();
+    assert!(error.contains("at most"), "{}", error);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn tensor_streams_deliver_frames_in_order_per_request() {
+    let harness = MeshHarness::start(3).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    harness.converge(TIMEOUT).await.unwrap();
+    // Deux nœuds légers, reliés seulement au bootstrap: le flux ouvre une connexion directe
+    let sender = harness.node(1).unwrap().handle.clone();
+    let receiver = harness.node(2).unwrap().handle.clone();
+
+    // Deux requêtes entrelacées: chacune reçoit ses propres trames, dans l'ordre
+    let options = FrameOptions { dtype: WireDType::BF16, compression: Compression::Zstd, ..FrameOptions::default() };
+    let mut first = sender.open_tensor_stream(receiver.peer_id(), 42).await.unwrap().with_options(options);
+    let mut second = sender.open_tensor_stream(receiver.peer_id(), 43).await.unwrap();
+    for i in 0..3 {
+        first.send(&Tensor::new(vec![2], vec![i as f32, -1.0]).unwrap()).await.unwrap();
+        second.send(&Tensor::new(vec![1], vec![10.0 + i as f32]).unwrap()).await.unwrap();
+    }
+    // Trames arrivées avant que la requête ne soit réclamée: conservées pour elle
+    let mut frames_43 = receiver.frames(sender.peer_id(), 43).unwrap();
+    let mut frames_42 = receiver.frames(sender.peer_id(), 42).unwrap();
+    assert!(receiver.frames(sender.peer_id(), 42).is_err());
+    for i in 0..3 {
+        let frame = tokio::time::timeout(TIMEOUT, frames_42.recv()).await.unwrap().unwrap();
+        assert_eq!(frame.peer, sender.peer_id());
+        assert_eq!((frame.header.request_id, frame.header.sequence), (42, i));
+        assert_eq!(frame.tensor.data, vec![i as f32, -1.0]);
+    }
+    for i in 0..3 {
+        let frame = tokio::time::timeout(TIMEOUT, frames_43.recv()).await.unwrap().unwrap();
+        assert_eq!((frame.header.request_id, frame.header.sequence), (43, i));
+        assert_eq!(frame.tensor.data, vec![10.0 + i as f32]);
+    }
+    harness.shutdown().await.unwrap();
+}
@@ -0,0 +1,72 @@
+// Keystore chiffré et droits des clés en clair
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+use cortex_id::identity::keystore::{
+    check_plaintext_permissions, decrypt_keypair, encrypt_keypair, read_encrypted, write_encrypted,
+    write_private_file,
+};
+use libp2p::identity::ed25519;
+
+mod common;
+use common::temp_path;
+
+fn mode(path: &PathBuf) -> u32 {
+    fs::metadata(path).unwrap().permissions().mode() & 0o777
+}
+
+#[test]
+fn encrypted_keystore_round_trip() {
+    let dir = temp_path("roundtrip");
+    let path = dir.join("identity.key.enc");
+    let keypair = ed25519::Keypair::generate();
+
+    write_encrypted(&path, &encrypt_keypair(&keypair, "correct horse").unwrap()).unwrap();
+    assert_eq!(mode(&path), 0o600);
+    assert_eq!(mode(&dir), 0o700);
+
+    let file = read_encrypted(&path).unwrap();
+    let restored = decrypt_keypair(&file, "correct horse").unwrap();
+    assert_eq!(restored.to_bytes(), keypair.to_bytes());
+
+    assert!(decrypt_keypair(&file, "wrong").is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn plaintext_key_permissions_are_enforced() {
+    let dir = temp_path("perms");
+    let path = dir.join("identity.key");
+    write_private_file(&path, b"secret").unwrap();
+    assert_eq!(mode(&path), 0o600);
+    check_plaintext_permissions(&path).unwrap();
+
+    // Lisible par le groupe: ramenée à 0600
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+    check_plaintext_permissions(&path).unwrap();
+    assert_eq!(mode(&path), 0o600);
+
+    // Lisible par tous: refusée
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+    assert!(check_plaintext_permissions(&path).is_err());
+    assert_eq!(mode(&path), 0o644);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rewriting_a_readable_file_is_atomic_and_private() {
+    let dir = temp_path("rewrite");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("identity.key");
+    fs::write(&path, b"old").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+    // Le contenu n'est jamais exposé avec les anciens droits: le fichier est remplacé, pas réécrit
+    write_private_file(&path, b"secret").unwrap();
+    assert_eq!(mode(&path), 0o600);
+    assert_eq!(fs::read(&path).unwrap(), b"secret");
+    let names: Vec<_> = fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name()).collect();
+    assert_eq!(names, vec!["identity.key"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,182 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use cortex_id::discovery::NodeRole;
+use cortex_id::executor::{BackendRegistry, ShardExecutor, ShardSpec, Tensor};
+use cortex_id::harness::MeshHarness;
+use cortex_id::quorum::ReplicaRequest;
+use cortex_id::registry::AnnounceMsg;
+use cortex_id::rpc::{RpcRequest, RpcResponse};
+use libp2p::identity::Keypair;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Backend de test qui renvoie l'entrée après un délai
+struct Slow;
+
+impl ShardExecutor for Slow {
+    fn load(&mut self, _spec: &ShardSpec) -> Result<()> {
+        Ok(())
+    }
+
+    fn forward(&mut self, input: &Tensor) -> Result<Tensor> {
+        self.forward_stateless(input)
+    }
+
+    fn forward_stateless(&self, input: &Tensor) -> Result<Tensor> {
+        std::thread::sleep(Duration::from_millis(1500));
+        Ok(input.clone())
+    }
+
+    fn memory_usage(&self) -> usize {
+        0
+    }
+
+    fn unload(&mut self) {}
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn registries_converge_across_nodes() {
+    let harness = MeshHarness::start(4).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+
+    harness.converge(TIMEOUT).await.unwrap();
+
+    for node in harness.running() {
+        assert_eq!(node.registry().nodes.len(), 3);
+    }
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn goodbye_announce_removes_node_immediately() {
+    let mut harness = MeshHarness::start(3).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    harness.converge(TIMEOUT).await.unwrap();
+
+    let leaving = harness.node(2).unwrap().peer_id();
+    harness.leave(2).await.unwrap();
+
+    let removed = harness
+        .wait_until(TIMEOUT, |h| h.running().all(|n| !n.knows(&leaving)))
+        .await;
+    assert!(removed, "departed node still present in a registry");
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn late_joiner_converges_with_existing_mesh() {
+    let mut harness = MeshHarness::start(2).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    harness.converge(TIMEOUT).await.unwrap();
+
+    harness.spawn_node(NodeRole::Light).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    harness.converge(TIMEOUT).await.unwrap();
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn light_node_finds_bootstrap_through_dht() {
+    let harness = MeshHarness::start(2).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+
+    let bootstrap = harness.node(0).unwrap().peer_id();
+    let light = &harness.node(1).unwrap().handle;
+    let providers = light.find_providers().await.unwrap();
+    assert!(providers.contains(&bootstrap), "providers: {:?}", providers);
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn goodbye_for_another_node_is_ignored() {
+    let harness = MeshHarness::start(3).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    harness.converge(TIMEOUT).await.unwrap();
+
+    let attacker = harness.node(1).unwrap();
+    let victim = harness.node(2).unwrap().peer_id();
+    let goodbye = AnnounceMsg { leaving: true, ..AnnounceMsg::new(victim.to_string()) };
+    attacker.handle.publish_announce(goodbye).await.unwrap();
+    // Annonce témoin du même émetteur, reçue après l'annonce usurpée
+    let marker = AnnounceMsg { shards: vec!["marker".into()], ..AnnounceMsg::new(attacker.peer_id().to_string()) };
+    attacker.handle.publish_announce(marker).await.unwrap();
+
+    let attacker_id = attacker.peer_id().to_string();
+    let received = harness
+        .wait_until(TIMEOUT, |h| {
+            h.node(0).unwrap().registry().nodes[&attacker_id].shards.iter().any(|s| s.shard_id == "marker")
+        })
+        .await;
+    assert!(received);
+    assert!(harness.node(0).unwrap().knows(&victim), "a forged goodbye evicted the victim");
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn shutdown_answers_rpc_in_flight() {
+    let mut harness = MeshHarness::new();
+    let origin = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    let worker = harness
+        .spawn_node_with(NodeRole::Light, Keypair::generate_ed25519(), |options| {
+            options.backends = BackendRegistry::new();
+            options.backends.register("slow", || Box::new(Slow));
+        })
+        .await
+        .unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    let worker_handle = harness.node(worker).unwrap().handle.clone();
+    let spec = ShardSpec { id: "slow".into(), version: "1".into(), backend: "slow".into(), path: None };
+    worker_handle.load_shard(spec).await.unwrap();
+
+    let handle = harness.node(origin).unwrap().handle.clone();
+    let input = Tensor::new(vec![1, 2], vec![1.0, 2.0]).unwrap();
+    let request = RpcRequest::Replica(ReplicaRequest { shard_id: "slow".into(), input: input.clone() });
+    let call = tokio::spawn(async move { handle.rpc(worker_handle.peer_id(), request).await });
+
+    // Arrêt du nœud pendant qu'il sert la requête: la réponse part avant la fin du drainage
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    harness.leave(worker).await.unwrap();
+    match call.await.unwrap().unwrap() {
+        RpcResponse::Tensor(output) => assert_eq!(output, input),
+        other => panic!("unexpected response: {:?}", other),
+    }
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn connection_limit_denials_are_counted() {
+    let mut harness = MeshHarness::new();
+    harness.config_mut().limits.max_established_incoming = Some(1);
+    let bootstrap = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    *harness.config_mut() = Default::default();
+    harness.spawn_many(2).await.unwrap();
+
+    let metrics = harness.node(bootstrap).unwrap().handle.metrics();
+    let denied = harness
+        .wait_until(TIMEOUT, |_| metrics.snapshot().inbound_denied_connection_limit > 0)
+        .await;
+    assert!(denied, "second dial to the bootstrap was not denied");
+    assert_eq!(metrics.snapshot().connections_open, 1);
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn periodic_tasks_stop_with_the_node() {
+    let mut harness = MeshHarness::new();
+    let index = harness
+        .spawn_node_with(NodeRole::Bootstrap, Keypair::generate_ed25519(), |options| options.periodic_tasks = true)
+        .await
+        .unwrap();
+    // Une poignée reste vivante après l'arrêt, comme celle du serveur d'API
+    let handle = harness.node(index).unwrap().handle.clone();
+    let registry = handle.registry();
+    harness.shutdown().await.unwrap();
+
+    // Il ne reste que la poignée et cette référence: la tâche d'affichage du registre est finie
+    let deadline = tokio::time::Instant::now() + TIMEOUT;
+    while std::sync::Arc::strong_count(&registry) > 2 && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(std::sync::Arc::strong_count(&registry), 2);
+}
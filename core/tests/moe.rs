@@ -0,0 +1,226 @@
+// Couches Mixture-of-Experts: routage top-k vers les nœuds experts et repli
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use cortex_id::discovery::NodeRole;
+use cortex_id::executor::{ShardExecutor, ShardSpec, Tensor};
+use cortex_id::harness::MeshHarness;
+use cortex_id::moe::{
+    expert_shard_id, gate_weights, parse_expert, rank_experts, ExpertRequest, FallbackPolicy, MoeConfig, MoeLayer, Router,
+};
+use cortex_id::rpc::{RpcRequest, RpcResponse};
+use libp2p::identity::Keypair;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Expert de test: multiplie l'entrée par `n + 1` (n: indice de l'expert)
+#[derive(Default)]
+struct Scale {
+    factor: f32,
+}
+
+impl ShardExecutor for Scale {
+    fn load(&mut self, spec: &ShardSpec) -> Result<()> {
+        let Some((_, _, expert)) = parse_expert(&spec.id) else {
+            bail!("Not an expert shard: {}", spec.id);
+        };
+        self.factor = (expert + 1) as f32;
+        Ok(())
+    }
+
+    fn forward(&mut self, input: &Tensor) -> Result<Tensor> {
+        self.forward_stateless(input)
+    }
+
+    fn forward_stateless(&self, input: &Tensor) -> Result<Tensor> {
+        if self.factor == 2.0 {
+            bail!("Expert 1 is broken");
+        }
+        Tensor::new(input.shape.clone(), input.data.iter().map(|x| x * self.factor).collect())
+    }
+
+    fn memory_usage(&self) -> usize {
+        0
+    }
+
+    fn unload(&mut self) {}
+}
+
+fn create_scale() -> Box<dyn ShardExecutor> {
+    Box::<Scale>::default()
+}
+
+/// Expert défaillant: annonce la forme de l'entrée sans en fournir les données
+struct Truncate;
+
+impl ShardExecutor for Truncate {
+    fn load(&mut self, _spec: &ShardSpec) -> Result<()> {
+        Ok(())
+    }
+
+    fn forward(&mut self, input: &Tensor) -> Result<Tensor> {
+        self.forward_stateless(input)
+    }
+
+    fn forward_stateless(&self, input: &Tensor) -> Result<Tensor> {
+        Ok(Tensor { shape: input.shape.clone(), data: Vec::new() })
+    }
+
+    fn memory_usage(&self) -> usize {
+        0
+    }
+
+    fn unload(&mut self) {}
+}
+
+fn create_truncate() -> Box<dyn ShardExecutor> {
+    Box::new(Truncate)
+}
+
+fn expert(n: usize) -> ShardSpec {
+    ShardSpec { id: expert_shard_id("mix", 3, n), version: "1".into(), backend: "scale".into(), path: None }
+}
+
+#[test]
+fn router_ranks_and_weights_experts() {
+    assert_eq!(parse_expert("mix/layer-3/expert-7"), Some(("mix", 3, 7)));
+    assert_eq!(parse_expert("mix/layers-0-3"), None);
+
+    let router = Router::new(Tensor::new(vec![3, 2], vec![1.0, 0.0, 0.0, 1.0, 1.0, 1.0]).unwrap()).unwrap();
+    let logits = router.logits(&Tensor::new(vec![1, 2], vec![2.0, -1.0]).unwrap()).unwrap();
+    assert_eq!(logits, vec![vec![2.0, -1.0, 1.0]]);
+    assert_eq!(rank_experts(&logits[0]), vec![0, 2, 1]);
+    let weights = gate_weights(&logits[0], &[0, 2]);
+    assert!((weights[0] - 1.0 / (1.0 + (-1.0f32).exp())).abs() < 1e-6);
+    assert!((weights.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+    assert!(router.logits(&Tensor::new(vec![3], vec![0.0; 3]).unwrap()).is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn missing_and_failing_experts_fall_back() {
+    let mut harness = MeshHarness::new();
+    let origin = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    let mut workers = Vec::new();
+    for _ in 0..2 {
+        let index = harness
+            .spawn_node_with(NodeRole::Light, Keypair::generate_ed25519(), |options| {
+                options.backends.register("scale", create_scale);
+            })
+            .await
+            .unwrap();
+        workers.push(harness.node(index).unwrap().handle.clone());
+    }
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    // Expert 1 échoue, expert 3 n'est hébergé nulle part
+    workers[0].load_shard(expert(0)).await.unwrap();
+    workers[0].load_shard(expert(1)).await.unwrap();
+    workers[1].load_shard(expert(2)).await.unwrap();
+    let handle = harness.node(origin).unwrap().handle.clone();
+    let announced = harness
+        .announce_until(TIMEOUT, |h| {
+            let registry = h.node(origin).unwrap().registry();
+            registry.nodes.values().flat_map(|n| &n.shards).filter(|s| parse_expert(&s.shard_id).is_some()).count() == 3
+        })
+        .await
+        .unwrap();
+    assert!(announced);
+
+    // Experts dirigés selon ±x et ±y
+    let gate = Tensor::new(vec![4, 2], vec![1.0, 0.0, 0.0, 1.0, -1.0, 0.0, 0.0, -1.0]).unwrap();
+    let layer = MoeLayer::new(handle.clone(), "mix", 3, Router::new(gate.clone()).unwrap());
+    let hosts = layer.hosts().unwrap();
+    assert_eq!(hosts.keys().copied().collect::<Vec<_>>(), vec![0, 1, 2]);
+    assert_eq!(hosts[&2], vec![workers[1].peer_id()]);
+
+    // Classements: [0, 1, 3, 2] puis [2, 3, 1, 0]; 1 et 3 indisponibles, donc {0, 2} partout
+    let hidden = Tensor::new(vec![2, 2], vec![1.0, 0.5, -1.0, -0.2]).unwrap();
+    let output = layer.forward(&hidden).await.unwrap();
+    for (token, x) in hidden.data.chunks(2).enumerate() {
+        let logits = [x[0], x[1], -x[0], -x[1]];
+        let w = gate_weights(&logits, &[0, 2]);
+        for (i, v) in x.iter().enumerate() {
+            let expected = w[0] * v + w[1] * 3.0 * v;
+            assert!((output.data[token * 2 + i] - expected).abs() < 1e-5);
+        }
+    }
+
+    // Sans repli, le premier token ne garde que l'expert 0, le second que l'expert 2
+    let config = MoeConfig { fallback: FallbackPolicy::Drop, ..MoeConfig::default() };
+    let dropped = MoeLayer::new(handle.clone(), "mix", 3, Router::new(gate.clone()).unwrap()).with_config(config);
+    assert_eq!(dropped.forward(&hidden).await.unwrap().data, vec![1.0, 0.5, -3.0, -0.6]);
+
+    let config = MoeConfig { fallback: FallbackPolicy::Fail, ..MoeConfig::default() };
+    let strict = MoeLayer::new(handle, "mix", 3, Router::new(gate).unwrap()).with_config(config);
+    assert!(strict.forward(&hidden).await.is_err());
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn crashed_expert_host_is_replaced() {
+    let mut harness = MeshHarness::new();
+    let origin = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    let mut workers = Vec::new();
+    for _ in 0..2 {
+        let index = harness
+            .spawn_node_with(NodeRole::Light, Keypair::generate_ed25519(), |options| {
+                options.backends.register("scale", create_scale);
+            })
+            .await
+            .unwrap();
+        workers.push(index);
+    }
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    for &i in &workers {
+        harness.node(i).unwrap().handle.load_shard(expert(0)).await.unwrap();
+    }
+    let announced = harness
+        .announce_until(TIMEOUT, |h| {
+            let registry = h.node(origin).unwrap().registry();
+            registry.nodes.values().flat_map(|n| &n.shards).filter(|s| parse_expert(&s.shard_id).is_some()).count() == 2
+        })
+        .await
+        .unwrap();
+    assert!(announced);
+
+    // Sans repli possible: seul un autre hôte du même expert peut sauver la couche
+    let handle = harness.node(origin).unwrap().handle.clone();
+    let config = MoeConfig { fallback: FallbackPolicy::Fail, ..MoeConfig::default() };
+    let layer = MoeLayer::new(handle, "mix", 3, Router::new(Tensor::new(vec![1, 2], vec![1.0, 0.0]).unwrap()).unwrap())
+        .with_config(config);
+    let hosts = layer.hosts().unwrap()[&0].clone();
+    assert_eq!(hosts.len(), 2);
+    let preferred = workers.iter().copied().find(|&i| harness.node(i).unwrap().peer_id() == hosts[0]).unwrap();
+    harness.crash(preferred).unwrap();
+
+    let hidden = Tensor::new(vec![1, 2], vec![0.5, -1.5]).unwrap();
+    assert_eq!(layer.forward(&hidden).await.unwrap().data, vec![0.5, -1.5]);
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn malformed_tensors_are_rejected_both_ways() {
+    let mut harness = MeshHarness::new();
+    let origin = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    let worker = harness
+        .spawn_node_with(NodeRole::Light, Keypair::generate_ed25519(), |options| {
+            options.backends.register("truncate", create_truncate);
+        })
+        .await
+        .unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    let spec = ShardSpec { backend: "truncate".into(), ..expert(0) };
+    let worker = harness.node(worker).unwrap().handle.clone();
+    worker.load_shard(spec.clone()).await.unwrap();
+    let handle = harness.node(origin).unwrap().handle.clone();
+    let request = |input: Tensor| RpcRequest::Expert(ExpertRequest { shard_id: spec.id.clone(), input });
+
+    // Requête dont les données ne remplissent pas la forme: aucune trame n'est émise
+    let short = Tensor { shape: vec![2, 2], data: vec![1.0; 2] };
+    assert!(handle.rpc(worker.peer_id(), request(short)).await.is_err());
+
+    // Sortie incohérente: le pair ne peut pas l'encoder et répond par une erreur
+    let hidden = Tensor::new(vec![1, 2], vec![0.5, -1.5]).unwrap();
+    let response = handle.rpc(worker.peer_id(), request(hidden)).await.unwrap();
+    assert!(matches!(response, RpcResponse::Error { .. }));
+    harness.shutdown().await.unwrap();
+}
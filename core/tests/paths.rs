@@ -0,0 +1,30 @@
+// Répertoire Cortex et profils
+use cortex_id::identity::CortexPaths;
+
+#[test]
+fn profiles_are_isolated_under_home() {
+    let root = CortexPaths::new("/srv/cortex", None).unwrap();
+    assert_eq!(root.key().to_str(), Some("/srv/cortex/identity.key"));
+    assert_eq!(root.config().to_str(), Some("/srv/cortex/config.yaml"));
+
+    let a = root.with_profile(Some("node-a".into())).unwrap();
+    let b = root.with_profile(Some("node-b".into())).unwrap();
+    assert_eq!(a.dir().to_str(), Some("/srv/cortex/profiles/node-a"));
+    assert_eq!(a.registry().to_str(), Some("/srv/cortex/profiles/node-a/registry.json"));
+    assert_eq!(a.shard_cache().to_str(), Some("/srv/cortex/profiles/node-a/shards"));
+    assert_ne!(a.encrypted_key(), b.encrypted_key());
+}
+
+#[test]
+fn profile_names_cannot_escape_home() {
+    for name in ["", "..", "../x", "a/b", ".hidden"] {
+        assert!(CortexPaths::new("/srv/cortex", Some(name.into())).is_err(), "{:?}", name);
+    }
+}
+
+#[test]
+fn explicit_home_and_profile_take_priority() {
+    let paths = CortexPaths::resolve(Some("/srv/cortex".into()), Some("node-a".into())).unwrap();
+    assert_eq!(paths, CortexPaths::new("/srv/cortex", Some("node-a".into())).unwrap());
+    assert!(CortexPaths::resolve(Some("/srv/cortex".into()), Some("../escape".into())).is_err());
+}
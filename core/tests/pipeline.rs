@@ -0,0 +1,308 @@
+// Inférence en pipeline: plan des étapes et génération répartie sur plusieurs nœuds
+#![cfg(feature = "backend-cpu")]
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use cortex_id::discovery::NodeRole;
+use cortex_id::executor::cpu::CpuTransformer;
+use cortex_id::executor::{ShardExecutor, ShardSpec, Tensor};
+use cortex_id::harness::{HarnessNode, MeshHarness};
+use cortex_id::pipeline::{parse_layer_range, PipelineCoordinator, PipelinePlan};
+use cortex_id::quorum::ReplicaRequest;
+use cortex_id::rpc::{RpcRequest, RpcResponse};
+use cortex_id::scheduler::SchedulePlan;
+use cortex_id::simulation::{LinkFaults, SimNetwork};
+
+mod common;
+use common::temp_dir;
+use common::tiny::{tiny_model, write_shard, HIDDEN, VOCAB};
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+fn spec(id: &str, path: PathBuf) -> ShardSpec {
+    ShardSpec { id: id.into(), version: "1".into(), backend: "cpu".into(), path: Some(path) }
+}
+
+/// Le nœud connaît les `providers` fournisseurs des shards du modèle, lui compris
+/// (chaque nœud annonce aussi un shard « light », à ne pas compter)
+fn sees_providers(node: &HarnessNode, providers: usize) -> bool {
+    let remote = PipelinePlan::candidates(&node.registry()).into_iter().filter(|(_, shard)| shard.starts_with("tiny/")).count();
+    let own = node.handle.shards().loaded().into_iter().filter(|shard| shard.id.starts_with("tiny/")).count();
+    remote + own == providers
+}
+
+#[test]
+fn plan_uses_fewest_stages() {
+    assert_eq!(parse_layer_range("llama/layers-0-15"), Some(("llama", 0, 15)));
+    assert_eq!(parse_layer_range("llama/layers-4-2"), None);
+    assert_eq!(parse_layer_range("layers-0-3"), None);
+
+    let candidates = [
+        ("b", "llama/layers-0-3"),
+        ("a", "llama/layers-0-3"),
+        ("c", "llama/layers-4-7"),
+        ("d", "llama/layers-0-5"),
+        ("e", "llama/layers-6-7"),
+        ("f", "other/layers-0-7"),
+    ]
+    .map(|(peer, shard)| (peer.to_string(), shard.to_string()));
+    // Deux étapes suffisent: la plus longue première plage l'emporte
+    let plan = PipelinePlan::build(candidates.clone(), "llama", 8).unwrap();
+    let chain: Vec<_> = plan.stages.iter().map(|s| (s.peer.as_str(), s.first_layer, s.last_layer)).collect();
+    assert_eq!(chain, vec![("d", 0, 5), ("e", 6, 7)]);
+    assert!(PipelinePlan::build(candidates, "llama", 9).is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn pipeline_generation_matches_local_model() {
+    let weights = tiny_model();
+    let dir = temp_dir("generate");
+    let full = write_shard(&dir, "full.safetensors", &weights, |_| true);
+    let first = write_shard(&dir, "first.safetensors", &weights, |n| {
+        n.starts_with("model.embed_tokens") || n.starts_with("model.layers.0.")
+    });
+    let last = write_shard(&dir, "last.safetensors", &weights, |n| {
+        n.starts_with("model.layers.1.") || n == "model.norm.weight" || n == "lm_head.weight"
+    });
+    let prompt = [3, 1, 4, 1, 5, 9, 2];
+    let mut local = CpuTransformer::default();
+    local.load(&spec("tiny", full)).unwrap();
+    let expected = local.generate(&prompt, 4).unwrap();
+
+    let mut harness = MeshHarness::new();
+    harness.config_mut().policy.allow_prompt_split = true;
+    let origin = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    let stages = harness.spawn_many(2).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    let node = |i: usize| harness.node(i).unwrap().handle.clone();
+    node(stages[0]).load_shard(spec("tiny/layers-0-0", first)).await.unwrap();
+    node(stages[1]).load_shard(spec("tiny/layers-1-1", last)).await.unwrap();
+
+    // Chaque nœud connaît les shards (et les adresses) des autres
+    let announced = harness
+        .announce_until(TIMEOUT, |h| h.running().all(|n| sees_providers(n, 2)))
+        .await
+        .unwrap();
+    assert!(announced);
+
+    let coordinator = PipelineCoordinator::from_registry(node(origin), "tiny", 2).unwrap().with_micro_batch(2);
+    let peers: Vec<_> = coordinator.plan().stages.iter().map(|s| s.peer.clone()).collect();
+    assert_eq!(peers, vec![node(stages[0]).peer_id().to_string(), node(stages[1]).peer_id().to_string()]);
+    let generation = coordinator.generate(&prompt, 4).await.unwrap();
+    assert_eq!(generation.tokens, expected);
+    assert_eq!(generation.logits.len(), VOCAB);
+
+    // Une seconde génération repart d'un cache vide
+    assert_eq!(coordinator.generate(&prompt, 4).await.unwrap().tokens, expected);
+    harness.shutdown().await.unwrap();
+    fs::remove_dir_all(dir).ok();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn vanished_stage_is_reassigned() {
+    let weights = tiny_model();
+    let dir = temp_dir("failover");
+    let full = write_shard(&dir, "full.safetensors", &weights, |_| true);
+    let first = write_shard(&dir, "first.safetensors", &weights, |n| {
+        n.starts_with("model.embed_tokens") || n.starts_with("model.layers.0.")
+    });
+    let last = write_shard(&dir, "last.safetensors", &weights, |n| {
+        n.starts_with("model.layers.1.") || n == "model.norm.weight" || n == "lm_head.weight"
+    });
+    let prompt = [2, 7, 1, 8];
+    let mut local = CpuTransformer::default();
+    local.load(&spec("tiny", full)).unwrap();
+    let expected = local.generate(&prompt, 3).unwrap();
+
+    let mut harness = MeshHarness::new();
+    harness.config_mut().policy.allow_prompt_split = true;
+    let origin = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    let stages = harness.spawn_many(3).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    let node = |i: usize| harness.node(i).unwrap().handle.clone();
+    node(stages[0]).load_shard(spec("tiny/layers-0-0", first)).await.unwrap();
+    // Deux fournisseurs de la dernière étape
+    for &i in &stages[1..] {
+        node(i).load_shard(spec("tiny/layers-1-1", last.clone())).await.unwrap();
+    }
+    let announced = harness
+        .announce_until(TIMEOUT, |h| h.running().all(|n| sees_providers(n, 3)))
+        .await
+        .unwrap();
+    assert!(announced);
+
+    // Le plan signé en vigueur l'emporte sur le registre, qui prendrait le plus petit PeerId
+    let default = PipelineCoordinator::from_registry(node(origin), "tiny", 2).unwrap().plan();
+    let other = stages[1..].iter()
+        .map(|&i| node(i).peer_id().to_string())
+        .find(|peer| *peer != default.stages[1].peer)
+        .unwrap();
+    let mut chain = default.stages.clone();
+    chain[1].peer = other.clone();
+    let plan = SchedulePlan { model: "tiny".into(), stages: chain, experts: Vec::new(), expected_latency_ms: 1.0 };
+    node(origin).publish_plan(plan).await.unwrap();
+    assert!(PipelineCoordinator::for_model(node(origin), "tiny", 3).is_err());
+    let coordinator = PipelineCoordinator::for_model(node(origin), "tiny", 2).unwrap();
+    assert_eq!(coordinator.plan().stages[1].peer, other);
+    assert_eq!(coordinator.generate(&prompt, 3).await.unwrap().tokens, expected);
+
+    // Le fournisseur choisi disparaît sans prévenir: il reste dans les registres jusqu'à expiration
+    let chosen = coordinator.plan().stages[1].peer.clone();
+    let (gone, spare) = if node(stages[1]).peer_id().to_string() == chosen {
+        (stages[1], stages[2])
+    } else {
+        (stages[2], stages[1])
+    };
+    let spare_peer = node(spare).peer_id().to_string();
+    harness.crash(gone).unwrap();
+    assert_eq!(coordinator.generate(&prompt, 3).await.unwrap().tokens, expected);
+    assert_eq!(coordinator.plan().stages[1].peer, spare_peer);
+
+    // Plus aucun fournisseur: l'erreur remonte à l'appelant
+    harness.crash(spare).unwrap();
+    assert!(coordinator.generate(&prompt, 3).await.is_err());
+    harness.shutdown().await.unwrap();
+    fs::remove_dir_all(dir).ok();
+}
+
+/// Arrête le fournisseur de l'étape `crashed` pendant le décodage: la génération se termine sur
+/// un autre fournisseur du même shard, sans que l'autre étape ne change de session ni ne recalcule
+async fn crash_stage_mid_generation(crashed: usize, name: &str) {
+    let weights = tiny_model();
+    let dir = temp_dir(name);
+    let full = write_shard(&dir, "full.safetensors", &weights, |_| true);
+    let first = write_shard(&dir, "first.safetensors", &weights, |n| {
+        n.starts_with("model.embed_tokens") || n.starts_with("model.layers.0.")
+    });
+    let last = write_shard(&dir, "last.safetensors", &weights, |n| {
+        n.starts_with("model.layers.1.") || n == "model.norm.weight" || n == "lm_head.weight"
+    });
+    let shards = [("tiny/layers-0-0", first), ("tiny/layers-1-1", last)];
+    let prompt = [4, 6, 2, 9];
+    let mut local = CpuTransformer::default();
+    local.load(&spec("tiny", full)).unwrap();
+    let expected = local.generate(&prompt, 10).unwrap();
+
+    let sim = SimNetwork::new(48);
+    let mut harness = MeshHarness::new().with_transport_factory(sim.transport_factory());
+    harness.config_mut().policy.allow_prompt_split = true;
+    let origin = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    // Deux fournisseurs pour l'étape arrêtée, un seul pour l'autre
+    let providers = harness.spawn_many(3).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    let node = |i: usize| harness.node(i).unwrap().handle.clone();
+    let (crashed_shard, crashed_path) = &shards[crashed];
+    let (kept_shard, kept_path) = &shards[1 - crashed];
+    node(providers[0]).load_shard(spec(kept_shard, kept_path.clone())).await.unwrap();
+    for &i in &providers[1..] {
+        node(i).load_shard(spec(crashed_shard, crashed_path.clone())).await.unwrap();
+    }
+    let announced = harness
+        .announce_until(TIMEOUT, |h| h.running().all(|n| sees_providers(n, 3)))
+        .await
+        .unwrap();
+    assert!(announced);
+
+    // Liens lents: chaque token met plusieurs dizaines de millisecondes à traverser la chaîne
+    sim.set_default_faults(LinkFaults::with_latency(Duration::from_millis(20)));
+    let coordinator = PipelineCoordinator::from_registry(node(origin), "tiny", 2).unwrap().with_micro_batch(1);
+    let chosen = coordinator.plan().stages[crashed].peer.clone();
+    let (gone, spare) = if node(providers[1]).peer_id().to_string() == chosen {
+        (providers[1], providers[2])
+    } else {
+        (providers[2], providers[1])
+    };
+    let spare_peer = node(spare).peer_id().to_string();
+
+    // Le fournisseur s'arrête une fois le prompt traité, pendant le décodage
+    let sessions = node(providers[0]).stage_sessions();
+    let generation = coordinator.generate(&prompt, 10);
+    let crash = async {
+        loop {
+            match sessions.position(kept_shard) {
+                Some((session, processed)) if processed >= prompt.len() + 2 => {
+                    harness.crash(gone).unwrap();
+                    return session;
+                },
+                _ => tokio::time::sleep(Duration::from_millis(5)).await,
+            }
+        }
+    };
+    let (generation, session) = tokio::join!(generation, crash);
+    assert_eq!(generation.unwrap().tokens, expected);
+    // La panne a bien été constatée par cette génération, qui s'est terminée sur le remplaçant
+    assert_eq!(coordinator.plan().stages[crashed].peer, spare_peer);
+    // L'autre étape a poursuivi la même session: chaque position n'y a été calculée qu'une fois
+    assert_eq!(sessions.position(kept_shard), Some((session, prompt.len() + 9)));
+    harness.shutdown().await.unwrap();
+    fs::remove_dir_all(dir).ok();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn last_stage_crashing_mid_generation_is_reassigned() {
+    crash_stage_mid_generation(1, "midway-last").await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn first_stage_crashing_mid_generation_is_reassigned() {
+    crash_stage_mid_generation(0, "midway-first").await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn replicas_do_not_disturb_running_generation() {
+    let weights = tiny_model();
+    let dir = temp_dir("replica");
+    let full = write_shard(&dir, "full.safetensors", &weights, |_| true);
+    let first = write_shard(&dir, "first.safetensors", &weights, |n| {
+        n.starts_with("model.embed_tokens") || n.starts_with("model.layers.0.")
+    });
+    let last = write_shard(&dir, "last.safetensors", &weights, |n| {
+        n.starts_with("model.layers.1.") || n == "model.norm.weight" || n == "lm_head.weight"
+    });
+    let prompt = [5, 3, 8, 1];
+    let mut local = CpuTransformer::default();
+    local.load(&spec("tiny", full)).unwrap();
+    let expected = local.generate(&prompt, 8).unwrap();
+
+    let mut harness = MeshHarness::new();
+    harness.config_mut().policy.allow_prompt_split = true;
+    let origin = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    let stages = harness.spawn_many(2).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    let node = |i: usize| harness.node(i).unwrap().handle.clone();
+    node(stages[0]).load_shard(spec("tiny/layers-0-0", first)).await.unwrap();
+    node(stages[1]).load_shard(spec("tiny/layers-1-1", last)).await.unwrap();
+    let announced = harness
+        .announce_until(TIMEOUT, |h| h.running().all(|n| sees_providers(n, 2)))
+        .await
+        .unwrap();
+    assert!(announced);
+
+    // Des répliques de validation croisée visent la dernière étape pendant toute la génération
+    let coordinator = PipelineCoordinator::from_registry(node(origin), "tiny", 2).unwrap().with_micro_batch(1);
+    let done = AtomicBool::new(false);
+    let generation = async {
+        let generation = coordinator.generate(&prompt, 8).await;
+        done.store(true, Ordering::SeqCst);
+        generation
+    };
+    let (origin, target) = (node(origin), node(stages[1]).peer_id());
+    let replicas = async {
+        let mut served = 0;
+        while !done.load(Ordering::SeqCst) {
+            let input = Tensor::zeros(vec![1, HIDDEN]);
+            let request = RpcRequest::Replica(ReplicaRequest { shard_id: "tiny/layers-1-1".into(), input });
+            if let RpcResponse::Tensor(_) = origin.rpc(target, request).await.unwrap() {
+                served += 1;
+            }
+        }
+        served
+    };
+    let (generation, served) = tokio::join!(generation, replicas);
+    assert!(served > 0);
+    assert_eq!(generation.unwrap().tokens, expected);
+    harness.shutdown().await.unwrap();
+    fs::remove_dir_all(dir).ok();
+}
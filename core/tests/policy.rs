@@ -0,0 +1,93 @@
+// Politique de consentement et de localité des données
+use std::time::Duration;
+
+use cortex_id::communicator::CommunicatorMessage;
+use cortex_id::config::CortexConfig;
+use cortex_id::discovery::NodeRole;
+use cortex_id::harness::MeshHarness;
+use cortex_id::policy::{DataClass, EgressRequest, PolicyAction, PolicyConfig, PolicyEngine, PolicyRule};
+use libp2p::PeerId;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+fn request(data: DataClass, peer: &str, trust_domain: Option<&str>) -> EgressRequest {
+    EgressRequest {
+        data,
+        peer: peer.to_string(),
+        trust_domain: trust_domain.map(String::from),
+        split: false,
+    }
+}
+
+#[test]
+fn first_matching_rule_wins() {
+    let friend = PeerId::random().to_string();
+    let yaml = format!(
+        r#"
+default: deny
+rules:
+  - data: [kv_cache]
+    trust_domains: [acme]
+    action: deny
+  - trust_domains: [acme]
+    action: allow
+  - data: [output]
+    peers: ["{}"]
+    action: allow
+"#,
+        friend
+    );
+    let config: PolicyConfig = serde_yaml::from_str(&yaml).unwrap();
+    config.validate().unwrap();
+    let engine = PolicyEngine::new(config);
+    let stranger = PeerId::random().to_string();
+
+    assert!(engine.evaluate(&request(DataClass::Prompt, &stranger, Some("acme"))).allowed);
+    assert!(!engine.evaluate(&request(DataClass::KvCache, &stranger, Some("acme"))).allowed);
+    assert!(engine.evaluate(&request(DataClass::Output, &friend, None)).allowed);
+    let denied = engine.evaluate(&request(DataClass::Prompt, &friend, None));
+    assert!(!denied.allowed);
+    assert_eq!(denied.rule, None);
+
+    // Découpage d'un prompt interdit par défaut, même vers un domaine autorisé
+    let mut split = request(DataClass::Prompt, &stranger, Some("acme"));
+    split.split = true;
+    assert!(!engine.evaluate(&split).allowed);
+
+    // Seuls les contrôles effectifs sont enregistrés
+    assert!(engine.decisions().is_empty());
+    assert!(engine.check(request(DataClass::Prompt, &stranger, None)).is_err());
+    assert_eq!(engine.decisions().len(), 1);
+    assert!(!engine.loggable("secret").contains("secret"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn denied_prompt_never_leaves_the_node() {
+    let mut config = CortexConfig::default();
+    config.policy.default = PolicyAction::Deny;
+    config.policy.rules.push(PolicyRule {
+        data: vec![DataClass::Output],
+        action: PolicyAction::Allow,
+        ..PolicyRule::default()
+    });
+    let mut harness = MeshHarness::with_config(config);
+    let bootstrap = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    let light = harness.spawn_node(NodeRole::Light).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+
+    let handle = harness.node(bootstrap).unwrap().handle.clone();
+    let target = harness.node(light).unwrap().peer_id();
+    let message = CommunicatorMessage {
+        sender: "test".into(),
+        payload: "données du patient".into(),
+        timestamp: 0,
+    };
+    assert!(handle.send_sealed(DataClass::Prompt, vec![target], message.clone()).await.is_err());
+    handle.send_sealed(DataClass::Output, vec![target], message).await.unwrap();
+
+    let decisions = handle.policy().decisions();
+    assert_eq!(decisions.len(), 2);
+    assert!(!decisions[0].allowed);
+    assert!(decisions[1].allowed);
+    harness.shutdown().await.unwrap();
+}
@@ -0,0 +1,192 @@
+// Validation croisée: comparaison des sorties, quorum et signalement des dissidents
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use cortex_id::config::{CortexConfig, TrustRootConfig};
+use cortex_id::discovery::NodeRole;
+use cortex_id::executor::{BackendRegistry, ShardExecutor, ShardSpec, Tensor};
+use cortex_id::harness::MeshHarness;
+use cortex_id::quorum::{DissentReporter, QuorumConfig, QuorumExecutor, Tolerance};
+use cortex_id::trust::{encode_public_key, NodeCertificate};
+use libp2p::identity::{ed25519, Keypair};
+use libp2p::PeerId;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+const SHARD: &str = "tiny/layers-0-3";
+
+/// Backend de test: multiplie l'entrée par deux, puis ajoute un biais (nul si honnête)
+struct Biased {
+    bias: f32,
+}
+
+impl ShardExecutor for Biased {
+    fn load(&mut self, _spec: &ShardSpec) -> Result<()> {
+        Ok(())
+    }
+
+    fn forward(&mut self, input: &Tensor) -> Result<Tensor> {
+        self.forward_stateless(input)
+    }
+
+    fn forward_stateless(&self, input: &Tensor) -> Result<Tensor> {
+        Tensor::new(input.shape.clone(), input.data.iter().map(|x| x * 2.0 + self.bias).collect())
+    }
+
+    fn memory_usage(&self) -> usize {
+        0
+    }
+
+    fn unload(&mut self) {}
+}
+
+fn honest() -> Box<dyn ShardExecutor> {
+    Box::new(Biased { bias: 0.0 })
+}
+
+fn dishonest() -> Box<dyn ShardExecutor> {
+    Box::new(Biased { bias: 0.5 })
+}
+
+#[derive(Default)]
+struct Reports(Mutex<Vec<(PeerId, String)>>);
+
+impl DissentReporter for Reports {
+    fn report_dissent(&self, peer: &PeerId, shard_id: &str) {
+        self.0.lock().unwrap().push((*peer, shard_id.to_string()));
+    }
+}
+
+#[test]
+fn tolerance_and_threshold() {
+    let a = Tensor::new(vec![2], vec![1.0, -2.0]).unwrap();
+    let close = Tensor::new(vec![2], vec![1.000_001, -2.0]).unwrap();
+    let far = Tensor::new(vec![2], vec![1.1, -2.0]).unwrap();
+    let nan = Tensor::new(vec![2], vec![f32::NAN, -2.0]).unwrap();
+    assert!(Tolerance::default().matches(&a, &close));
+    assert!(!Tolerance::default().matches(&a, &far));
+    assert!(!Tolerance::default().matches(&nan, &nan));
+    assert!(Tolerance::Exact.matches(&a, &a.clone()));
+    assert!(!Tolerance::Exact.matches(&a, &close));
+    assert!(!Tolerance::Exact.matches(&a, &Tensor::new(vec![1, 2], a.data.clone()).unwrap()));
+
+    assert_eq!(QuorumConfig::default().threshold(), 2);
+    assert_eq!(QuorumConfig { replicas: 4, ..QuorumConfig::default() }.threshold(), 3);
+    QuorumConfig::default().validate().unwrap();
+    // Deux groupes pourraient atteindre un quorum qui n'est pas une majorité stricte
+    assert!(QuorumConfig { replicas: 4, quorum: Some(2), ..QuorumConfig::default() }.validate().is_err());
+    assert!(QuorumConfig { quorum: Some(4), ..QuorumConfig::default() }.validate().is_err());
+    let negative = Tolerance::Approx { absolute: -1.0, relative: 0.0 };
+    assert!(QuorumConfig { tolerance: negative, ..QuorumConfig::default() }.validate().is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn quorum_rejects_dissenting_provider() {
+    let mut harness = MeshHarness::new();
+    let origin = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    let mut workers = Vec::new();
+    for create in [honest, honest, dishonest] {
+        let index = harness
+            .spawn_node_with(NodeRole::Light, Keypair::generate_ed25519(), |options| {
+                options.backends = BackendRegistry::new();
+                options.backends.register("biased", create);
+            })
+            .await
+            .unwrap();
+        workers.push(harness.node(index).unwrap().handle.clone());
+    }
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    for worker in &workers {
+        let spec = ShardSpec { id: SHARD.into(), version: "1".into(), backend: "biased".into(), path: None };
+        worker.load_shard(spec).await.unwrap();
+    }
+    let announced = harness
+        .announce_until(TIMEOUT, |h| {
+            let registry = h.node(origin).unwrap().registry();
+            registry.nodes.values().flat_map(|n| &n.shards).filter(|s| s.shard_id == SHARD).count() == 3
+        })
+        .await
+        .unwrap();
+    assert!(announced);
+
+    let reports = Arc::new(Reports::default());
+    let handle = harness.node(origin).unwrap().handle.clone();
+    let config = QuorumConfig { tolerance: Tolerance::Exact, ..QuorumConfig::default() };
+    let executor = QuorumExecutor::new(handle.clone()).with_config(config.clone()).with_reporter(reports.clone());
+    assert_eq!(executor.providers(SHARD).unwrap().len(), 3);
+
+    let input = Tensor::new(vec![1, 3], vec![1.0, -0.5, 3.0]).unwrap();
+    let outcome = executor.execute(SHARD, &input).await.unwrap();
+    assert_eq!(outcome.output.data, vec![2.0, -1.0, 6.0]);
+    let mut agreeing = outcome.agreeing.clone();
+    agreeing.sort();
+    let mut honest_peers = vec![workers[0].peer_id(), workers[1].peer_id()];
+    honest_peers.sort();
+    assert_eq!(agreeing, honest_peers);
+    assert_eq!(outcome.dissenting, vec![workers[2].peer_id()]);
+    assert!(outcome.failed.is_empty());
+    assert_eq!(*reports.0.lock().unwrap(), vec![(workers[2].peer_id(), SHARD.to_string())]);
+
+    // Unanimité exigée: la sortie dissidente empêche le quorum, sans désigner de coupable
+    let unanimous = QuorumConfig { quorum: Some(3), ..config };
+    let strict = QuorumExecutor::new(handle).with_config(unanimous).with_reporter(reports.clone());
+    assert!(strict.execute(SHARD, &input).await.is_err());
+    assert_eq!(reports.0.lock().unwrap().len(), 1);
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn providers_are_drawn_across_trust_domains() {
+    let acme = ed25519::Keypair::generate();
+    let globex = ed25519::Keypair::generate();
+    let mut config = CortexConfig::default();
+    for (domain, org) in [("acme", &acme), ("globex", &globex)] {
+        config.trust.roots.push(TrustRootConfig { domain: domain.into(), public_key: encode_public_key(&org.public()) });
+    }
+    let mut harness = MeshHarness::with_config(config);
+    let origin = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    // Deux fournisseurs acme, un globex et un sans certificat
+    let year = Duration::from_secs(365 * 24 * 3600);
+    let mut workers = Vec::new();
+    for org in [Some(("acme", &acme)), Some(("acme", &acme)), Some(("globex", &globex)), None] {
+        let key = Keypair::generate_ed25519();
+        let cert = org.map(|(domain, org)| NodeCertificate::issue(org, domain, &key.public().to_peer_id(), Vec::new(), year));
+        let index = harness
+            .spawn_node_with(NodeRole::Light, key, |options| {
+                options.backends.register("biased", honest);
+                options.certificate = cert.clone();
+            })
+            .await
+            .unwrap();
+        workers.push(harness.node(index).unwrap().handle.clone());
+    }
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    for worker in &workers {
+        let spec = ShardSpec { id: SHARD.into(), version: "1".into(), backend: "biased".into(), path: None };
+        worker.load_shard(spec).await.unwrap();
+    }
+    let announced = harness
+        .announce_until(TIMEOUT, |h| {
+            let registry = h.node(origin).unwrap().registry();
+            let hosts = registry.nodes.values().filter(|n| n.shards.iter().any(|s| s.shard_id == SHARD));
+            hosts.filter(|n| n.trust.is_some()).count() == 3
+        })
+        .await
+        .unwrap();
+    assert!(announced);
+
+    let executor = QuorumExecutor::new(harness.node(origin).unwrap().handle.clone());
+    let acme_peers = [workers[0].peer_id(), workers[1].peer_id()];
+    let mut drawn = HashSet::new();
+    for _ in 0..64 {
+        let providers = executor.providers(SHARD).unwrap();
+        assert_eq!(providers.len(), 3);
+        assert_eq!(providers.iter().filter(|p| acme_peers.contains(p)).count(), 1);
+        assert!(providers.contains(&workers[2].peer_id()) && providers.contains(&workers[3].peer_id()));
+        drawn.extend(providers);
+    }
+    // Tirage au hasard: les deux fournisseurs acme sont sollicités tour à tour
+    assert_eq!(drawn.len(), 4);
+    harness.shutdown().await.unwrap();
+}
@@ -0,0 +1,170 @@
+// Réputation des pairs: décroissance, score, persistance, attestations et pondération de l'ordonnancement
+#![cfg(feature = "backend-identity")]
+use std::time::Duration;
+
+use cortex_id::discovery::NodeRole;
+use cortex_id::executor::{identity, ShardSpec, Tensor};
+use cortex_id::harness::MeshHarness;
+use cortex_id::quorum::{QuorumConfig, QuorumExecutor, Tolerance};
+use cortex_id::reputation::{Observation, Reputation, ReputationAttestation, ReputationConfig};
+use cortex_id::scheduler::{weigh_by_reputation, NodeCapacity};
+use libp2p::identity::{ed25519, Keypair};
+use libp2p::PeerId;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+const HOUR: u64 = 3600;
+const START: u64 = 1_000_000;
+
+fn reputation() -> Reputation {
+    Reputation::new(ReputationConfig { half_life_secs: HOUR, ..ReputationConfig::default() })
+}
+
+#[test]
+fn score_reflects_observations_and_decays() {
+    let reputation = reputation();
+    for _ in 0..10 {
+        reputation.observe_at("good", Observation::QuorumAgreement, START);
+        reputation.observe_at("good", Observation::RpcSuccess { latency: Duration::from_millis(10) }, START);
+        reputation.observe_at("bad", Observation::QuorumDissent, START);
+        reputation.observe_at("bad", Observation::RpcFailure, START);
+    }
+    let scores = reputation.scores_at(START);
+    assert_eq!(scores.iter().map(|s| s.peer.as_str()).collect::<Vec<_>>(), vec!["good", "bad"]);
+    assert!(scores[0].score > 0.6 && scores[1].score < 0.4);
+
+    // Après de nombreuses demi-vies, les deux pairs reviennent vers le score d'un inconnu
+    let later = reputation.scores_at(START + 20 * HOUR);
+    assert!(later.iter().all(|s| (s.score - 0.5).abs() < 0.01), "{:?}", later);
+
+    // Chaque série de messages invalides divise le score par deux
+    let before = reputation.scores_at(START)[0].score;
+    for _ in 0..4 {
+        reputation.observe_at("good", Observation::InvalidMessage, START);
+    }
+    let after = reputation.scores_at(START).into_iter().find(|s| s.peer == "good").unwrap();
+    assert!((after.score - before / 2.0).abs() < 1e-4);
+    assert!(reputation.gossip_score("unknown").abs() < f64::EPSILON);
+    reputation.observe("liar", Observation::QuorumDissent);
+    assert!(reputation.gossip_score("liar") < 0.0);
+}
+
+#[test]
+fn latency_percentiles_and_presence() {
+    let reputation = reputation();
+    for ms in 1..=100 {
+        reputation.observe_at("peer", Observation::RpcSuccess { latency: Duration::from_millis(ms) }, START);
+    }
+    let score = reputation.scores_at(START).remove(0);
+    assert_eq!(score.latency_p50_ms.map(f32::round), Some(51.0));
+    assert_eq!(score.latency_p99_ms.map(f32::round), Some(99.0));
+
+    let present = vec!["peer".to_string(), "other".to_string()];
+    reputation.record_presence_at(&present, START);
+    reputation.record_presence_at(&present[1..], START + 60);
+    let scores = reputation.scores_at(START + 60);
+    let uptime = |peer: &str| scores.iter().find(|s| s.peer == peer).unwrap().uptime;
+    assert!(uptime("other") > uptime("peer"));
+
+    // Un pair absent depuis trop longtemps est oublié
+    reputation.record_presence_at(&present[1..], START + 9 * HOUR);
+    let peers: Vec<String> = reputation.scores_at(START + 9 * HOUR).into_iter().map(|s| s.peer).collect();
+    assert_eq!(peers, vec!["other".to_string()]);
+}
+
+#[test]
+fn reputation_survives_restart_and_key_succession() {
+    let path = std::env::temp_dir().join(format!("cortex-reputation-{}.json", PeerId::random()));
+    let reputation = reputation();
+    reputation.observe("old", Observation::QuorumDissent);
+    reputation.save_to(&path).unwrap();
+
+    let restored = Reputation::load_from(&path, reputation.config().clone()).unwrap();
+    assert!((restored.score("old") - reputation.score("old")).abs() < 1e-3);
+    assert!(restored.migrate("old", "new"));
+    assert!(restored.record("old").is_none());
+    assert!(restored.score("new") < 0.5);
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn attestation_is_signed_by_its_issuer() {
+    let identity = ed25519::Keypair::generate();
+    let reputation = reputation();
+    reputation.observe("peer", Observation::QuorumAgreement);
+    let attestation = ReputationAttestation::sign(&identity, reputation.scores());
+    attestation.verify().unwrap();
+
+    let mut tampered = attestation.clone();
+    tampered.scores[0].score = 1.0;
+    assert!(tampered.verify().is_err());
+    let mut impostor = attestation.clone();
+    impostor.issuer = PeerId::random().to_string();
+    assert!(impostor.verify().is_err());
+
+    // Seule l'attestation la plus récente d'un émetteur est retenue
+    assert!(reputation.store_attestation(attestation.clone()));
+    assert!(!reputation.store_attestation(attestation));
+    assert_eq!(reputation.attestations().len(), 1);
+}
+
+#[test]
+fn scheduler_weighs_and_excludes_by_reputation() {
+    let node = |peer: &str| NodeCapacity { peer: peer.into(), memory_mb: 4000, latency_ms: 10.0, load: 0, reliability: 1.0 };
+    let reputation = reputation();
+    for _ in 0..20 {
+        reputation.observe("trusted", Observation::QuorumAgreement);
+        reputation.observe("cheater", Observation::QuorumDissent);
+        reputation.observe("cheater", Observation::InvalidMessage);
+    }
+    let weighed = weigh_by_reputation(vec![node("trusted"), node("unknown"), node("cheater")], &reputation);
+    assert_eq!(weighed.iter().map(|n| n.peer.as_str()).collect::<Vec<_>>(), vec!["trusted", "unknown"]);
+    assert!(weighed[0].cost_ms() < weighed[1].cost_ms());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn quorum_and_rpc_outcomes_feed_reputation() {
+    let path = std::env::temp_dir().join(format!("cortex-reputation-{}.json", PeerId::random()));
+    let mut harness = MeshHarness::new();
+    let origin = {
+        let path = path.clone();
+        harness
+            .spawn_node_with(NodeRole::Bootstrap, Keypair::generate_ed25519(), |options| {
+                options.reputation_path = Some(path);
+            })
+            .await
+            .unwrap()
+    };
+    let worker = harness.spawn_node(NodeRole::Light).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    let worker = harness.node(worker).unwrap().handle.clone();
+    let spec = ShardSpec { id: "echo".into(), version: "1".into(), backend: identity::BACKEND_NAME.into(), path: None };
+    worker.load_shard(spec).await.unwrap();
+    let announced = harness
+        .announce_until(TIMEOUT, |h| {
+            let registry = h.node(origin).unwrap().registry();
+            registry.nodes.values().flat_map(|n| &n.shards).any(|s| s.shard_id == "echo")
+        })
+        .await
+        .unwrap();
+    assert!(announced);
+
+    let handle = harness.node(origin).unwrap().handle.clone();
+    let config = QuorumConfig { replicas: 1, tolerance: Tolerance::Exact, ..QuorumConfig::default() };
+    let input = Tensor::new(vec![1, 2], vec![1.0, 2.0]).unwrap();
+    QuorumExecutor::new(handle.clone()).with_config(config).execute("echo", &input).await.unwrap();
+
+    let peer = worker.peer_id().to_string();
+    let record = handle.reputation().record(&peer).unwrap();
+    assert_eq!(record.quorum.good, record.quorum.total);
+    assert!(record.quorum.total > 0.9);
+    assert!(record.rpc.good > 0.9);
+    assert!(record.latency_percentile(50.0).is_some());
+    let score = handle.reputation().score(&peer);
+    assert!(score > 0.5);
+
+    // Persistée à l'arrêt du nœud, rechargée au prochain démarrage
+    harness.shutdown().await.unwrap();
+    let restored = Reputation::load_from(&path, ReputationConfig::default()).unwrap();
+    assert!(restored.score(&peer) > 0.5);
+    let _ = std::fs::remove_file(path);
+}
@@ -0,0 +1,160 @@
+// Ordonnanceur: affectation déterministe des couches et des experts, plans signés
+use std::time::Duration;
+
+use cortex_id::registry::{AnnounceMsg, Registry};
+use cortex_id::scheduler::{
+    capacities, schedule, schedule_from_registry, ModelManifest, SignedPlan, MAX_PLAN_CLOCK_SKEW_SECS, ROUTER_ROLE,
+};
+use cortex_id::trust::TrustInfo;
+use libp2p::identity::{ed25519, PeerId, PublicKey};
+
+mod common;
+use common::start_with_router;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+fn announce(node_id: &str, ram_free_mb: u32, load: u32) -> AnnounceMsg {
+    AnnounceMsg { shards: vec!["light".into()], ram_free_mb, load, ..AnnounceMsg::new(node_id) }
+}
+
+/// a et b rapides, c rapide mais chargé et petit, d lent (jamais mesuré) mais vaste
+fn registry(order: &[&str]) -> Registry {
+    let mut registry = Registry::default();
+    for &id in order {
+        let (ram, load, rtt) = match id {
+            "a" => (4000, 0, Some(10)),
+            "b" => (4000, 0, Some(20)),
+            "c" => (2000, 3, Some(5)),
+            _ => (16000, 0, None),
+        };
+        registry.update_from_announce(announce(id, ram, load));
+        if let Some(ms) = rtt {
+            registry.record_latency(id, Duration::from_millis(ms));
+        }
+    }
+    registry
+}
+
+fn manifest(num_layers: usize) -> ModelManifest {
+    ModelManifest {
+        model: "mix".into(),
+        num_layers,
+        layer_memory_mb: 1000,
+        experts_per_layer: 2,
+        expert_memory_mb: 500,
+        trust_domain: None,
+    }
+}
+
+#[test]
+fn schedule_is_deterministic_and_fits_memory() {
+    let plan = schedule_from_registry(&manifest(8), &registry(&["a", "b", "c", "d"])).unwrap();
+    let again = schedule_from_registry(&manifest(8), &registry(&["d", "c", "b", "a"])).unwrap();
+    assert_eq!(plan, again);
+    assert_eq!(plan.digest(), again.digest());
+    let mut nodes = capacities(&registry(&["a", "b", "c", "d"]), None);
+    nodes.reverse();
+    assert_eq!(schedule(&manifest(8), &nodes).unwrap().digest(), plan.digest());
+
+    // Couches sur les deux nœuds les moins coûteux (10 + 20 ms, contre 50 ms pour d seul)
+    let stages: Vec<_> = plan.stages.iter().map(|s| (s.peer.as_str(), s.first_layer, s.last_layer)).collect();
+    assert_eq!(stages, vec![("a", 0, 3), ("b", 4, 7)]);
+    assert_eq!(plan.pipeline().stages[1].shard_id, "mix/layers-4-7");
+
+    // Experts sur la mémoire restante: deux nœuds distincts par couche tant que c a de la place
+    assert_eq!(plan.experts.len(), 16);
+    assert_eq!(plan.shards_for("c").len(), 4);
+    assert_eq!(plan.shards_for("d").len(), 12);
+    for layer in 0..2 {
+        let peers: Vec<_> = plan.experts.iter().filter(|e| e.layer == layer).map(|e| e.peer.as_str()).collect();
+        assert_eq!(peers, vec!["c", "d"]);
+    }
+    assert_eq!(plan.expected_latency_ms, 30.0 + 8.0 * 50.0);
+
+    // Trop de couches pour la mémoire du mesh
+    assert!(schedule_from_registry(&manifest(30), &registry(&["a", "b", "c", "d"])).is_err());
+}
+
+#[test]
+fn signed_plan_detects_tampering() {
+    let plan = schedule_from_registry(&manifest(8), &registry(&["a", "b", "c", "d"])).unwrap();
+    let signed = SignedPlan::sign(&ed25519::Keypair::generate(), plan);
+    signed.verify().unwrap();
+
+    let mut moved = signed.clone();
+    moved.plan.stages[0].peer = "d".into();
+    assert!(moved.verify().is_err());
+    let mut forged = signed.clone();
+    forged.plan.stages[0].peer = "d".into();
+    forged.digest = forged.plan.digest();
+    assert!(forged.verify().is_err());
+    let mut impostor = signed;
+    let other = PublicKey::from(ed25519::Keypair::generate().public());
+    impostor.signer = PeerId::from_public_key(&other).to_string();
+    assert!(impostor.verify().is_err());
+}
+
+#[test]
+fn plan_acceptance_needs_router_and_sane_date() {
+    let mut reg = registry(&["a", "b"]);
+    let trust = TrustInfo { domain: "acme".into(), roles: vec![ROUTER_ROLE.into()], serial: 1, expires_at: u64::MAX };
+    reg.nodes.get_mut("a").unwrap().trust = Some(trust.clone());
+    reg.nodes.get_mut("b").unwrap().trust = Some(TrustInfo { roles: vec!["shard_executor".into()], ..trust.clone() });
+    assert!(reg.holds_role("a", ROUTER_ROLE));
+    assert!(!reg.holds_role("b", ROUTER_ROLE));
+    assert!(!reg.holds_role("unknown", ROUTER_ROLE));
+    reg.nodes.get_mut("a").unwrap().trust = Some(TrustInfo { expires_at: 0, ..trust });
+    assert!(!reg.holds_role("a", ROUTER_ROLE));
+
+    let plan = schedule_from_registry(&manifest(4), &registry(&["a", "b"])).unwrap();
+    let signed = SignedPlan::sign(&ed25519::Keypair::generate(), plan);
+    assert!(!signed.is_future_dated(signed.issued_at));
+    assert!(!signed.is_future_dated(signed.issued_at - MAX_PLAN_CLOCK_SKEW_SECS));
+    assert!(signed.is_future_dated(signed.issued_at - MAX_PLAN_CLOCK_SKEW_SECS - 1));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn published_plan_reaches_other_nodes() {
+    let harness = start_with_router(3).await;
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    harness.converge(TIMEOUT).await.unwrap();
+    let router = harness.node(0).unwrap().handle.clone();
+
+    let registry = router.registry();
+    let plan = {
+        let mut reg = registry.lock().unwrap();
+        // Mémoire fixée pour ne pas dépendre de la machine de test
+        for id in reg.eligible_nodes() {
+            if let Some(entry) = reg.nodes.get_mut(&id) {
+                entry.ram_free_mb = 4000;
+            }
+        }
+        let dense = ModelManifest { experts_per_layer: 0, ..manifest(8) };
+        schedule_from_registry(&dense, &reg).unwrap()
+    };
+    let signed = router.publish_plan(plan).await.unwrap();
+    assert_eq!(router.plan("mix"), Some(signed.clone()));
+
+    let received = harness
+        .announce_until(TIMEOUT, |h| (1..3).all(|i| h.node(i).unwrap().handle.plan("mix").as_ref() == Some(&signed)))
+        .await
+        .unwrap();
+    assert!(received);
+
+    // Un nœud sans certificat de routeur ne peut pas imposer son plan
+    let rogue = harness.node(1).unwrap().handle.clone();
+    // Daté d'une seconde plus tard, il serait plus récent que le plan du routeur
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let mut replan = signed.plan.clone();
+    replan.stages.reverse();
+    let forged = rogue.publish_plan(replan).await.unwrap();
+    let replaced = harness
+        .announce_until(Duration::from_secs(3), |h| {
+            [0, 2].iter().any(|i| h.node(*i).unwrap().handle.plan("mix").as_ref() == Some(&forged))
+        })
+        .await
+        .unwrap();
+    assert!(!replaced);
+    assert_eq!(router.plan("mix"), Some(signed));
+    harness.shutdown().await.unwrap();
+}
@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use cortex_id::config::CortexConfig;
+use cortex_id::harness::MeshHarness;
+use cortex_id::registry::Registry;
+use cortex_id::simulation::{LinkFaults, SimNetwork};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Banc sur réseau simulé, avec un TTL de registre court pour observer les expirations
+async fn simulated_mesh(sim: &SimNetwork, n: usize) -> MeshHarness {
+    let mut config = CortexConfig::default();
+    config.mesh.node_ttl_secs = 2;
+    let mut harness = MeshHarness::with_config(config).with_transport_factory(sim.transport_factory());
+    harness.spawn_many(n).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    harness
+}
+
+#[test]
+fn saved_registry_is_reloaded_with_the_configured_ttl() {
+    let path = std::env::temp_dir().join(format!("cortex-registry-{}.json", std::process::id()));
+    let snapshot = serde_json::json!({
+        "timestamp": std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
+        "nodes": { "seen-a-minute-ago": {
+            "shards": [],
+            "vram_free_mb": 0,
+            "last_seen_secs_ago": 60,
+            "trust": { "domain": "acme", "roles": ["router"], "serial": 1, "expires_at": u64::MAX },
+            "admitted": true,
+        } },
+    });
+    std::fs::write(&path, snapshot.to_string()).unwrap();
+
+    let short = Registry::load_from(&path, Duration::from_secs(30)).unwrap();
+    assert!(short.nodes.is_empty());
+    assert_eq!(short.ttl(), Duration::from_secs(30));
+    let long = Registry::load_from(&path, Duration::from_secs(300)).unwrap();
+    assert_eq!(long.nodes.len(), 1);
+    assert_eq!(long.ttl(), Duration::from_secs(300));
+    // Confiance et admission attendent la prochaine annonce vérifiée
+    let entry = &long.nodes["seen-a-minute-ago"];
+    assert!(entry.trust.is_none() && !entry.admitted);
+    assert!(long.eligible_nodes().is_empty());
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn same_seed_replays_same_fault_schedule() {
+    let faults = LinkFaults { loss: 0.3, ..LinkFaults::with_latency(Duration::from_millis(5)) };
+    let a = SimNetwork::new(42);
+    let b = SimNetwork::new(42);
+    let c = SimNetwork::new(7);
+    for net in [&a, &b, &c] {
+        net.set_default_faults(faults);
+    }
+
+    assert_eq!(a.delay_schedule(1, 2, 0, 64), b.delay_schedule(2, 1, 0, 64));
+    assert_ne!(a.delay_schedule(1, 2, 0, 64), c.delay_schedule(1, 2, 0, 64));
+    assert_ne!(a.delay_schedule(1, 2, 0, 64), a.delay_schedule(1, 2, 1, 64));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn mesh_converges_despite_latency_and_loss() {
+    let sim = SimNetwork::new(1);
+    sim.set_default_faults(LinkFaults {
+        loss: 0.05,
+        ..LinkFaults::with_latency(Duration::from_millis(20))
+    });
+    let harness = simulated_mesh(&sim, 4).await;
+
+    harness.converge(TIMEOUT).await.unwrap();
+    assert!(sim.stats().delayed_segments > 0);
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn partitioned_node_is_pruned_then_rejoins_after_heal() {
+    let sim = SimNetwork::new(2);
+    let harness = simulated_mesh(&sim, 3).await;
+    harness.converge(TIMEOUT).await.unwrap();
+
+    let isolated = harness.node(2).unwrap().peer_id();
+    let majority = harness.ports(&[0, 1]);
+    let minority = harness.ports(&[2]);
+    sim.partition(&minority, &majority);
+
+    let pruned = harness
+        .announce_until(TIMEOUT, |h| h.running().filter(|n| n.peer_id() != isolated).all(|n| !n.knows(&isolated)))
+        .await
+        .unwrap();
+    assert!(pruned, "isolated node still present after TTL");
+    assert!(sim.stats().connections_cut > 0);
+
+    // Le nœud isolé retente le bootstrap et rejoint le mesh une fois la partition levée
+    sim.heal_all();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    harness.converge(TIMEOUT).await.unwrap();
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn crashed_node_expires_from_registries() {
+    let sim = SimNetwork::new(3);
+    let mut harness = simulated_mesh(&sim, 3).await;
+    harness.converge(TIMEOUT).await.unwrap();
+
+    let crashed = harness.crash(1).unwrap();
+    let expired = harness
+        .announce_until(TIMEOUT, |h| h.running().all(|n| !n.knows(&crashed)))
+        .await
+        .unwrap();
+    assert!(expired, "crashed node never expired");
+    harness.converge(TIMEOUT).await.unwrap();
+    harness.shutdown().await.unwrap();
+}
@@ -0,0 +1,139 @@
+// Rotation de clé: certificats de succession, migration du registre et réémission des plans
+use std::time::Duration;
+
+use cortex_id::discovery::NodeRole;
+use cortex_id::identity::succession::KeySuccession;
+use cortex_id::pipeline::PipelineStage;
+use cortex_id::registry::{AnnounceMsg, Registry};
+use cortex_id::scheduler::{ExpertPlacement, SchedulePlan, SignedPlan};
+use cortex_id::trust::TrustInfo;
+use libp2p::identity::{ed25519, Keypair};
+
+mod common;
+use common::start_with_router;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+fn announce(node_id: &str) -> AnnounceMsg {
+    AnnounceMsg { shards: vec!["light".into()], vram_free_mb: 512, ..AnnounceMsg::new(node_id) }
+}
+
+#[test]
+fn succession_requires_both_signatures() {
+    let old = ed25519::Keypair::generate();
+    let new = ed25519::Keypair::generate();
+    let succession = KeySuccession::sign(&old, &new, Some("rotation".into()));
+    succession.verify().unwrap();
+
+    let mut redirected = succession.clone();
+    let other = KeySuccession::sign(&old, &ed25519::Keypair::generate(), None);
+    redirected.new_peer_id = other.new_peer_id;
+    redirected.new_public_key = other.new_public_key;
+    assert!(redirected.verify().is_err());
+
+    let mut tampered = succession;
+    tampered.reason = Some("compromise".into());
+    assert!(tampered.verify().is_err());
+}
+
+#[test]
+fn registry_migrates_and_ignores_retired_identity() {
+    let mut registry = Registry::default();
+    registry.update_from_announce(announce("old"));
+    let trust = TrustInfo { domain: "acme".into(), roles: vec!["router".into()], serial: 1, expires_at: u64::MAX };
+    registry.set_trust("old", Some(trust));
+
+    assert!(registry.migrate("old", "new"));
+    assert!(!registry.nodes.contains_key("old"));
+    assert_eq!(registry.nodes["new"].vram_free_mb, 512);
+    // Le certificat de l'ancien PeerId ne vaut pas pour le successeur
+    assert!(registry.nodes["new"].trust.is_none());
+    assert_eq!(registry.resolve("old"), "new");
+
+    registry.update_from_announce(announce("old"));
+    assert!(!registry.nodes.contains_key("old"));
+}
+
+#[test]
+fn reissued_plan_names_successor_and_supersedes() {
+    let stage = PipelineStage { peer: "old".into(), shard_id: "s0".into(), first_layer: 0, last_layer: 3 };
+    let expert = ExpertPlacement { peer: "old".into(), shard_id: "e0".into(), layer: 0, expert: 1 };
+    let plan = SchedulePlan { model: "m".into(), stages: vec![stage], experts: vec![expert], expected_latency_ms: 1.0 };
+    let router = ed25519::Keypair::generate();
+    let signed = SignedPlan::sign(&router, plan);
+
+    let mut moved = signed.plan.clone();
+    assert!(moved.replace_peer("old", "new"));
+    assert!(!moved.involves("old") && moved.involves("new"));
+    assert!(!moved.clone().replace_peer("old", "new"));
+    let reissued = signed.reissue(&router, moved);
+    reissued.verify().unwrap();
+    assert!(reissued.issued_at > signed.issued_at);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn peers_migrate_rotated_node() {
+    let mut harness = start_with_router(3).await;
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    harness.converge(TIMEOUT).await.unwrap();
+
+    // Le nœud 2 tourne sa clé: l'ancienne identité s'arrête, la nouvelle publie sa succession
+    let old_key = ed25519::Keypair::generate();
+    let old_index = harness.spawn_node_with_key(NodeRole::Light, Keypair::from(old_key.clone())).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    harness.converge(TIMEOUT).await.unwrap();
+
+    // Le routeur confie un shard à l'ancienne identité
+    let router = harness.node(0).unwrap().handle.clone();
+    let old_id = harness.node(old_index).unwrap().peer_id().to_string();
+    let stage = PipelineStage { peer: old_id, shard_id: "s0".into(), first_layer: 0, last_layer: 3 };
+    let plan = SchedulePlan { model: "m".into(), stages: vec![stage], experts: Vec::new(), expected_latency_ms: 1.0 };
+    let signed = router.publish_plan(plan).await.unwrap();
+    let received = harness
+        .announce_until(TIMEOUT, |h| h.running().all(|n| n.handle.plan("m").as_ref() == Some(&signed)))
+        .await
+        .unwrap();
+    assert!(received);
+    let old_peer = harness.crash(old_index).unwrap();
+
+    let new_key = ed25519::Keypair::generate();
+    let succession = KeySuccession::sign(&old_key, &new_key, Some("rotation".into()));
+    let new_index = harness.spawn_node_with_key(NodeRole::Light, Keypair::from(new_key)).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    let new_peer = harness.node(new_index).unwrap().peer_id();
+
+    let handle = harness.node(new_index).unwrap().handle.clone();
+    let mut migrated = false;
+    let deadline = tokio::time::Instant::now() + TIMEOUT;
+    while !migrated && tokio::time::Instant::now() < deadline {
+        handle.publish_succession(succession.clone()).await.unwrap();
+        migrated = harness
+            .wait_until(Duration::from_millis(500), |h| {
+                h.running()
+                    .filter(|n| n.peer_id() != new_peer)
+                    .all(|n| !n.knows(&old_peer) && n.registry().successors.contains_key(&old_peer.to_string()))
+            })
+            .await;
+    }
+    assert!(migrated, "old identity still registered");
+    // Le routeur réémet le plan pour le successeur, et tous les nœuds l'adoptent
+    let router_id = harness.node(0).unwrap().peer_id().to_string();
+    let reissued = harness
+        .announce_until(TIMEOUT, |h| {
+            h.running().all(|n| {
+                n.handle.plan("m").is_some_and(|p| {
+                    p.signer == router_id && p.plan.stages[0].peer == new_peer.to_string() && p.issued_at > signed.issued_at
+                })
+            })
+        })
+        .await
+        .unwrap();
+    assert!(reissued, "plan not reissued for the successor");
+
+    // Un nœud arrivé plus tard retrouve la succession dans la DHT
+    let late = harness.spawn_node(NodeRole::Light).await.unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+    let found = harness.node(late).unwrap().handle.find_successor(old_peer).await.unwrap();
+    assert_eq!(found.map(|s| s.new_peer_id), Some(new_peer.to_string()));
+    harness.shutdown().await.unwrap();
+}
@@ -0,0 +1,68 @@
+// Espace de noms des topics: décodage, isolation des meshes et compatibilité v1
+use cortex_id::topics::{Channel, ParsedTopic, TopicNamespace};
+use libp2p::kad::RecordKey;
+
+const CHANNELS: [Channel; 6] = [
+    Channel::Announce,
+    Channel::Communicator,
+    Channel::Succession,
+    Channel::Trust,
+    Channel::Plan,
+    Channel::Reputation,
+];
+
+#[test]
+fn topics_round_trip_through_parse() {
+    let namespace = TopicNamespace::new("lab", 3, 2, false);
+    for channel in CHANNELS {
+        let topic = namespace.topic(channel);
+        let parsed = TopicNamespace::parse_hash(&topic.hash()).unwrap();
+        assert_eq!(parsed, ParsedTopic { mesh: Some("lab".into()), version: 3, channel });
+        assert!(namespace.accepts_topic(&parsed));
+    }
+    assert_eq!(namespace.announce().hash().as_str(), "cortex/lab/v3/announce");
+
+    for malformed in ["cortex/lab/v3", "cortex/lab/3/announce", "cortex/lab/v3/unknown", "cortex/lab/v3/announce/x", "other/lab/v3/announce"] {
+        assert!(TopicNamespace::parse(malformed).is_none(), "{}", malformed);
+    }
+}
+
+#[test]
+fn other_meshes_and_versions_are_rejected() {
+    let namespace = TopicNamespace::new("lab", 3, 2, false);
+    let other_mesh = TopicNamespace::parse("cortex/prod/v3/announce").unwrap();
+    assert!(!namespace.accepts_topic(&other_mesh));
+
+    let too_new = TopicNamespace::parse("cortex/lab/v4/announce").unwrap();
+    let too_old = TopicNamespace::parse("cortex/lab/v1/announce").unwrap();
+    let oldest_accepted = TopicNamespace::parse("cortex/lab/v2/announce").unwrap();
+    assert!(!namespace.accepts_topic(&too_new));
+    assert!(!namespace.accepts_topic(&too_old));
+    assert!(namespace.accepts_topic(&oldest_accepted));
+
+    // Un abonnement par version acceptée, de la plus récente à la plus ancienne
+    let subscriptions: Vec<String> = namespace.subscriptions(Channel::Plan).iter().map(|t| t.hash().to_string()).collect();
+    assert_eq!(subscriptions, vec!["cortex/lab/v3/plan", "cortex/lab/v2/plan"]);
+}
+
+#[test]
+fn legacy_topics_depend_on_compat_mode() {
+    let legacy = TopicNamespace::parse("cortex/announce").unwrap();
+    assert_eq!(legacy, ParsedTopic { mesh: None, version: 1, channel: Channel::Announce });
+
+    let compat = TopicNamespace::new("lab", 2, 1, true);
+    assert!(compat.accepts_topic(&legacy));
+    let published: Vec<String> = compat.publications(Channel::Announce).iter().map(|t| t.hash().to_string()).collect();
+    assert_eq!(published, vec!["cortex/lab/v2/announce", "cortex/announce"]);
+    // Les canaux apparus après v1 n'ont pas de topic historique
+    assert_eq!(compat.publications(Channel::Trust).len(), 1);
+    assert_eq!(compat.subscriptions(Channel::Trust).len(), 1);
+    assert_eq!(compat.legacy_discovery_key(), Some(RecordKey::new(&b"cortex-mesh:v1")));
+
+    let strict = TopicNamespace::new("lab", 2, 1, false);
+    assert!(!strict.accepts_topic(&legacy));
+    assert_eq!(strict.publications(Channel::Announce).len(), 1);
+    assert_eq!(strict.subscriptions(Channel::Announce).len(), 1);
+    assert!(strict.legacy_discovery_key().is_none());
+    assert_ne!(strict.discovery_key(), RecordKey::new(&b"cortex-mesh:v1"));
+}
@@ -0,0 +1,101 @@
+// Confiance d'organisation: certificats de nœud et révocations
+use std::time::Duration;
+
+use cortex_id::config::{CortexConfig, TrustRootConfig};
+use cortex_id::discovery::NodeRole;
+use cortex_id::harness::MeshHarness;
+use cortex_id::trust::{encode_public_key, NodeCertificate, RevocationList, TrustStore};
+use libp2p::identity::{ed25519, Keypair};
+use libp2p::PeerId;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+const YEAR: Duration = Duration::from_secs(365 * 24 * 3600);
+
+fn store_for(org: &ed25519::Keypair) -> TrustStore {
+    let mut store = TrustStore::default();
+    store.add_root("acme", &encode_public_key(&org.public())).unwrap();
+    store
+}
+
+#[test]
+fn certificate_validation() {
+    let org = ed25519::Keypair::generate();
+    let store = store_for(&org);
+    let peer = PeerId::random();
+
+    let cert = NodeCertificate::issue(&org, "acme", &peer, vec!["shard_executor".into()], YEAR);
+    let info = store.validate(&cert, &peer.to_string()).unwrap();
+    assert_eq!(info.roles, vec!["shard_executor".to_string()]);
+
+    // Présenté par un autre nœud
+    assert!(store.validate(&cert, &PeerId::random().to_string()).is_err());
+    // Rôle ajouté après signature
+    let mut forged = cert.clone();
+    forged.roles.push("router".into());
+    assert!(store.validate(&forged, &peer.to_string()).is_err());
+    // Autre organisation
+    let rogue = NodeCertificate::issue(&ed25519::Keypair::generate(), "acme", &peer, vec![], YEAR);
+    assert!(store.validate(&rogue, &peer.to_string()).is_err());
+    // Expiré
+    let expired = NodeCertificate::issue(&org, "acme", &peer, vec![], Duration::ZERO);
+    assert!(store.validate(&expired, &peer.to_string()).is_err());
+}
+
+#[test]
+fn revocation_list_must_come_from_root() {
+    let org = ed25519::Keypair::generate();
+    let mut store = store_for(&org);
+    let peer = PeerId::random();
+    let cert = NodeCertificate::issue(&org, "acme", &peer, vec![], YEAR);
+
+    let rogue = RevocationList::sign(&ed25519::Keypair::generate(), "acme", vec![peer.to_string()], vec![]);
+    assert!(store.apply_revocation(rogue).is_err());
+    assert!(store.validate(&cert, &peer.to_string()).is_ok());
+
+    let list = RevocationList::sign(&org, "acme", vec![], vec![cert.serial]);
+    assert!(store.apply_revocation(list.clone()).unwrap());
+    assert!(!store.apply_revocation(list).unwrap());
+    assert!(store.validate(&cert, &peer.to_string()).is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn registry_filters_by_trust_domain() {
+    let org = ed25519::Keypair::generate();
+    let mut config = CortexConfig::default();
+    config.trust.roots.push(TrustRootConfig {
+        domain: "acme".into(),
+        public_key: encode_public_key(&org.public()),
+    });
+    config.trust.require_certificate = true;
+
+    let mut harness = MeshHarness::with_config(config);
+    let bootstrap = harness.spawn_node(NodeRole::Bootstrap).await.unwrap();
+    let stranger = harness.spawn_node(NodeRole::Light).await.unwrap();
+    let key = Keypair::generate_ed25519();
+    let cert = NodeCertificate::issue(&org, "acme", &key.public().to_peer_id(), vec!["shard_executor".into()], YEAR);
+    let member = harness
+        .spawn_node_with(NodeRole::Light, key, |options| options.certificate = Some(cert.clone()))
+        .await
+        .unwrap();
+    harness.wait_for_connections(TIMEOUT).await.unwrap();
+
+    let member_id = harness.node(member).unwrap().peer_id();
+    let stranger_id = harness.node(stranger).unwrap().peer_id();
+    let admitted = harness
+        .announce_until(TIMEOUT, |h| h.node(bootstrap).unwrap().knows(&member_id))
+        .await
+        .unwrap();
+    assert!(admitted);
+    let registry = harness.node(bootstrap).unwrap().registry();
+    assert!(!registry.nodes.contains_key(&stranger_id.to_string()));
+    assert_eq!(registry.in_trust_domain("acme", Some("shard_executor")), vec![member_id.to_string()]);
+
+    // Révocation diffusée depuis le bootstrap: le membre perd sa confiance
+    let list = RevocationList::sign(&org, "acme", vec![member_id.to_string()], vec![]);
+    harness.node(bootstrap).unwrap().handle.apply_revocation(list).await.unwrap();
+    let revoked = harness
+        .wait_until(TIMEOUT, |h| h.node(bootstrap).unwrap().registry().in_trust_domain("acme", None).is_empty())
+        .await;
+    assert!(revoked);
+    harness.shutdown().await.unwrap();
+}
@@ -0,0 +1,135 @@
+// Chargement des poids: mmap safetensors/GGUF, manifeste de shard et blocs quantifiés
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use cortex_id::weights::{DType, ShardManifest, ShardWeights, WeightFile, WeightFormat, MANIFEST_FILE};
+use half::f16;
+use safetensors::tensor::TensorView;
+use safetensors::Dtype;
+
+mod common;
+use common::temp_dir;
+
+fn write_safetensors(path: &Path, tensors: &[(&str, Vec<usize>, Vec<f32>)]) {
+    let bytes: Vec<(String, Vec<usize>, Vec<u8>)> = tensors.iter()
+        .map(|(name, shape, data)| (name.to_string(), shape.clone(), data.iter().flat_map(|v| v.to_le_bytes()).collect()))
+        .collect();
+    let views: Vec<(&str, TensorView)> = bytes.iter()
+        .map(|(name, shape, data)| (name.as_str(), TensorView::new(Dtype::F32, shape.clone(), data).unwrap()))
+        .collect();
+    safetensors::serialize_to_file(views, &None, path).unwrap();
+}
+
+/// GGUF v3 minimal: une métadonnée et des tenseurs `(nom, dims ggml, type ggml, octets)`
+fn write_gguf(path: &Path, tensors: &[(&str, Vec<u64>, u32, Vec<u8>)]) {
+    fn string(out: &mut Vec<u8>, s: &str) {
+        out.extend((s.len() as u64).to_le_bytes());
+        out.extend(s.as_bytes());
+    }
+    let mut out = b"GGUF".to_vec();
+    out.extend(3u32.to_le_bytes());
+    out.extend((tensors.len() as u64).to_le_bytes());
+    out.extend(1u64.to_le_bytes());
+    string(&mut out, "general.name");
+    out.extend(8u32.to_le_bytes());
+    string(&mut out, "tiny");
+    let mut offset = 0u64;
+    for (name, dims, kind, data) in tensors {
+        string(&mut out, name);
+        out.extend((dims.len() as u32).to_le_bytes());
+        for d in dims {
+            out.extend(d.to_le_bytes());
+        }
+        out.extend(kind.to_le_bytes());
+        out.extend(offset.to_le_bytes());
+        offset += (data.len() as u64).div_ceil(32) * 32;
+    }
+    out.resize(out.len().div_ceil(32) * 32, 0);
+    for (_, _, _, data) in tensors {
+        out.extend(data);
+        out.resize(out.len().div_ceil(32) * 32, 0);
+    }
+    fs::write(path, out).unwrap();
+}
+
+#[test]
+fn manifest_selects_and_validates_tensors() {
+    let dir = temp_dir("manifest");
+    write_safetensors(&dir.join("part-1.safetensors"), &[
+        ("model.layers.0.w", vec![2, 2], vec![1.0, 2.0, 3.0, 4.0]),
+        ("model.layers.1.w", vec![2, 2], vec![5.0, 6.0, 7.0, 8.0]),
+    ]);
+    write_safetensors(&dir.join("part-2.safetensors"), &[("model.norm.weight", vec![2], vec![0.5, 0.25])]);
+
+    let files = vec![PathBuf::from("part-1.safetensors"), PathBuf::from("part-2.safetensors")];
+    let manifest = ShardManifest::describe("tiny/1-2", &dir, &files, |name| !name.starts_with("model.layers.0.")).unwrap();
+    assert_eq!(manifest.tensors.len(), 2);
+    manifest.save(&dir.join(MANIFEST_FILE)).unwrap();
+
+    // Seuls les tenseurs du manifeste sont accessibles
+    let weights = ShardWeights::open(&dir).unwrap();
+    assert_eq!(weights.names().collect::<Vec<_>>(), vec!["model.layers.1.w", "model.norm.weight"]);
+    assert!(weights.tensor("model.layers.0.w").is_err());
+    let tensor = weights.tensor("model.layers.1.w").unwrap();
+    assert_eq!((tensor.dtype, tensor.shape), (DType::F32, &[2usize, 2][..]));
+    assert_eq!(tensor.to_f32().unwrap(), vec![5.0, 6.0, 7.0, 8.0]);
+    assert_eq!(weights.size_bytes(), 24);
+
+    // Forme, type et empreinte sont vérifiés au chargement
+    let mut wrong_shape = manifest.clone();
+    wrong_shape.tensors[0].shape = Some(vec![4]);
+    assert!(ShardWeights::from_manifest(&wrong_shape, &dir).is_err());
+    let mut wrong_dtype = manifest.clone();
+    wrong_dtype.tensors[0].dtype = Some(DType::F16);
+    assert!(ShardWeights::from_manifest(&wrong_dtype, &dir).is_err());
+    write_safetensors(&dir.join("part-2.safetensors"), &[("model.norm.weight", vec![2], vec![0.5, 0.125])]);
+    assert!(ShardWeights::from_manifest(&manifest, &dir).is_err());
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn gguf_exposes_quantized_blocks() {
+    let dir = temp_dir("gguf");
+    let path = dir.join("tiny.gguf");
+
+    // Q8_0: échelle 0.5, quants -16..16
+    let mut q8 = f16::from_f32(0.5).to_le_bytes().to_vec();
+    q8.extend((0..32).map(|i| (i as i8 - 16) as u8));
+    // Q4_0: échelle 2, octet j = (j % 16) | (15 - j % 16) << 4
+    let mut q4 = f16::from_f32(2.0).to_le_bytes().to_vec();
+    q4.extend((0..16u8).map(|j| j | ((15 - j) << 4)));
+    let f32s: Vec<u8> = [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+    write_gguf(&path, &[
+        ("q8", vec![32, 1], 8, q8),
+        ("q4", vec![32], 2, q4),
+        ("dense", vec![3, 2], 0, f32s),
+        ("other", vec![256], 12, vec![0; 144]),
+    ]);
+
+    let file = WeightFile::open(&path).unwrap();
+    assert_eq!(file.format(), WeightFormat::Gguf);
+    assert_eq!(file.metadata("general.name"), Some("tiny"));
+
+    let dense = file.tensor("dense").unwrap();
+    assert_eq!(dense.shape, &[2, 3]);
+    assert_eq!(dense.to_f32().unwrap(), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+    let q8 = file.tensor("q8").unwrap();
+    assert_eq!((q8.dtype, q8.shape), (DType::Q8_0, &[1usize, 32][..]));
+    let blocks: Vec<_> = q8.q8_0_blocks().unwrap().collect();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].quants[0], -16);
+    assert_eq!(q8.to_f32().unwrap()[31], 7.5);
+    assert!(q8.q4_0_blocks().is_err());
+
+    let q4 = file.tensor("q4").unwrap().to_f32().unwrap();
+    let expected: Vec<f32> = (0..16).map(|j| (j - 8) as f32 * 2.0)
+        .chain((0..16).map(|j| (7 - j) as f32 * 2.0))
+        .collect();
+    assert_eq!(q4, expected);
+
+    // Les types non pris en charge ne gênent que si on les lit
+    assert!(file.contains("other"));
+    assert!(file.tensor("other").is_err());
+    fs::remove_dir_all(dir).ok();
+}
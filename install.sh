@@ -12,7 +12,9 @@ HOSTNAME=$(hostname)
 export HOSTNAME
 
 mkdir -p "$CORTEX_DIR"
-chmod 777 "$CORTEX_DIR"
+# La clé d'identité y est stockée: accès réservé au propriétaire
+chown "$REAL_USER" "$CORTEX_DIR"
+chmod 700 "$CORTEX_DIR"
 
 # Vérifie que Docker est installé
 if ! command -v docker &>/dev/null; then
@@ -30,10 +32,10 @@ fi
 echo "==> Initialisation du nœud Cortex..."
 
 # Génération d'identité si manquante
-if [ ! -f "$IDENTITY_FILE" ]; then
+if [ ! -f "$IDENTITY_FILE" ] && [ ! -f "$IDENTITY_FILE.enc" ]; then
     echo "==> Génération d'une identité..."
     docker compose build cortex-id
-    docker compose run --rm --user "$(id -u):$(id -g)" -v "$CORTEX_DIR":/home/cortexuser/.cortex cortex-id
+    docker compose run --rm --user "$(id -u):$(id -g)" -v "$CORTEX_DIR":/home/cortexuser/.cortex cortex-id identity init
 else
     echo "==> Identité déjà présente : $IDENTITY_FILE"
 fi
@@ -74,7 +76,17 @@ roles:
   monitor: true
 
 mesh:
-  pubsub_topic: cortex-v1
+  name: default
+  protocol_version: 2
+  min_protocol_version: 1
+  legacy_compat: true
+
+limits:
+  max_established_incoming: 64
+  max_established_outgoing: 64
+  max_established_per_peer: 2
+  max_memory_fraction: 0.8
+  idle_connection_timeout_secs: 60
 EOF
 
 # Lancement du conteneur